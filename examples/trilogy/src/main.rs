@@ -59,9 +59,12 @@ fn main() {
       }
     }";
 
-    match graphql::handle_query(query, HashMap::new(), Service) {
+    match graphql::handle_query(query, None, HashMap::new(), Service) {
         Ok(result) => println!("{}", result),
-        Err(err) => println!("{:?}", err),
+        Err(err) => match err.to_response() {
+            Some(response) => println!("{}", response),
+            None => println!("{:?}", err),
+        },
     }
 }
 
@@ -90,13 +93,17 @@ impl AbstractQuery for DbQuery {
     type Human = Human;
     type Episode = Episode;
 
+    // `Character` is an interface, so its concrete type is an enum over the
+    // types which implement it. Build a member and convert it.
     fn hero(&self, _episode: Option<Episode>) -> QlResult<Option<Character>> {
-        Ok(Some(Character {
+        let human = Human {
             id: Id("0".to_owned()),
             name: "Bob".to_owned(),
             friends: Some(vec![]),
             appearsIn: vec![],
-        }))
+            homePlanet: None,
+        };
+        Ok(Some(human.to_Character()?))
     }
 
     fn human(&self, _id: Id) -> QlResult<Option<Human>> {
@@ -112,15 +119,17 @@ impl AbstractQuery for DbQuery {
 
 ImplQuery!(DbQuery);
 
-// Example of overriding the default implementation:
+// Example of overriding the default implementation. An interface has no
+// fields of its own to resolve, so the replacement must implement `Resolve`
+// itself:
 // use types::{query, result};
 // struct MyCharacter;
 
 // ImplCharacter!(MyCharacter);
 
-// impl AbstractCharacter for MyCharacter {
-//     fn resolve_field(&self, field: &query::Field) -> QlResult<result::Value> {
-//         // magic the field out of thin air
+// impl Resolve for MyCharacter {
+//     fn resolve(&self, fields: &[query::Selection]) -> QlResult<result::Value> {
+//         // magic the fields out of thin air
 //         unimplemented!();
 //     }
 // }
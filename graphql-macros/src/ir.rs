@@ -1,4 +1,4 @@
-use graphql::types::{schema, Name};
+use graphql::types::{query, schema, Name};
 use std::collections::{HashMap, HashSet};
 use std::iter::FromIterator;
 use proc_macro::{quote, TokenStream};
@@ -6,49 +6,78 @@ use proc_macro::{quote, TokenStream};
 #[derive(Clone, Debug)]
 pub struct Schema {
     pub items: HashMap<Name, Item>,
+    pub directives: Vec<Directive>,
 }
 
 #[derive(Clone, Debug)]
 pub enum Item {
     Object(Object),
+    Interface(Interface),
     // TODO Do we need this?
     Schema(Object),
+    // The root type of subscriptions, whose fields return streams of values.
+    Subscription(Object),
     Enum(Enum),
+    InputObject(InputObject),
+    Union(Union),
+    Scalar(Scalar),
 }
 
 impl Item {
     pub fn name(&self) -> &Name {
         match *self {
             Item::Object(ref o) => &o.name,
+            Item::Interface(ref i) => &i.name,
             Item::Schema(ref o) => &o.name,
+            Item::Subscription(ref o) => &o.name,
             Item::Enum(ref e) => &e.name,
-        }
-    }
-
-    pub fn assert_object(&self) -> &Object {
-        match *self {
-            Item::Object(ref obj) => obj,
-            _ => panic!("expected object, found {:?}", self),
+            Item::InputObject(ref i) => &i.name,
+            Item::Union(ref u) => &u.name,
+            Item::Scalar(ref s) => &s.name,
         }
     }
 
     pub fn emit_schema(&self) -> TokenStream {
         match *self {
             Item::Object(ref o) => o.emit_schema(),
-            Item::Schema(_) => Schema::emit_schema(),
+            Item::Interface(ref i) => i.emit_schema(),
+            Item::Schema(ref o) => Schema::emit_schema(o.has_field("mutation"), o.has_field("subscription")),
+            Item::Subscription(ref o) => o.emit_schema(),
             Item::Enum(ref e) => e.emit_schema(),
+            Item::InputObject(ref i) => i.emit_schema(),
+            Item::Union(ref u) => u.emit_schema(),
+            Item::Scalar(ref s) => s.emit_schema(),
         }
     }
 
     pub fn emit_assoc_ty(&self) -> TokenStream {
         match *self {
             Item::Object(ref o) => o.emit_assoc_ty(),
+            Item::Interface(ref i) => i.emit_assoc_ty(),
             Item::Schema(_) => quote!(),
+            Item::Subscription(ref o) => o.emit_subscription_assoc_ty(),
             Item::Enum(ref e) => e.emit_assoc_ty(),
+            Item::InputObject(ref i) => i.emit_assoc_ty(),
+            Item::Union(ref u) => u.emit_assoc_ty(),
+            Item::Scalar(ref s) => s.emit_assoc_ty(),
         }
     }
 }
 
+impl Schema {
+    // True if the `schema` block has a `mutation` root.
+    pub fn has_mutation(&self) -> bool {
+        self.items.values().any(|i| match *i {
+            Item::Schema(ref o) => o.has_field("mutation"),
+            _ => false,
+        })
+    }
+
+    pub fn has_subscription(&self) -> bool {
+        self.items.values().any(|i| matches!(*i, Item::Subscription(_)))
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Object {
     pub name: Name,
@@ -60,22 +89,89 @@ pub struct Object {
     pub has_fields: bool,
     // True if there are any function fields.
     pub has_fns: bool,
+    pub description: Option<String>,
+}
+
+impl Object {
+    pub fn has_field(&self, name: &str) -> bool {
+        self.fields.iter().any(|f| f.name.0 == name)
+    }
+}
+
+// Lowered to a Rust enum with one variant for each object type which
+// implements the interface, like a union, so that fragments on those types can
+// be resolved.
+#[derive(Clone, Debug)]
+pub struct Interface {
+    pub name: Name,
+    pub implements: Vec<Name>,
+    pub fields: Vec<Field>,
+    // The object types which implement the interface, directly or indirectly.
+    pub members: Vec<Name>,
+    pub description: Option<String>,
 }
 
 #[derive(Clone, Debug)]
 pub struct Enum {
     pub name: Name,
     pub variants: Vec<Variant>,
+    pub description: Option<String>,
 }
 
 #[derive(Clone, Debug)]
-pub struct Variant(pub Name);
+pub struct Variant {
+    pub name: Name,
+    pub description: Option<String>,
+    pub directives: Vec<query::Directive>,
+}
+
+#[derive(Clone, Debug)]
+pub struct InputObject {
+    pub name: Name,
+    pub fields: Vec<Field>,
+    pub description: Option<String>,
+}
+
+// Lowered to a Rust enum with one variant for each member.
+#[derive(Clone, Debug)]
+pub struct Union {
+    pub name: Name,
+    pub members: Vec<Name>,
+    pub description: Option<String>,
+}
+
+// Custom scalars are always implemented by the user.
+#[derive(Clone, Debug)]
+pub struct Scalar {
+    pub name: Name,
+    pub description: Option<String>,
+}
 
 #[derive(Clone, Debug)]
 pub struct Field {
     pub name: Name,
-    pub args: Vec<(Name, Type)>,
+    pub args: Vec<Arg>,
     pub ty: Type,
+    pub description: Option<String>,
+    pub directives: Vec<query::Directive>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Arg {
+    pub name: Name,
+    pub ty: Type,
+    pub default: Option<query::Value>,
+    pub description: Option<String>,
+    pub directives: Vec<query::Directive>,
+}
+
+// A directive defined in the schema.
+#[derive(Clone, Debug)]
+pub struct Directive {
+    pub name: Name,
+    pub args: Vec<Arg>,
+    pub locations: Vec<schema::DirectiveLocation>,
+    pub description: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -88,6 +184,7 @@ impl Type {
     fn name(&self) -> Option<Name> {
         match self.kind {
             TypeKind::Name(ref n) => Some(n.clone()),
+            TypeKind::Array(ref t) => t.name(),
             _ => None,
         }
     }
@@ -97,42 +194,83 @@ impl Type {
 pub enum TypeKind {
     String,
     Id,
+    Int,
+    Float,
+    Boolean,
     Name(Name),
     Array(Box<Type>),
 }
 
 pub fn lower_schema(schema: &schema::Schema) -> Schema {
+    let subscription = schema
+        .items
+        .get(&Name(schema::SCHEMA_NAME.to_owned()))
+        .and_then(|s| s.fields().iter().find(|f| f.name.0 == "subscription"))
+        .and_then(|f| f.ty.as_name_null());
     Schema {
         items: schema
             .items
             .iter()
-            .map(|(n, i)| (n.clone(), lower_item(n, i)))
+            .map(|(n, i)| match *i {
+                schema::Item::Object(ref o) if Some(n) == subscription => {
+                    (n.clone(), Item::Subscription(lower_subscription(n, o)))
+                }
+                _ => (n.clone(), lower_item(n, i, schema)),
+            })
+            .collect(),
+        directives: schema
+            .directives
+            .iter()
+            .map(|d| Directive {
+                name: d.name.clone(),
+                args: d.args.iter().map(lower_arg).collect(),
+                locations: d.locations.clone(),
+                description: d.description.clone(),
+            })
             .collect(),
     }
 }
 
-fn lower_item(name: &Name, item: &schema::Item) -> Item {
+fn lower_item(name: &Name, item: &schema::Item, schema: &schema::Schema) -> Item {
     match *item {
-        schema::Item::Schema(ref i) => Item::Schema(lower_interface(name, i)),
+        schema::Item::Schema(ref i) => Item::Schema(lower_schema_item(name, i)),
         schema::Item::Object(ref o) => Item::Object(lower_object(name, o)),
-        schema::Item::Interface(ref i) => Item::Object(lower_interface(name, i)),
+        schema::Item::Interface(ref i) => Item::Interface(Interface {
+            name: name.clone(),
+            implements: i.implements.clone(),
+            fields: i.fields.iter().map(lower_field).collect(),
+            members: schema.possible_types(name).into_iter().cloned().collect(),
+            description: i.description.clone(),
+        }),
         schema::Item::Enum(ref e) => Item::Enum(lower_enum(name, e)),
+        schema::Item::InputObject(ref i) => Item::InputObject(lower_input_object(name, i)),
+        schema::Item::Union(ref u) => Item::Union(Union {
+            name: name.clone(),
+            members: u.members.clone(),
+            description: u.description.clone(),
+        }),
+        schema::Item::Scalar(ref s) => Item::Scalar(Scalar {
+            name: name.clone(),
+            description: s.description.clone(),
+        }),
     }
 }
 
-fn lower_interface(name: &Name, interface: &schema::Interface) -> Object {
+// The `schema` item, whose fields are the root types.
+fn lower_schema_item(name: &Name, interface: &schema::Interface) -> Object {
     let fields: Vec<Field> = interface.fields.iter().map(|f| lower_field(f)).collect();
     let has_fields = fields.iter().any(|f| f.args.is_empty());
     let has_fns = fields.iter().any(|f| !f.args.is_empty());
-    let mut abs_names = HashSet::new();
+    let mut abs_names = HashSet::from_iter(interface.implements.clone());
     add_types_from_fields(&fields, &mut abs_names);
     Object {
         name: name.clone(),
-        implements: vec![],
+        implements: interface.implements.clone(),
         fields,
         abs_names,
         has_fields,
         has_fns,
+        description: interface.description.clone(),
     }
 }
 
@@ -149,6 +287,27 @@ fn lower_object(name: &Name, object: &schema::Object) -> Object {
         abs_names,
         has_fields,
         has_fns,
+        description: object.description.clone(),
+    }
+}
+
+// Every field of a subscription is a function which returns a stream, so all
+// types used by its fields must be kept abstract.
+fn lower_subscription(name: &Name, object: &schema::Object) -> Object {
+    let fields: Vec<Field> = object.fields.iter().map(lower_field).collect();
+    let mut abs_names = HashSet::new();
+    for f in &fields {
+        abs_names.extend(f.ty.name());
+        abs_names.extend(f.args.iter().filter_map(|a| a.ty.name()));
+    }
+    Object {
+        name: name.clone(),
+        implements: vec![],
+        fields,
+        abs_names,
+        has_fields: false,
+        has_fns: true,
+        description: object.description.clone(),
     }
 }
 
@@ -160,7 +319,7 @@ fn add_types_from_fields(fields: &[Field], abs_names: &mut HashSet<Name>) {
             }
         }
         for a in &f.args {
-            if let Some(n) = a.1.name() {
+            if let Some(n) = a.ty.name() {
                 abs_names.insert(n);
             }
         }
@@ -170,19 +329,43 @@ fn add_types_from_fields(fields: &[Field], abs_names: &mut HashSet<Name>) {
 fn lower_enum(name: &Name, e: &schema::Enum) -> Enum {
     Enum {
         name: name.clone(),
-        variants: e.variants.iter().map(|n| Variant(n.clone())).collect(),
+        variants: e.variants
+            .iter()
+            .map(|v| Variant {
+                name: v.name.clone(),
+                description: v.description.clone(),
+                directives: v.directives.clone(),
+            })
+            .collect(),
+        description: e.description.clone(),
+    }
+}
+
+fn lower_input_object(name: &Name, input: &schema::InputObject) -> InputObject {
+    InputObject {
+        name: name.clone(),
+        fields: input.fields.iter().map(lower_field).collect(),
+        description: input.description.clone(),
     }
 }
 
 fn lower_field(field: &schema::Field) -> Field {
     Field {
         name: field.name.clone(),
-        args: field
-            .args
-            .iter()
-            .map(|&(ref n, ref t)| (n.clone(), lower_type(t)))
-            .collect(),
+        args: field.args.iter().map(lower_arg).collect(),
         ty: lower_type(&field.ty),
+        description: field.description.clone(),
+        directives: field.directives.clone(),
+    }
+}
+
+fn lower_arg(arg: &schema::Argument) -> Arg {
+    Arg {
+        name: arg.name.clone(),
+        ty: lower_type(&arg.ty),
+        default: arg.default.clone(),
+        description: arg.description.clone(),
+        directives: arg.directives.clone(),
     }
 }
 
@@ -191,6 +374,9 @@ fn lower_type(ty: &schema::Type) -> Type {
         kind: match ty.kind {
             schema::TypeKind::String => TypeKind::String,
             schema::TypeKind::Id => TypeKind::Id,
+            schema::TypeKind::Int => TypeKind::Int,
+            schema::TypeKind::Float => TypeKind::Float,
+            schema::TypeKind::Boolean => TypeKind::Boolean,
             schema::TypeKind::Name(ref n) => TypeKind::Name(n.clone()),
             schema::TypeKind::Array(ref ty) => TypeKind::Array(Box::new(lower_type(ty))),
         },
@@ -10,16 +10,18 @@ extern crate rustfmt_nightly as rustfmt;
 
 use proc_macro::{quote, Span, Term, TokenNode, TokenStream, TokenTree};
 use graphql::{parse_schema, QlResult};
-use graphql::types::Name;
+use graphql::types::{query, schema, Name};
 
 mod ir;
 
 #[proc_macro]
 pub fn schema(input: TokenStream) -> TokenStream {
-    // TODO to_string loses whitespace from the input, so we must use commas
-    // Worse, Rust seems to insert newlines randomly into the string, so we must
-    // replace them.
-    let schema = parse_schema(&input.to_string().replace('\n', " ")).expect("Error parsing schema");
+    let schema = parse_schema(&idl_text(&input.to_string())).expect("Error parsing schema");
+    // The whole schema is in one macro invocation, so there is nothing else
+    // for these to extend.
+    if let Some(ext) = schema.extensions.first() {
+        panic!("Extended type not found: {}", ext.name.0);
+    }
     let lowered = ir::lower_schema(&schema);
 
     let mut result = vec![];
@@ -42,6 +44,63 @@ pub fn schema(input: TokenStream) -> TokenStream {
     result.to_string().parse().unwrap()
 }
 
+// TODO to_string loses whitespace from the input, so we must use commas
+// Worse, Rust seems to insert newlines randomly into the string, so we must
+// replace them. Newlines inside block strings are kept, since they are part
+// of the value.
+//
+// Rust lexes a block string, `"""text"""`, as three string literals,
+// `""`, `"text"` and `""`, which we join back together. So a block string may
+// not contain quotes.
+fn idl_text(input: &str) -> String {
+    // Alternating text between string literals and string literals, starting
+    // and ending with text.
+    let mut chunks = vec![];
+    let mut rest = input;
+    while let Some(start) = rest.find('"') {
+        let len = string_literal_len(&rest[start..]);
+        chunks.push(&rest[..start]);
+        chunks.push(&rest[start..start + len]);
+        rest = &rest[start + len..];
+    }
+    chunks.push(rest);
+
+    let mut result = String::new();
+    let mut i = 0;
+    while i < chunks.len() {
+        let chunk = chunks[i];
+        let is_literal = i % 2 == 1;
+        if is_literal && chunk == "\"\"" && i + 4 < chunks.len() && chunks[i + 4] == "\"\""
+            && chunks[i + 1].trim().is_empty() && chunks[i + 3].trim().is_empty()
+        {
+            // The middle literal brings the third quote of each delimiter.
+            result.push_str("\"\"");
+            result.push_str(chunks[i + 2]);
+            result.push_str("\"\"");
+            i += 5;
+            continue;
+        }
+        result.push_str(&chunk.replace('\n', " "));
+        i += 1;
+    }
+    result
+}
+
+// The length of the string literal at the start of `s`, including its quotes.
+fn string_literal_len(s: &str) -> usize {
+    let mut chars = s.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '"' => return i + 1,
+            _ => {}
+        }
+    }
+    s.len()
+}
+
 fn emit_schema(schema: &ir::Schema) -> QlResult<TokenStream> {
     let mut result = vec![];
     for item in schema.items.values() {
@@ -57,7 +116,12 @@ fn emit_schema(schema: &ir::Schema) -> QlResult<TokenStream> {
 fn emit_item(item: &ir::Item, schema: &ir::Schema) -> QlResult<TokenStream> {
     match *item {
         ir::Item::Object(ref o) => emit_object(o, schema),
+        ir::Item::Interface(ref i) => emit_interface(i),
+        ir::Item::Subscription(ref o) => emit_subscription(o),
         ir::Item::Enum(ref e) => emit_enum(e, schema),
+        ir::Item::InputObject(ref i) => emit_input_object(i, schema),
+        ir::Item::Union(ref u) => emit_union(u),
+        ir::Item::Scalar(ref s) => emit_scalar(s),
         _ => unreachable!(),
     }
 }
@@ -101,7 +165,43 @@ fn emit_enum(item: &ir::Enum, _schema: &ir::Schema) -> QlResult<TokenStream> {
     Ok(builder.finish())
 }
 
-fn emit_object(item: &ir::Object, schema: &ir::Schema) -> QlResult<TokenStream> {
+fn emit_input_object(item: &ir::InputObject, _schema: &ir::Schema) -> QlResult<TokenStream> {
+    let mut builder = TokenBuilder::new();
+    // pub trait AbstractFoo: Reflect + FromValue
+    builder.push(item.emit_abstract_trait());
+    // pub struct Foo
+    builder.push(item.emit_concrete_struct());
+    // impl Reflect for Foo
+    builder.push(item.emit_impl_reflect());
+    // impl FromValue for Foo
+    builder.push(item.emit_impl_from_value());
+    // impl AbstractFoo for Foo
+    builder.push(item.emit_abstract_impl());
+
+    Ok(builder.finish())
+}
+
+fn emit_interface(item: &ir::Interface) -> QlResult<TokenStream> {
+    let mut builder = TokenBuilder::new();
+    // pub trait AbstractFoo: Reflect + Resolve
+    builder.push(item.emit_abstract_trait());
+    // pub macro ImplFoo
+    //     impl schema::Reflect for $concrete
+    //     impl AbstractFoo for $concrete
+    builder.push(item.emit_impl_macro());
+    // pub enum Foo
+    builder.push(item.emit_concrete_enum());
+    // ImplFoo!(Foo);
+    builder.push(item.emit_impl_macro_use());
+    // impl Resolve for Foo
+    builder.push(item.emit_impl_resolve());
+    // impl Foo { fn to_Bar }
+    builder.push(item.emit_conversions());
+
+    Ok(builder.finish())
+}
+
+fn emit_object(item: &ir::Object, _schema: &ir::Schema) -> QlResult<TokenStream> {
     let mut builder = TokenBuilder::new();
     // pub trait AbstractCharacter: ResolveObject
     builder.push(item.emit_abstract_trait());
@@ -119,13 +219,57 @@ fn emit_object(item: &ir::Object, schema: &ir::Schema) -> QlResult<TokenStream>
             // impl ResolveObject for Foo
             builder.push(item.emit_resolve_object_impl());
             // impl AbstractFoo for Foo
-            builder.push(item.emit_abstract_impl(schema));
+            builder.push(item.emit_abstract_impl());
         }
     }
 
     Ok(builder.finish())
 }
 
+fn emit_scalar(item: &ir::Scalar) -> QlResult<TokenStream> {
+    let mut builder = TokenBuilder::new();
+    // pub trait AbstractFoo: ResolveScalar + Resolve + FromValue
+    builder.push(item.emit_abstract_trait());
+    // pub macro ImplFoo
+    //     impl schema::Reflect for $concrete
+    //     impl Resolve for $concrete
+    //     impl FromValue for $concrete
+    //     impl AbstractFoo for $concrete
+    builder.push(item.emit_impl_macro());
+
+    Ok(builder.finish())
+}
+
+fn emit_union(item: &ir::Union) -> QlResult<TokenStream> {
+    let mut builder = TokenBuilder::new();
+    // pub trait AbstractFoo: Reflect + Resolve
+    builder.push(item.emit_abstract_trait());
+    // pub macro ImplFoo
+    //     impl schema::Reflect for $concrete
+    //     impl AbstractFoo for $concrete
+    builder.push(item.emit_impl_macro());
+    // pub enum Foo
+    builder.push(item.emit_concrete_enum());
+    // ImplFoo!(Foo);
+    builder.push(item.emit_impl_macro_use());
+    // impl Resolve for Foo
+    builder.push(item.emit_impl_resolve());
+
+    Ok(builder.finish())
+}
+
+fn emit_subscription(item: &ir::Object) -> QlResult<TokenStream> {
+    let mut builder = TokenBuilder::new();
+    // pub trait AbstractSubscription: Reflect + Subscribe
+    builder.push(item.emit_subscription_trait());
+    // pub macro ImplSubscription
+    //     impl schema::Reflect for $concrete
+    //     impl result::Subscribe for $concrete
+    builder.push(item.emit_subscription_impl_macro());
+
+    Ok(builder.finish())
+}
+
 fn ident(s: &str) -> TokenTree {
     TokenTree {
         span: Span::call_site(),
@@ -133,6 +277,63 @@ fn ident(s: &str) -> TokenTree {
     }
 }
 
+// `#[doc = "..."]` if the IDL has a description, so it shows up in rustdoc.
+fn emit_doc(description: &Option<String>) -> TokenStream {
+    match *description {
+        Some(ref d) => {
+            let d = ident(&format!("{:?}", d));
+            quote!(#[doc = $d])
+        }
+        None => quote!(),
+    }
+}
+
+// The description as an `Option<String>` for the emitted schema.
+fn emit_description(description: &Option<String>) -> TokenStream {
+    match *description {
+        Some(ref d) => {
+            let d = ident(&format!("{:?}", d));
+            quote!(Some($d.to_owned()))
+        }
+        None => quote!(None),
+    }
+}
+
+// `#[deprecated]` if the field or enum value is marked `@deprecated`.
+fn emit_deprecated(directives: &[query::Directive]) -> TokenStream {
+    match schema::deprecation_reason(directives) {
+        Some(reason) => {
+            let reason = ident(&format!("{:?}", reason));
+            quote!(#[deprecated(note = $reason)])
+        }
+        None => quote!(),
+    }
+}
+
+// The directives used on part of the schema, as a `Vec<query::Directive>`.
+fn emit_directives(directives: &[query::Directive]) -> TokenStream {
+    let directives: TokenStream = directives
+        .iter()
+        .map(|d| {
+            let name_str = ident(&format!("\"{}\"", d.name.0));
+            let args: TokenStream = d.args
+                .iter()
+                .map(|(n, v)| {
+                    let n = ident(&format!("\"{}\"", n.0));
+                    let v = emit_value(v);
+                    quote!((Name($n.to_owned()), $v),)
+                })
+                .collect();
+            quote!(query::Directive {
+                name: Name($name_str.to_owned()),
+                args: vec![$args],
+                span: ::graphql::types::Span::dummy(),
+            },)
+        })
+        .collect();
+    quote!(vec![$directives])
+}
+
 // TODO can we share code between Enum and Object?
 impl ir::Enum {
     fn name_t(&self) -> TokenTree {
@@ -153,15 +354,478 @@ impl ir::Enum {
 
     fn emit_abstract_trait(&self) -> TokenStream {
         let abs_name_t = self.abs_name_t();
-        // QUESTION hygiene problem? Can't find graphql
-        quote!(pub trait $abs_name_t: ::graphql::types::schema::ResolveEnum {})
+        let doc = emit_doc(&self.description);
+        // QUESTION hygiene problem? Can't find graphql
+        quote!(
+            $doc
+            pub trait $abs_name_t: ::graphql::types::schema::ResolveEnum {}
+        )
+    }
+
+    fn emit_impl_macro(&self) -> TokenStream {
+        let impl_name_t = self.impl_name_t();
+        let abs_name_t = self.abs_name_t();
+        let name_str = self.name_str();
+        let variants: TokenStream = self.variants.iter().map(|v| v.emit_schema()).collect();
+        let description = emit_description(&self.description);
+
+        quote!(
+            pub macro $impl_name_t($$concrete: ident) {
+                impl schema::Reflect for $$concrete {
+                    const NAME: &'static str = $name_str;
+
+                    fn schema() -> schema::Item {
+                        schema::Item::Enum(schema::Enum {
+                            variants: vec![$variants],
+                            description: $description,
+                            directives: vec![],
+                        })
+                    }
+                }
+                impl ResolveEnum for $$concrete {}
+                impl $abs_name_t for $$concrete {}
+            }
+        )
+    }
+
+    fn emit_concrete_enum(&self) -> TokenStream {
+        let name_t = self.name_t();
+        let variants: TokenStream = self.variants.iter().map(|v| v.emit_decl()).collect();
+        let doc = emit_doc(&self.description);
+
+        quote!(
+            $doc
+            #[allow(non_snake_case)]
+            #[derive(Clone, Debug)]
+            pub enum $name_t {
+                $variants
+            }
+        )
+    }
+
+    fn emit_impl_macro_use(&self) -> TokenStream {
+        let name_t = self.name_t();
+        let impl_name_t = self.impl_name_t();
+
+        quote!($impl_name_t!($name_t);)
+    }
+
+    fn emit_impl_from_value(&self) -> TokenStream {
+        let name_t = self.name_t();
+        let arms = self.variants
+            .iter()
+            .map(|v| v.emit_from_str_arm(name_t.clone()));
+        let expect_str = ident(&format!("\"{}\"", self.name.0));
+        let arms: TokenStream = arms.chain(
+            Some(quote!(
+                    _ => return Err(QlError::TranslationError(
+                            format!("{:?}", value.kind),
+                            $expect_str.to_owned()
+                         )),
+                )).into_iter(),
+        ).collect();
+
+        quote!(
+            #[allow(deprecated)]
+            impl FromValue for $name_t {
+                fn from(value: &query::Value) -> QlResult<$name_t> {
+                    Ok(match &*<::graphql::types::Name as FromValue>::from(value)?.0 {
+                        $arms
+                    })
+                }
+            }
+        )
+    }
+
+    fn emit_impl_resolve(&self) -> TokenStream {
+        let name_t = self.name_t();
+        let variants: TokenStream = self.variants
+            .iter()
+            .map(|v| v.emit_resolve_arm(name_t.clone()))
+            .collect();
+
+        quote!(
+            #[allow(deprecated)]
+            impl Resolve for $name_t {
+                fn resolve(&self, _fields: &[query::Selection]) -> QlResult<result::Value> {
+                    Ok(match *self {
+                        $variants
+                    })
+                }
+            }
+        )
+    }
+
+    fn emit_assoc_ty(&self) -> TokenStream {
+        let name_t = self.name_t();
+        let abs_name_t = self.abs_name_t();
+        quote!(type $name_t: $abs_name_t + FromValue = $name_t;)
+    }
+
+    fn emit_schema(&self) -> TokenStream {
+        let name_t = self.name_t();
+        quote!(Name(<Self as Root>::$name_t::NAME.to_owned()), <Self as Root>::$name_t::schema())
+    }
+}
+
+// Input objects are only ever passed in to resolvers, so unlike objects and
+// enums there is no macro for implementing them on a user's type.
+impl ir::InputObject {
+    fn name_t(&self) -> TokenTree {
+        ident(&self.name.0)
+    }
+
+    fn name_str(&self) -> TokenTree {
+        ident(&format!("\"{}\"", self.name.0))
+    }
+
+    fn abs_name_t(&self) -> TokenTree {
+        ident(&format!("Abstract{}", self.name.0))
+    }
+
+    fn emit_abstract_trait(&self) -> TokenStream {
+        let abs_name_t = self.abs_name_t();
+        let doc = emit_doc(&self.description);
+        quote!(
+            $doc
+            pub trait $abs_name_t:
+                ::graphql::types::schema::Reflect + ::graphql::types::query::FromValue {}
+        )
+    }
+
+    fn emit_concrete_struct(&self) -> TokenStream {
+        let name_t = self.name_t();
+        let fields: TokenStream = self.fields.iter().map(|f| f.emit_struct_field()).collect();
+        let doc = emit_doc(&self.description);
+
+        quote!(
+            $doc
+            #[allow(non_snake_case)]
+            #[derive(Clone, Debug)]
+            pub struct $name_t {
+                $fields
+            }
+        )
+    }
+
+    fn emit_impl_reflect(&self) -> TokenStream {
+        let name_t = self.name_t();
+        let name_str = self.name_str();
+        let field_schemas: TokenStream = self.fields.iter().map(|f| f.emit_schema()).collect();
+        let description = emit_description(&self.description);
+
+        quote!(
+            impl schema::Reflect for $name_t {
+                const NAME: &'static str = $name_str;
+
+                fn schema() -> schema::Item {
+                    let fields = vec![$field_schemas];
+                    schema::Item::InputObject(schema::InputObject {
+                        fields,
+                        description: $description,
+                        directives: vec![],
+                    })
+                }
+            }
+        )
+    }
+
+    fn emit_impl_from_value(&self) -> TokenStream {
+        let name_t = self.name_t();
+        let name_str = self.name_str();
+        let fields: TokenStream = self.fields
+            .iter()
+            .map(|f| f.emit_from_value_field(name_str.clone()))
+            .collect();
+
+        quote!(
+            #[allow(deprecated)]
+            impl FromValue for $name_t {
+                fn from(value: &query::Value) -> QlResult<$name_t> {
+                    match value.kind {
+                        query::ValueKind::Object(_) => {}
+                        _ => return Err(QlError::TranslationError(
+                                format!("{:?}", value.kind),
+                                $name_str.to_owned()
+                             )),
+                    }
+                    Ok($name_t {
+                        $fields
+                    })
+                }
+            }
+        )
+    }
+
+    fn emit_abstract_impl(&self) -> TokenStream {
+        let name_t = self.name_t();
+        let abs_name_t = self.abs_name_t();
+        quote!(impl $abs_name_t for $name_t {})
+    }
+
+    fn emit_assoc_ty(&self) -> TokenStream {
+        let name_t = self.name_t();
+        let abs_name_t = self.abs_name_t();
+        quote!(type $name_t: $abs_name_t = $name_t;)
+    }
+
+    fn emit_schema(&self) -> TokenStream {
+        let name_t = self.name_t();
+        quote!(Name(<Self as Root>::$name_t::NAME.to_owned()), <Self as Root>::$name_t::schema())
+    }
+}
+
+// The user supplies the type for a scalar and implements `ResolveScalar`, the
+// rest is done by the `ImplFoo` macro.
+impl ir::Scalar {
+    fn name_t(&self) -> TokenTree {
+        ident(&self.name.0)
+    }
+
+    fn name_str(&self) -> TokenTree {
+        ident(&format!("\"{}\"", self.name.0))
+    }
+
+    fn abs_name_t(&self) -> TokenTree {
+        ident(&format!("Abstract{}", self.name.0))
+    }
+
+    fn impl_name_t(&self) -> TokenTree {
+        ident(&format!("Impl{}", self.name.0))
+    }
+
+    fn emit_abstract_trait(&self) -> TokenStream {
+        let abs_name_t = self.abs_name_t();
+        let doc = emit_doc(&self.description);
+        quote!(
+            $doc
+            pub trait $abs_name_t:
+                ::graphql::types::schema::ResolveScalar
+                + ::graphql::types::schema::Reflect
+                + ::graphql::types::result::Resolve
+                + ::graphql::types::query::FromValue {}
+        )
+    }
+
+    fn emit_impl_macro(&self) -> TokenStream {
+        let impl_name_t = self.impl_name_t();
+        let abs_name_t = self.abs_name_t();
+        let name_str = self.name_str();
+        let description = emit_description(&self.description);
+
+        quote!(
+            pub macro $impl_name_t($$concrete: ident) {
+                impl schema::Reflect for $$concrete {
+                    const NAME: &'static str = $name_str;
+
+                    fn schema() -> schema::Item {
+                        schema::Item::Scalar(schema::Scalar {
+                            description: $description,
+                            directives: vec![],
+                        })
+                    }
+                }
+                impl Resolve for $$concrete {
+                    fn resolve(&self, _fields: &[query::Selection]) -> QlResult<result::Value> {
+                        schema::ResolveScalar::to_value(self)
+                    }
+                }
+                impl FromValue for $$concrete {
+                    fn from(value: &query::Value) -> QlResult<$$concrete> {
+                        schema::ResolveScalar::from_value(value)
+                    }
+                }
+                impl $abs_name_t for $$concrete {}
+            }
+        )
+    }
+
+    fn emit_assoc_ty(&self) -> TokenStream {
+        let name_t = self.name_t();
+        let abs_name_t = self.abs_name_t();
+        quote!(type $name_t: $abs_name_t;)
+    }
+
+    fn emit_schema(&self) -> TokenStream {
+        let name_t = self.name_t();
+        quote!(Name(<Self as Root>::$name_t::NAME.to_owned()), <Self as Root>::$name_t::schema())
+    }
+}
+
+impl ir::Union {
+    fn name_t(&self) -> TokenTree {
+        ident(&self.name.0)
+    }
+
+    fn name_str(&self) -> TokenTree {
+        ident(&format!("\"{}\"", self.name.0))
+    }
+
+    fn abs_name_t(&self) -> TokenTree {
+        ident(&format!("Abstract{}", self.name.0))
+    }
+
+    fn impl_name_t(&self) -> TokenTree {
+        ident(&format!("Impl{}", self.name.0))
+    }
+
+    fn emit_abstract_trait(&self) -> TokenStream {
+        let abs_name_t = self.abs_name_t();
+        let doc = emit_doc(&self.description);
+        quote!(
+            $doc
+            pub trait $abs_name_t:
+                ::graphql::types::schema::Reflect + ::graphql::types::result::Resolve {}
+        )
+    }
+
+    fn emit_impl_macro(&self) -> TokenStream {
+        let impl_name_t = self.impl_name_t();
+        let abs_name_t = self.abs_name_t();
+        let name_str = self.name_str();
+        let members: TokenStream = self.members
+            .iter()
+            .map(|m| {
+                let m_str = ident(&format!("\"{}\"", m.0));
+                quote!(Name($m_str.to_owned()),)
+            })
+            .collect();
+        let description = emit_description(&self.description);
+
+        quote!(
+            pub macro $impl_name_t($$concrete: ident) {
+                impl schema::Reflect for $$concrete {
+                    const NAME: &'static str = $name_str;
+
+                    fn schema() -> schema::Item {
+                        schema::Item::Union(schema::Union {
+                            members: vec![$members],
+                            span: ::graphql::types::Span::dummy(),
+                            description: $description,
+                            directives: vec![],
+                        })
+                    }
+                }
+                impl $abs_name_t for $$concrete {}
+            }
+        )
+    }
+
+    fn emit_concrete_enum(&self) -> TokenStream {
+        emit_member_enum(self.name_t(), &self.members, &self.description)
+    }
+
+    fn emit_impl_macro_use(&self) -> TokenStream {
+        let name_t = self.name_t();
+        let impl_name_t = self.impl_name_t();
+
+        quote!($impl_name_t!($name_t);)
+    }
+
+    fn emit_impl_resolve(&self) -> TokenStream {
+        emit_member_resolve(self.name_t(), &self.members)
+    }
+
+    fn emit_assoc_ty(&self) -> TokenStream {
+        let name_t = self.name_t();
+        let abs_name_t = self.abs_name_t();
+        quote!(type $name_t: $abs_name_t = $name_t;)
+    }
+
+    fn emit_schema(&self) -> TokenStream {
+        let name_t = self.name_t();
+        quote!(Name(<Self as Root>::$name_t::NAME.to_owned()), <Self as Root>::$name_t::schema())
+    }
+}
+
+// The Rust enum for a union or interface, with a variant for each of the object
+// types which a value may have.
+fn emit_member_enum(name_t: TokenTree, members: &[Name], description: &Option<String>) -> TokenStream {
+    let variants: TokenStream = members
+        .iter()
+        .map(|m| {
+            let m = ident(&m.0);
+            quote!($m($m),)
+        })
+        .collect();
+    let doc = emit_doc(description);
+
+    quote!(
+        $doc
+        #[derive(Clone, Debug)]
+        pub enum $name_t {
+            $variants
+        }
+    )
+}
+
+// Each member only sees the fields selected by fragments on its type.
+fn emit_member_resolve(name_t: TokenTree, members: &[Name]) -> TokenStream {
+    let arms: TokenStream = members
+        .iter()
+        .map(|m| {
+            let m_t = ident(&m.0);
+            let m_str = ident(&format!("\"{}\"", m.0));
+            quote!(
+                $name_t::$m_t(ref value) => {
+                    value.resolve(::graphql::execution::type_selections(fields, $m_str))
+                }
+            )
+        })
+        .collect();
+
+    quote!(
+        impl Resolve for $name_t {
+            fn resolve(&self, fields: &[query::Selection]) -> QlResult<result::Value> {
+                match *self {
+                    $arms
+                }
+            }
+        }
+    )
+}
+
+// Like a union, but the reflected schema has the interface's fields.
+impl ir::Interface {
+    fn name_t(&self) -> TokenTree {
+        ident(&self.name.0)
+    }
+
+    fn name_str(&self) -> TokenTree {
+        ident(&format!("\"{}\"", self.name.0))
+    }
+
+    fn abs_name_t(&self) -> TokenTree {
+        ident(&format!("Abstract{}", self.name.0))
+    }
+
+    fn impl_name_t(&self) -> TokenTree {
+        ident(&format!("Impl{}", self.name.0))
+    }
+
+    fn emit_abstract_trait(&self) -> TokenStream {
+        let abs_name_t = self.abs_name_t();
+        let doc = emit_doc(&self.description);
+        quote!(
+            $doc
+            pub trait $abs_name_t:
+                ::graphql::types::schema::Reflect + ::graphql::types::result::Resolve {}
+        )
     }
 
     fn emit_impl_macro(&self) -> TokenStream {
         let impl_name_t = self.impl_name_t();
         let abs_name_t = self.abs_name_t();
         let name_str = self.name_str();
-        let variants: TokenStream = self.variants.iter().map(|v| v.emit_string()).collect();
+        let field_schemas: TokenStream = self.fields.iter().map(|f| f.emit_schema()).collect();
+        let implements: TokenStream = self.implements
+            .iter()
+            .map(|n| {
+                let n_str = ident(&format!("\"{}\"", n.0));
+                quote!(Name($n_str.to_owned()),)
+            })
+            .collect();
+        let description = emit_description(&self.description);
 
         quote!(
             pub macro $impl_name_t($$concrete: ident) {
@@ -169,26 +833,22 @@ impl ir::Enum {
                     const NAME: &'static str = $name_str;
 
                     fn schema() -> schema::Item {
-                        schema::Item::Enum(schema::Enum { variants: vec![$variants] })
+                        let fields = vec![$field_schemas];
+                        schema::Item::Interface(schema::Interface {
+                            implements: vec![$implements],
+                            fields,
+                            description: $description,
+                            directives: vec![],
+                        })
                     }
                 }
-                impl ResolveEnum for $$concrete {}
                 impl $abs_name_t for $$concrete {}
             }
         )
     }
 
     fn emit_concrete_enum(&self) -> TokenStream {
-        let name_t = self.name_t();
-        let variants: TokenStream = self.variants.iter().map(|v| v.emit_decl()).collect();
-
-        quote!(
-            #[allow(non_snake_case)]
-            #[derive(Clone, Debug)]
-            pub enum $name_t {
-                $variants
-            }
-        )
+        emit_member_enum(self.name_t(), &self.members, &self.description)
     }
 
     fn emit_impl_macro_use(&self) -> TokenStream {
@@ -198,46 +858,44 @@ impl ir::Enum {
         quote!($impl_name_t!($name_t);)
     }
 
-    fn emit_impl_from_value(&self) -> TokenStream {
-        let name_t = self.name_t();
-        let arms = self.variants
-            .iter()
-            .map(|v| v.emit_from_str_arm(name_t.clone()));
-        let expect_str = ident(&format!("\"Option<{}>\"", self.name.0));
-        let arms: TokenStream = arms.chain(
-            Some(quote!(
-                    _ => return Err(QlError::TranslationError(
-                            format!("{:?}", value),
-                            $expect_str.to_owned()
-                         )),
-                )).into_iter(),
-        ).collect();
-
-        quote!(
-            impl FromValue for $name_t {
-                fn from(value: &query::Value) -> QlResult<$name_t> {
-                    Ok(match &*<String as FromValue>::from(value)? {
-                        $arms
-                    })
-                }
-            }
-        )
+    fn emit_impl_resolve(&self) -> TokenStream {
+        emit_member_resolve(self.name_t(), &self.members)
     }
 
-    fn emit_impl_resolve(&self) -> TokenStream {
+    // A `to_X` function for each interface in the implements list. Every
+    // member of this interface is also a member of those interfaces.
+    fn emit_conversions(&self) -> TokenStream {
+        if self.implements.is_empty() {
+            return quote!();
+        }
+
         let name_t = self.name_t();
-        let variants: TokenStream = self.variants
+        let conversion_fns: TokenStream = self.implements
             .iter()
-            .map(|v| v.emit_resolve_arm(name_t.clone()))
+            .map(|n| {
+                let i_name = ident(&n.0);
+                let fn_name = ident(&format!("to_{}", n.0));
+                let arms: TokenStream = self.members
+                    .iter()
+                    .map(|m| {
+                        let m_t = ident(&m.0);
+                        quote!($name_t::$m_t(ref value) => $i_name::$m_t(value.clone()),)
+                    })
+                    .collect();
+                quote!(
+                    #[allow(non_snake_case)]
+                    pub fn $fn_name(&self) -> QlResult<$i_name> {
+                        Ok(match *self {
+                            $arms
+                        })
+                    }
+                )
+            })
             .collect();
 
         quote!(
-            impl Resolve for $name_t {
-                fn resolve(&self, _fields: &[query::Field]) -> QlResult<result::Value> {
-                    Ok(match *self {
-                        $variants
-                    })
-                }
+            impl $name_t {
+                $conversion_fns
             }
         )
     }
@@ -245,7 +903,7 @@ impl ir::Enum {
     fn emit_assoc_ty(&self) -> TokenStream {
         let name_t = self.name_t();
         let abs_name_t = self.abs_name_t();
-        quote!(type $name_t: $abs_name_t + FromValue = $name_t;)
+        quote!(type $name_t: $abs_name_t = $name_t;)
     }
 
     fn emit_schema(&self) -> TokenStream {
@@ -274,8 +932,10 @@ impl ir::Object {
     fn emit_concrete_struct(&self) -> TokenStream {
         let name_t = self.name_t();
         let fields: TokenStream = self.fields.iter().map(|f| f.emit_struct_field()).collect();
+        let doc = emit_doc(&self.description);
 
         quote!(
+            $doc
             #[allow(non_snake_case)]
             #[derive(Clone, Debug)]
             pub struct $name_t {
@@ -300,7 +960,7 @@ impl ir::Object {
             .collect();
 
         // Conversion functions for converting this object to
-        // an object in its implements list.
+        // an interface in its implements list.
         let conversion_fns: TokenStream = self.implements
             .iter()
             .map(|n| {
@@ -318,7 +978,10 @@ impl ir::Object {
             .map(|f| f.emit_fn_sig(&format!("Abstract{}", self.name.0)))
             .collect();
 
+        let doc = emit_doc(&self.description);
+
         quote!(
+            $doc
             pub trait $abs_name_t: ::graphql::types::schema::ResolveObject {
                 $impl_types
                 $conversion_fns
@@ -327,12 +990,12 @@ impl ir::Object {
         )
     }
 
-    fn emit_abstract_impl(&self, schema: &ir::Schema) -> TokenStream {
+    fn emit_abstract_impl(&self) -> TokenStream {
         let name_t = self.name_t();
         let abs_name_t = self.abs_name_t();
 
         // Assoc types and conversion functions for converting this object to
-        // an object in its implements list.
+        // an interface in its implements list.
         let impl_types: TokenStream = self.abs_names
             .iter()
             .map(|n| {
@@ -345,26 +1008,16 @@ impl ir::Object {
             .map(|n| {
                 let i_name = ident(&n.0);
                 let fn_name = ident(&format!("to_{}", n.0));
-                let super_ty = schema.items[&n].assert_object();
-                let fields: TokenStream = super_ty
-                    .fields
-                    .iter()
-                    .map(|f| {
-                        let f_name = ident(&f.name.0);
-                        quote!($f_name: self.$f_name.clone(),)
-                    })
-                    .collect();
                 quote!(
                 fn $fn_name(&self) -> QlResult<Self::$i_name> {
-                    Ok($i_name {
-                        $fields
-                    })
+                    Ok($i_name::$name_t(self.clone()))
                 }
             )
             })
             .collect();
 
         quote!(
+            #[allow(deprecated)]
             impl $abs_name_t for $name_t {
                 $impl_types
                 $conversion_fns
@@ -376,10 +1029,18 @@ impl ir::Object {
         let impl_name_t = self.impl_name_t();
         let name_str = self.name_str();
         let field_schemas: TokenStream = self.fields.iter().map(|f| f.emit_schema()).collect();
+        let implements: TokenStream = self.implements
+            .iter()
+            .map(|n| {
+                let n_str = ident(&format!("\"{}\"", n.0));
+                quote!(Name($n_str.to_owned()),)
+            })
+            .collect();
         let resolve_fields: TokenStream = self.fields
             .iter()
             .map(|f| f.emit_resolve_arm(&format!("Abstract{}", self.name.0)))
             .collect();
+        let description = emit_description(&self.description);
 
         let impl_resolve_object = if !self.has_fields {
             // TODO share code with emit_resolve_object_impl
@@ -388,6 +1049,7 @@ impl ir::Object {
                 .map(|f| f.emit_dispatch_resolve_arm())
                 .collect();
             quote!(
+                #[allow(deprecated)]
                 impl ResolveObject for $$concrete {
                     fn resolve_field(&self, field: &query::Field) -> QlResult<result::Value> {
                         match &*field.name.0 {
@@ -417,14 +1079,22 @@ impl ir::Object {
 
                     fn schema() -> schema::Item {
                         let fields = vec![$field_schemas];
-                        schema::Item::Object(schema::Object { implements: vec![], fields })
+                        schema::Item::Object(schema::Object {
+                            implements: vec![$implements],
+                            fields,
+                            description: $description,
+                            // Directives on items aren't reflected.
+                            directives: vec![],
+                        })
                     }
                 }
 
+                #[allow(deprecated)]
                 impl Resolve for $$concrete {
-                    fn resolve(&self, fields: &[query::Field]) -> QlResult<result::Value> {
+                    fn resolve(&self, fields: &[query::Selection]) -> QlResult<result::Value> {
                         let mut result = vec![];
                         for field in fields {
+                            let field = field.assert_field();
                             match &*field.name.0 {
                                 $resolve_fields
                                 n => return Err(QlError::ExecutionError(
@@ -456,6 +1126,7 @@ impl ir::Object {
             .collect();
 
         quote!(
+            #[allow(deprecated)]
             impl ResolveObject for $name_t {
                 fn resolve_field(&self, field: &query::Field) -> QlResult<result::Value> {
                     match &*field.name.0 {
@@ -471,6 +1142,84 @@ impl ir::Object {
         )
     }
 
+    fn emit_subscription_trait(&self) -> TokenStream {
+        let abs_name_t = self.abs_name_t();
+        let abs_name_str = format!("Abstract{}", self.name.0);
+
+        let impl_types: TokenStream = self.abs_names
+            .iter()
+            .map(|n| {
+                let i_name = ident(&n.0);
+                let i_abs_name = ident(&format!("Abstract{}", n.0));
+                quote!(type $i_name: $i_abs_name = $i_name;)
+            })
+            .collect();
+        let fns: TokenStream = self.fields
+            .iter()
+            .map(|f| f.emit_stream_fn_sig(&abs_name_str))
+            .collect();
+
+        let doc = emit_doc(&self.description);
+
+        quote!(
+            $doc
+            pub trait $abs_name_t:
+                ::graphql::types::schema::Reflect + ::graphql::types::result::Subscribe {
+                $impl_types
+                $fns
+            }
+        )
+    }
+
+    fn emit_subscription_impl_macro(&self) -> TokenStream {
+        let impl_name_t = self.impl_name_t();
+        let name_str = self.name_str();
+        let abs_name_str = format!("Abstract{}", self.name.0);
+        let field_schemas: TokenStream = self.fields.iter().map(|f| f.emit_schema()).collect();
+        let subscribe_arms: TokenStream = self.fields
+            .iter()
+            .map(|f| f.emit_subscribe_arm(&abs_name_str))
+            .collect();
+        let description = emit_description(&self.description);
+
+        quote!(
+            pub macro $impl_name_t($$concrete: ident) {
+                impl schema::Reflect for $$concrete {
+                    const NAME: &'static str = $name_str;
+
+                    fn schema() -> schema::Item {
+                        let fields = vec![$field_schemas];
+                        schema::Item::Object(schema::Object {
+                            implements: vec![],
+                            fields,
+                            description: $description,
+                            directives: vec![],
+                        })
+                    }
+                }
+
+                #[allow(deprecated)]
+                impl result::Subscribe for $$concrete {
+                    fn subscribe(&self, field: &query::Field) -> QlResult<result::Stream> {
+                        match &*field.name.0 {
+                            $subscribe_arms
+                            n => Err(QlError::ExecutionError(
+                                    format!("Missing field executor in {}: {}", $name_str, n)
+                                 )),
+                        }
+                    }
+                }
+            }
+        )
+    }
+
+    // Subscriptions are always implemented by the user, so there is no default.
+    fn emit_subscription_assoc_ty(&self) -> TokenStream {
+        let name_t = self.name_t();
+        let abs_name_t = self.abs_name_t();
+        quote!(type $name_t: $abs_name_t;)
+    }
+
     fn emit_assoc_ty(&self) -> TokenStream {
         let name_t = self.name_t();
         let abs_name_t = self.abs_name_t();
@@ -503,12 +1252,23 @@ fn emit_server(schema: &ir::Schema) -> QlResult<TokenStream> {
 impl ir::Schema {
     fn emit_root_trait(&self) -> TokenStream {
         let types: TokenStream = self.items.values().map(|i| i.emit_assoc_ty()).collect();
+        let mutation_fn = if self.has_mutation() {
+            quote!(fn mutation(&self) -> QlResult<Self::Mutation>;)
+        } else {
+            quote!()
+        };
+        let subscription_fn = if self.has_subscription() {
+            quote!(fn subscription(&self) -> QlResult<Self::Subscription>;)
+        } else {
+            quote!()
+        };
         quote!(
             pub trait Root: query::Root {
                 $types
 
                 fn query(&self) -> QlResult<Self::Query>;
-                // FIXME Mutations
+                $mutation_fn
+                $subscription_fn
             }
         )
     }
@@ -521,6 +1281,30 @@ impl ir::Schema {
                 quote!(schema.items.insert($sch);)
             })
             .collect();
+        let directives: TokenStream = self.directives.iter().map(|d| d.emit_schema()).collect();
+        // Top-level mutation fields are resolved in document order by the
+        // mutation object's `Resolve` impl, so are executed serially.
+        let mutation_arm = if self.has_mutation() {
+            quote!(
+                "mutation" => {
+                    assert_eq!(field.args.len(), 0);
+                    let result = self.mutation()?;
+                    let result = result.resolve(&field.selections)?;
+                    results.push((types::Name("data".to_owned()), result));
+                }
+            )
+        } else {
+            quote!()
+        };
+        let subscribe_fn = if self.has_subscription() {
+            quote!(
+                fn subscribe(&self, field: &query::Field) -> QlResult<result::Stream> {
+                    result::Subscribe::subscribe(&self.subscription()?, field)
+                }
+            )
+        } else {
+            quote!()
+        };
 
         quote!(
             pub macro ImplRoot($$concrete: ident) {
@@ -528,26 +1312,30 @@ impl ir::Schema {
                     fn schema() -> schema::Schema {
                         let mut schema = schema::Schema::new();
                         $schema_items
+                        schema.directives = vec![$directives];
                         assert!(schema.validate().is_ok());
                         schema
                     }
+
+                    $subscribe_fn
                 }
 
                 impl Resolve for $$concrete {
-                    fn resolve(&self, fields: &[query::Field]) -> QlResult<result::Value> {
+                    fn resolve(&self, fields: &[query::Selection]) -> QlResult<result::Value> {
                         let mut results = vec![];
                         for field in fields {
+                            let field = field.assert_field();
                             match &*field.name.0 {
                                 "query" => {
                                     assert_eq!(field.args.len(), 0);
                                     let result = self.query()?;
-                                    let result = result.resolve(&field.fields)?;
+                                    let result = result.resolve(&field.selections)?;
 
                                     // This is a special case where the result
                                     // doesn't match the query
                                     results.push((types::Name("data".to_owned()), result));
                                 }
-                                // FIXME mutations
+                                $mutation_arm
                                 n => return Err(QlError::ExecutionError(
                                         format!("Missing field executor in Root: {}", n)
                                      )),
@@ -560,32 +1348,49 @@ impl ir::Schema {
         )
     }
 
-    fn emit_schema() -> TokenStream {
-        quote!(Name(schema::SCHEMA_NAME.to_owned()), schema::schema_type())
+    fn emit_schema(has_mutation: bool, has_subscription: bool) -> TokenStream {
+        let has_mutation = ident(&has_mutation.to_string());
+        let has_subscription = ident(&has_subscription.to_string());
+        quote!(
+            Name(schema::SCHEMA_NAME.to_owned()),
+            schema::schema_type($has_mutation, $has_subscription)
+        )
     }
 }
 
 impl ir::Variant {
     fn emit_resolve_arm(&self, enum_name: TokenTree) -> TokenStream {
-        let id = ident(&(self.0).0);
-        let id_str = ident(&format!("\"{}\"", (self.0).0));
+        let id = ident(&self.name.0);
+        let id_str = ident(&format!("\"{}\"", self.name.0));
         quote!($enum_name::$id => result::Value::String($id_str.to_owned()),)
     }
 
     fn emit_from_str_arm(&self, enum_name: TokenTree) -> TokenStream {
-        let id = ident(&(self.0).0);
-        let id_str = ident(&format!("\"{}\"", (self.0).0));
+        let id = ident(&self.name.0);
+        let id_str = ident(&format!("\"{}\"", self.name.0));
         quote!($id_str => $enum_name::$id,)
     }
 
     fn emit_decl(&self) -> TokenStream {
-        let id = ident(&(self.0).0);
-        quote!($id,)
+        let id = ident(&self.name.0);
+        let doc = emit_doc(&self.description);
+        let deprecated = emit_deprecated(&self.directives);
+        quote!(
+            $doc
+            $deprecated
+            $id,
+        )
     }
 
-    fn emit_string(&self) -> TokenStream {
-        let id = ident(&format!("\"{}\"", (self.0).0));
-        quote!(Name($id.to_owned()),)
+    fn emit_schema(&self) -> TokenStream {
+        let id = ident(&format!("\"{}\"", self.name.0));
+        let description = emit_description(&self.description);
+        let directives = emit_directives(&self.directives);
+        quote!(schema::Variant {
+            name: Name($id.to_owned()),
+            description: $description,
+            directives: $directives,
+        },)
     }
 }
 
@@ -594,7 +1399,7 @@ impl ir::Field {
         let name = ident(&self.name.0);
         let name_str = ident(&format!("\"{}\"", self.name.0));
         if self.args.is_empty() {
-            quote!($name_str => self.$name.resolve(&field.fields),)
+            quote!($name_str => self.$name.resolve(&field.selections),)
         } else {
             quote!($name_str => panic!("trying to dispatch function as field: {}", $name_str),)
         }
@@ -605,83 +1410,164 @@ impl ir::Field {
         if self.args.is_empty() {
             quote!(
                 $name_str => result.push(
-                    (types::Name($name_str.to_owned()),
+                    (field.response_key().clone(),
                     self.resolve_field(field)?)
                 ),
             )
         } else {
             let name = ident(&self.name.0);
-
-            let process_args: TokenStream = self.args
-                .iter()
-                .enumerate()
-                .map(|(i, a)| {
-                    let arg_n = ident(&format!("arg{}", i));
-                    let arg_ty = a.1.emit_abs_rust_type(abs_self_type);
-                    let name_str = ident(&format!("\"{}\"", a.0));
-                    let none_expr = if a.1.nullable {
-                        quote!(None)
-                    } else {
-                        quote!(panic!("Required non-null argument not supplied: {}", $name_str))
-                    };
-                    quote!(
-                    let $arg_n: $arg_ty = match field.find_arg(&Name($name_str.to_owned())) {
-                        Some(val) => FromValue::from(val)?,
-                        None => $none_expr,
-                    };
-                )
-                })
-                .collect();
-
-            let arg_list: TokenStream = (0..self.args.len())
-                .map(|i| {
-                    let arg_n = ident(&format!("arg{}", i));
-                    quote!($arg_n,)
-                })
-                .collect();
+            let (process_args, arg_list) = self.emit_process_args(abs_self_type);
 
             quote!(
                 $name_str => {
                     $process_args
 
                     let sub_result = self.$name($arg_list)?;
-                    let sub_result = sub_result.resolve(&field.fields)?;
+                    let sub_result = sub_result.resolve(&field.selections)?;
 
-                    result.push((types::Name($name_str.to_owned()), sub_result))
+                    result.push((field.response_key().clone(), sub_result))
                 }
             )
         }
     }
 
+    // Returns code which reads the arguments from `field` into local variables
+    // and the list of those variables for passing to the field's function.
+    fn emit_process_args(&self, abs_self_type: &str) -> (TokenStream, TokenStream) {
+        let process_args: TokenStream = self.args
+            .iter()
+            .enumerate()
+            .map(|(i, a)| {
+                let arg_n = ident(&format!("arg{}", i));
+                let arg_ty = a.ty.emit_abs_rust_type(abs_self_type);
+                let name_str = ident(&format!("\"{}\"", a.name.0));
+                let none_expr = match a.default {
+                    Some(ref default) => {
+                        let default = emit_value(default);
+                        quote!(FromValue::from(&$default)?)
+                    }
+                    None if a.ty.nullable => quote!(None),
+                    // Validation rejects queries which omit required arguments,
+                    // but fields can be resolved without validating first.
+                    None => quote!(return Err(QlError::ExecutionError(
+                        format!("Required non-null argument not supplied: {}", $name_str)
+                    ))),
+                };
+                quote!(
+                let $arg_n: $arg_ty = match field.find_arg(&Name($name_str.to_owned())) {
+                    Some(val) => FromValue::from(val)?,
+                    None => $none_expr,
+                };
+            )
+            })
+            .collect();
+
+        let arg_list: TokenStream = (0..self.args.len())
+            .map(|i| {
+                let arg_n = ident(&format!("arg{}", i));
+                quote!($arg_n,)
+            })
+            .collect();
+
+        (process_args, arg_list)
+    }
+
+    fn emit_subscribe_arm(&self, abs_self_type: &str) -> TokenStream {
+        let name = ident(&self.name.0);
+        let name_str = ident(&format!("\"{}\"", self.name.0));
+        let (process_args, arg_list) = self.emit_process_args(abs_self_type);
+
+        quote!(
+            $name_str => {
+                $process_args
+
+                let events = self.$name($arg_list)?;
+                Ok(result::resolve_stream(events, field))
+            }
+        )
+    }
+
+    // A field initialiser for an input object's `FromValue` impl.
+    fn emit_from_value_field(&self, obj_name_str: TokenTree) -> TokenStream {
+        let name = ident(&self.name.0);
+        let name_str = ident(&format!("\"{}\"", self.name.0));
+        let none_expr = if self.ty.nullable {
+            quote!(None)
+        } else {
+            quote!(return Err(QlError::TranslationError(
+                format!("{:?}", value.kind),
+                $obj_name_str.to_owned()
+            )))
+        };
+        quote!(
+            $name: match value.find_field(&Name($name_str.to_owned())) {
+                Some(val) => FromValue::from(val)?,
+                None => $none_expr,
+            },
+        )
+    }
+
     fn emit_struct_field(&self) -> TokenStream {
         let name = ident(&self.name.0);
         let ty = self.ty.emit_rust_type();
-        quote!(pub $name: $ty,)
+        let doc = emit_doc(&self.description);
+        let deprecated = emit_deprecated(&self.directives);
+        quote!(
+            $doc
+            $deprecated
+            pub $name: $ty,
+        )
     }
 
     fn emit_schema(&self) -> TokenStream {
         let name_str = ident(&format!("\"{}\"", self.name.0));
         let ty = self.ty.emit_type_schema();
 
-        if self.args.is_empty() {
-            quote!(schema::Field::field(Name($name_str.to_owned()), $ty),)
+        let field = if self.args.is_empty() {
+            quote!(schema::Field::field(Name($name_str.to_owned()), $ty))
         } else {
-            let args: TokenStream = self.args
-                .iter()
-                .map(|a| {
-                    let name_str = ident(&format!("\"{}\"", (a.0).0));
-                    let ty = (a.1).emit_type_schema();
-                    quote!((Name($name_str.to_owned()), $ty))
-                })
-                .collect();
+            let args: TokenStream = self.args.iter().map(|a| a.emit_schema()).collect();
             quote!(
                 schema::Field::fun(
                     Name($name_str.to_owned()),
                     vec![$args],
                     $ty,
-                ),
+                )
             )
+        };
+
+        if self.description.is_none() && self.directives.is_empty() {
+            return quote!($field,);
         }
+        let description = emit_description(&self.description);
+        let directives = emit_directives(&self.directives);
+        quote!(schema::Field {
+            description: $description,
+            directives: $directives,
+            ..$field
+        },)
+    }
+
+    // Docs for the field's function, rustdoc has no syntax for documenting
+    // arguments so they are listed after the field's description.
+    fn emit_fn_doc(&self) -> TokenStream {
+        let mut lines: Vec<String> = self.description.iter().cloned().collect();
+        let args: Vec<_> = self.args
+            .iter()
+            .filter_map(|a| a.description.as_ref().map(|d| format!("* `{}` - {}", a.name.0, d)))
+            .collect();
+        if !args.is_empty() {
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            lines.push("# Arguments".to_owned());
+            lines.push(String::new());
+            lines.extend(args);
+        }
+        lines
+            .into_iter()
+            .map(|l| emit_doc(&Some(l)))
+            .collect()
     }
 
     fn emit_fn_sig(&self, abs_self_type: &str) -> TokenStream {
@@ -694,22 +1580,161 @@ impl ir::Field {
         let args: TokenStream = self.args
             .iter()
             .map(|a| {
-                let name = ident(&(a.0).0);
-                let ty = (a.1).emit_abs_rust_type(abs_self_type);
+                let name = ident(&a.name.0);
+                let ty = a.ty.emit_abs_rust_type(abs_self_type);
+                quote!($name: $ty,)
+            })
+            .collect();
+
+        let doc = self.emit_fn_doc();
+        let deprecated = emit_deprecated(&self.directives);
+
+        quote!(
+            $doc
+            $deprecated
+            fn $name(&self, $args) -> QlResult<$ty>;
+        )
+    }
+
+    // Like `emit_fn_sig`, but the function returns a stream of values and is
+    // emitted even if the field has no arguments.
+    fn emit_stream_fn_sig(&self, abs_self_type: &str) -> TokenStream {
+        let name = ident(&self.name.0);
+        let ty = self.ty.emit_abs_rust_type(abs_self_type);
+        let args: TokenStream = self.args
+            .iter()
+            .map(|a| {
+                let name = ident(&a.name.0);
+                let ty = a.ty.emit_abs_rust_type(abs_self_type);
                 quote!($name: $ty,)
             })
             .collect();
 
-        quote!(fn $name(&self, $args) -> QlResult<$ty>;)
+        let doc = self.emit_fn_doc();
+        let deprecated = emit_deprecated(&self.directives);
+
+        quote!(
+            $doc
+            $deprecated
+            fn $name(&self, $args) -> QlResult<Box<dyn Iterator<Item = $ty>>>;
+        )
+    }
+}
+
+impl ir::Arg {
+    fn emit_schema(&self) -> TokenStream {
+        let name_str = ident(&format!("\"{}\"", self.name.0));
+        let ty = self.ty.emit_type_schema();
+        let default = match self.default {
+            Some(ref default) => {
+                let default = emit_value(default);
+                quote!(Some($default))
+            }
+            None => quote!(None),
+        };
+        let description = emit_description(&self.description);
+        let directives = emit_directives(&self.directives);
+        quote!(schema::Argument {
+            name: Name($name_str.to_owned()),
+            ty: $ty,
+            default: $default,
+            description: $description,
+            directives: $directives,
+        },)
+    }
+}
+
+impl ir::Directive {
+    fn emit_schema(&self) -> TokenStream {
+        let name_str = ident(&format!("\"{}\"", self.name.0));
+        let args: TokenStream = self.args.iter().map(|a| a.emit_schema()).collect();
+        let locations: TokenStream = self.locations
+            .iter()
+            .map(|l| {
+                let l = ident(&format!("{:?}", l));
+                quote!(schema::DirectiveLocation::$l,)
+            })
+            .collect();
+        let description = emit_description(&self.description);
+        quote!(schema::Directive {
+            name: Name($name_str.to_owned()),
+            args: vec![$args],
+            locations: vec![$locations],
+            description: $description,
+            span: ::graphql::types::Span::dummy(),
+        },)
     }
 }
 
+// Emits an expression which constructs `value`, used for default arguments.
+fn emit_value(value: &query::Value) -> TokenStream {
+    let kind = match value.kind {
+        query::ValueKind::Null => quote!(query::ValueKind::Null),
+        // Literals must be non-negative, so negative numbers are emitted as a
+        // negation.
+        query::ValueKind::Int(n) if n < 0 => {
+            let n = ident(&format!("{}i64", n.wrapping_neg() as u64));
+            quote!(query::ValueKind::Int(-$n))
+        }
+        query::ValueKind::Int(n) => {
+            let n = ident(&format!("{}i64", n));
+            quote!(query::ValueKind::Int($n))
+        }
+        query::ValueKind::Float(n) if n < 0.0 => {
+            let n = ident(&format!("{:?}f64", -n));
+            quote!(query::ValueKind::Float(-$n))
+        }
+        query::ValueKind::Float(n) => {
+            let n = ident(&format!("{:?}f64", n));
+            quote!(query::ValueKind::Float($n))
+        }
+        query::ValueKind::Boolean(b) => {
+            let b = ident(&b.to_string());
+            quote!(query::ValueKind::Boolean($b))
+        }
+        query::ValueKind::String(ref s) => {
+            let s = ident(&format!("{:?}", s));
+            quote!(query::ValueKind::String($s.to_owned()))
+        }
+        query::ValueKind::Enum(ref n) => {
+            let n = ident(&format!("\"{}\"", n.0));
+            quote!(query::ValueKind::Enum(Name($n.to_owned())))
+        }
+        query::ValueKind::Array(ref values) => {
+            let values: TokenStream = values
+                .iter()
+                .map(|v| {
+                    let v = emit_value(v);
+                    quote!($v,)
+                })
+                .collect();
+            quote!(query::ValueKind::Array(vec![$values]))
+        }
+        query::ValueKind::Object(ref fields) => {
+            let fields: TokenStream = fields
+                .iter()
+                .map(|(n, v)| {
+                    let n = ident(&format!("\"{}\"", n.0));
+                    let v = emit_value(v);
+                    quote!((Name($n.to_owned()), $v),)
+                })
+                .collect();
+            quote!(query::ValueKind::Object(vec![$fields]))
+        }
+        query::ValueKind::Variable(_) => panic!("default values may not use variables"),
+    };
+    quote!(query::Value::new($kind, ::graphql::types::Span::dummy()))
+}
+
 impl ir::Type {
     fn emit_type_schema(&self) -> TokenStream {
         let nullable = ident(&self.nullable.to_string());
         let kind = match self.kind {
             ir::TypeKind::String => quote!(schema::TypeKind::String),
             ir::TypeKind::Id => quote!(schema::TypeKind::Id),
+            ir::TypeKind::Int => quote!(schema::TypeKind::Int),
+            ir::TypeKind::Float => quote!(schema::TypeKind::Float),
+            ir::TypeKind::Boolean => quote!(schema::TypeKind::Boolean),
             ir::TypeKind::Name(ref n) => {
                 let n = ident(&format!("\"{}\"", n.0));
                 quote!(schema::TypeKind::Name(Name($n.to_owned())))
@@ -722,6 +1747,7 @@ impl ir::Type {
         quote!(schema::Type {
             kind: $kind,
             nullable: $nullable,
+            span: ::graphql::types::Span::dummy(),
         })
     }
 
@@ -729,6 +1755,9 @@ impl ir::Type {
         let result = match self.kind {
             ir::TypeKind::String => quote!(String),
             ir::TypeKind::Id => quote!(Id),
+            ir::TypeKind::Int => quote!(i32),
+            ir::TypeKind::Float => quote!(f64),
+            ir::TypeKind::Boolean => quote!(bool),
             ir::TypeKind::Name(ref n) => {
                 let n = ident(&n.0);
                 n.into()
@@ -752,6 +1781,9 @@ impl ir::Type {
         let result = match self.kind {
             ir::TypeKind::String => quote!(String),
             ir::TypeKind::Id => quote!(Id),
+            ir::TypeKind::Int => quote!(i32),
+            ir::TypeKind::Float => quote!(f64),
+            ir::TypeKind::Boolean => quote!(bool),
             ir::TypeKind::Name(ref n) => {
                 let n = ident(&format!("<Self as {}>::{}", abs_self_type, n.0));
                 n.into()
@@ -7,7 +7,7 @@ extern crate graphql_macros;
 
 use graphql::{QlError, QlResult};
 use graphql::types::{self, query, result, schema, Id, Name};
-use graphql::types::schema::{Reflect, ResolveEnum, ResolveObject};
+use graphql::types::schema::{Reflect, ResolveEnum, ResolveObject, ResolveScalar};
 use graphql::types::query::FromValue;
 use graphql::types::result::Resolve;
 
@@ -18,40 +18,82 @@ use std::collections::HashMap;
 schema! {
     schema {
         query: Query,
+        mutation: Mutation,
+        subscription: Subscription,
     }
 
+    "The relative cost of resolving a field."
+    directive @cost(value: Int!) on FIELD_DEFINITION
+
     type Query {
         hero(episode: Episode): Character,
         human(id : ID!): Human,
-        droid(id : ID!): Droid,
+        droid(id : ID!): Droid @deprecated(reason: "Use `search`."),
+        search(text: String!): [SearchResult] @cost(value: 10),
+        "The first film released after `after`."
+        premiere(after: Date!): Date,
+        "The average rating of an episode, out of 10."
+        rating(
+            "The number of the episode, e.g., 4 for A New Hope."
+            episode: Int! = 4,
+            "If true, the rating is out of 100."
+            percent: Boolean = false,
+        ): Float,
+    }
+
+    "A date, written as `YYYY-MM-DD`."
+    scalar Date
+
+    union SearchResult = Human | Droid
+
+    type Mutation {
+        renameHuman(id: ID!, name: String!): Human,
+    }
+
+    type Subscription {
+        heroChanged(episode: Episode): Character,
     }
 
+    "One of the films in the original trilogy."
     enum Episode {
+        "Released in 1977."
         NEWHOPE,
         EMPIRE,
         JEDI,
     }
 
-    interface Character {
+    interface Node {
+        id: ID!,
+    }
+
+    "A character in the films."
+    interface Character implements Node {
         id: ID!,
         name: String!,
-        friends: [Character],
+        friends: [Character] @deprecated(reason: "Friendships are not tracked."),
         appearsIn: [Episode!]!,
     }
 
-    type Human implements Character {
+    "A humanoid creature in the Star Wars universe."
+    type Human implements Node & Character {
         id: ID!,
         name: String!,
         friends: [Character],
         appearsIn: [Episode!]!,
+        "The home planet of the human, or null if unknown."
         homePlanet: String,
     }
 
-    type Droid implements Character {
+    """
+    A mechanical creature in the Star Wars universe.
+      Droids are built for a purpose.
+    """
+    type Droid implements Character, Node {
         id: ID!,
         name: String!,
         friends: [Character],
         appearsIn: [Episode!]!,
+        """What the droid was built for."""
         primaryFunction: String,
     }
 }
@@ -68,6 +110,11 @@ impl Service {
 
 impl Root for Service {
     type Query = StaticQuery;
+    type Mutation = StaticMutation;
+    type Subscription = StaticSubscription;
+    type Node = Node;
+    type SearchResult = SearchResult;
+    type Date = Date;
     type Character = Character;
     type Human = Human;
     type Droid = Droid;
@@ -76,6 +123,14 @@ impl Root for Service {
     fn query(&self) -> QlResult<StaticQuery> {
         Ok(self.query.clone())
     }
+
+    fn mutation(&self) -> QlResult<StaticMutation> {
+        Ok(StaticMutation { query: self.query.clone() })
+    }
+
+    fn subscription(&self) -> QlResult<StaticSubscription> {
+        Ok(StaticSubscription { query: self.query.clone() })
+    }
 }
 
 ImplRoot!(Service);
@@ -155,6 +210,8 @@ impl StaticQuery {
 }
 
 impl AbstractQuery for StaticQuery {
+    type SearchResult = SearchResult;
+    type Date = Date;
     type Character = Character;
     type Human = Human;
     type Droid = Droid;
@@ -181,12 +238,111 @@ impl AbstractQuery for StaticQuery {
     fn droid(&self, _id: Id) -> QlResult<Option<Droid>> {
         Ok(None)
     }
+
+    fn search(&self, text: String) -> QlResult<Option<Vec<Option<SearchResult>>>> {
+        let humans = [&self.luke, &self.vader, &self.han, &self.leia, &self.tarkin];
+        let droids = [&self.threepio, &self.artoo];
+        let mut results: Vec<_> = humans
+            .iter()
+            .filter(|h| h.name.contains(&*text))
+            .map(|h| Some(SearchResult::Human((*h).clone())))
+            .collect();
+        results.extend(
+            droids
+                .iter()
+                .filter(|d| d.name.contains(&*text))
+                .map(|d| Some(SearchResult::Droid((*d).clone()))),
+        );
+        Ok(Some(results))
+    }
+
+    fn premiere(&self, after: Date) -> QlResult<Option<Date>> {
+        let premieres = [Date(1977, 5, 25), Date(1980, 5, 21), Date(1983, 5, 25)];
+        Ok(premieres.iter().find(|d| **d > after).cloned())
+    }
+
+    fn rating(&self, episode: i32, percent: Option<bool>) -> QlResult<Option<f64>> {
+        let rating = match episode {
+            4 => 8.6,
+            5 => 8.7,
+            6 => 8.3,
+            _ => return Ok(None),
+        };
+        if percent == Some(true) {
+            Ok(Some(rating * 10.0))
+        } else {
+            Ok(Some(rating))
+        }
+    }
 }
 
 ImplQuery!(StaticQuery);
 
+// `YYYY-MM-DD`
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct Date(u32, u32, u32);
+
+impl ResolveScalar for Date {
+    fn to_value(&self) -> QlResult<result::Value> {
+        Ok(result::Value::String(format!("{:04}-{:02}-{:02}", self.0, self.1, self.2)))
+    }
+
+    fn from_value(value: &query::Value) -> QlResult<Date> {
+        let s = <String as FromValue>::from(value)?;
+        let parts: Vec<u32> = s.split('-').filter_map(|p| p.parse().ok()).collect();
+        match parts[..] {
+            [y, m, d] if (1..=12).contains(&m) && (1..=31).contains(&d) => Ok(Date(y, m, d)),
+            _ => Err(QlError::TranslationError(s, "Date".to_owned())),
+        }
+    }
+}
+
+ImplDate!(Date);
+
+// Mutations don't change the static data, they return a modified copy.
+struct StaticMutation {
+    query: StaticQuery,
+}
+
+impl AbstractMutation for StaticMutation {
+    type Human = Human;
+
+    fn renameHuman(&self, id: Id, name: String) -> QlResult<Option<Human>> {
+        Ok(self.query.human(id)?.map(|mut human| {
+            human.name = name;
+            human
+        }))
+    }
+}
+
+ImplMutation!(StaticMutation);
+
+// An in-process event source, the hero changes to each character in turn.
+struct StaticSubscription {
+    query: StaticQuery,
+}
+
+impl AbstractSubscription for StaticSubscription {
+    type Character = Character;
+    type Episode = Episode;
+
+    fn heroChanged(&self, _episode: Option<Episode>) -> QlResult<Box<dyn Iterator<Item = Option<Character>>>> {
+        let heroes = vec![
+            self.query.luke.to_Character()?,
+            self.query.artoo.to_Character()?,
+        ];
+        Ok(Box::new(heroes.into_iter().map(Some)))
+    }
+}
+
+ImplSubscription!(StaticSubscription);
+
 fn query_string(query: &str, expected: &str) {
-    let result = format!("{}", graphql::handle_query(query, HashMap::new(), Service::new()).unwrap());
+    query_with_variables(query, HashMap::new(), expected);
+}
+
+fn query_with_variables(query: &str, variables: query::Variables, expected: &str) {
+    let result = format!("{}", graphql::handle_query(query, None, variables, Service::new()).unwrap());
     assert_eq!(expected, result);
 }
 
@@ -223,3 +379,398 @@ fn find_luke() {
     }"#;
     query_string(q, r#"{data:{human:{name:"Luke Skywalker"}}}"#);
 }
+
+#[test]
+fn find_with_aliases() {
+    let q = r#"{
+      luke: human(id: 1000) {
+        name
+        planet: homePlanet
+      }
+      han: human(id: 1002) {
+        name
+      }
+    }"#;
+    query_string(
+        q,
+        r#"{data:{luke:{name:"Luke Skywalker",planet:"Tatooine"},han:{name:"Han Solo"}}}"#,
+    );
+}
+
+#[test]
+fn find_luke_with_variables() {
+    let q = r#"query FetchLukeQuery($id: ID!) {
+      human(id: $id) {
+        name
+      }
+    }"#;
+    let mut variables = HashMap::new();
+    variables.insert(
+        "id".to_owned(),
+        query::Value::new(query::ValueKind::String("1000".to_owned()), types::Span::dummy()),
+    );
+    query_with_variables(q, variables, r#"{data:{human:{name:"Luke Skywalker"}}}"#);
+}
+
+#[test]
+fn select_operation_by_name() {
+    let q = r#"query HeroNameQuery {
+      hero {
+        name
+      }
+    }
+
+    query FetchLukeQuery {
+      human(id: 1000) {
+        name
+      }
+    }"#;
+    let result = graphql::handle_query(q, Some("FetchLukeQuery"), HashMap::new(), Service::new()).unwrap();
+    assert_eq!(format!("{}", result), r#"{data:{human:{name:"Luke Skywalker"}}}"#);
+
+    match graphql::handle_query(q, None, HashMap::new(), Service::new()) {
+        Err(QlError::ExecutionError(_)) => {}
+        r => panic!("Expected an error, found {:?}", r),
+    }
+    match graphql::handle_query(q, Some("FetchLeiaQuery"), HashMap::new(), Service::new()) {
+        Err(QlError::ResolveError("operation", ..)) => {}
+        r => panic!("Expected an error, found {:?}", r),
+    }
+}
+
+#[test]
+fn find_luke_with_fragments() {
+    let q = r#"{
+      human(id: 1000) {
+        ...characterName
+        ... on Human {
+          homePlanet
+        }
+      }
+    }
+
+    fragment characterName on Character {
+      name
+    }"#;
+    query_string(q, r#"{data:{human:{name:"Luke Skywalker",homePlanet:"Tatooine"}}}"#);
+}
+
+#[test]
+fn skip_and_include_fields() {
+    let q = r#"query FetchLukeQuery($withPlanet: Boolean!) {
+      human(id: 1000) {
+        name @include(if: true)
+        id @skip(if: true)
+        homePlanet @include(if: $withPlanet)
+      }
+    }"#;
+    let mut variables = HashMap::new();
+    variables.insert(
+        "withPlanet".to_owned(),
+        query::Value::new(query::ValueKind::Boolean(false), types::Span::dummy()),
+    );
+    query_with_variables(q, variables, r#"{data:{human:{name:"Luke Skywalker"}}}"#);
+}
+
+#[test]
+fn rename_luke() {
+    let q = r#"mutation RenameLuke {
+      renameHuman(id: 1000, name: "Red Five") {
+        name
+        homePlanet
+      }
+    }"#;
+    query_string(q, r#"{data:{renameHuman:{name:"Red Five",homePlanet:"Tatooine"}}}"#);
+}
+
+#[test]
+fn subscribe_to_hero() {
+    let q = r#"subscription HeroSubscription {
+      heroChanged {
+        name
+      }
+    }"#;
+    let results: Vec<String> = graphql::handle_subscription(q, None, HashMap::new(), Service::new())
+        .unwrap()
+        .map(|r| format!("{}", r.unwrap()))
+        .collect();
+    assert_eq!(
+        results,
+        vec![
+            r#"{data:{heroChanged:{name:"Luke Skywalker"}}}"#,
+            r#"{data:{heroChanged:{name:"R2-D2"}}}"#,
+        ]
+    );
+
+    // Queries can't be executed as subscriptions and vice versa.
+    assert!(graphql::handle_subscription("{ hero { name } }", None, HashMap::new(), Service::new()).is_err());
+    assert!(graphql::handle_query(q, None, HashMap::new(), Service::new()).is_err());
+}
+
+#[test]
+fn convert_to_interfaces() {
+    fn node_id(node: Node) -> String {
+        match node {
+            Node::Human(h) => h.id.0,
+            Node::Droid(d) => d.id.0,
+        }
+    }
+
+    let query = StaticQuery::new();
+    assert_eq!(node_id(query.luke.to_Node().unwrap()), "1000");
+    assert_eq!(node_id(query.artoo.to_Node().unwrap()), "2001");
+    assert_eq!(node_id(query.luke.to_Character().unwrap().to_Node().unwrap()), "1000");
+}
+
+#[test]
+fn interface_fragments() {
+    let q = r#"{
+      hero(episode: EMPIRE) {
+        name
+        ... on Human {
+          homePlanet
+        }
+        ... on Droid {
+          primaryFunction
+        }
+      }
+    }"#;
+    query_string(q, r#"{data:{hero:{name:"Luke Skywalker",homePlanet:"Tatooine"}}}"#);
+
+    let q = r#"{
+      hero {
+        ... on Human {
+          homePlanet
+        }
+        ... on Droid {
+          primaryFunction
+        }
+      }
+    }"#;
+    query_string(q, r#"{data:{hero:{primaryFunction:"Astromech"}}}"#);
+}
+
+#[test]
+fn search_union() {
+    let q = r#"{
+      search(text: "D") {
+        ... on Human {
+          name
+          homePlanet
+        }
+        ... on Droid {
+          name
+          primaryFunction
+        }
+      }
+    }"#;
+    query_string(
+        q,
+        r#"{data:{search:[{name:"Darth Vader",homePlanet:"Tatooine"},{name:"R2-D2",primaryFunction:"Astromech"}]}}"#,
+    );
+}
+
+#[test]
+fn custom_scalar() {
+    query_string(
+        r#"{ premiere(after: "1978-01-01") }"#,
+        r#"{data:{premiere:"1980-05-21"}}"#,
+    );
+    assert!(graphql::handle_query(r#"{ premiere(after: "1978-13-01") }"#, None, HashMap::new(), Service::new()).is_err());
+}
+
+#[test]
+fn enum_args() {
+    query_string(
+        r#"{ hero(episode: EMPIRE) { name } }"#,
+        r#"{data:{hero:{name:"Luke Skywalker"}}}"#,
+    );
+    query_string(
+        r#"{ hero(episode: JEDI) { name } }"#,
+        r#"{data:{hero:{name:"R2-D2"}}}"#,
+    );
+
+    let mut variables = HashMap::new();
+    variables.insert(
+        "e".to_owned(),
+        query::Value::new(query::ValueKind::Enum(types::Name("EMPIRE".to_owned())), types::Span::dummy()),
+    );
+    query_with_variables(
+        r#"query ($e: Episode) { hero(episode: $e) { name } }"#,
+        variables,
+        r#"{data:{hero:{name:"Luke Skywalker"}}}"#,
+    );
+
+    for q in &[r#"{ hero(episode: FOO) { name } }"#, r#"{ hero(episode: "EMPIRE") { name } }"#] {
+        match graphql::handle_query(q, None, HashMap::new(), Service::new()) {
+            Err(QlError::ValidationError(_)) => {}
+            r => panic!("Expected a validation error, found {:?}", r),
+        }
+    }
+}
+
+#[test]
+fn builtin_scalars() {
+    query_string(
+        r#"{ rating(episode: 5) }"#,
+        r#"{data:{rating:8.7}}"#,
+    );
+    query_string(
+        r#"{ rating(episode: 4, percent: true) }"#,
+        r#"{data:{rating:86}}"#,
+    );
+    query_string(
+        r#"{ rating(episode: 1) }"#,
+        r#"{data:{rating:null}}"#,
+    );
+}
+
+#[test]
+fn default_args() {
+    query_string(
+        r#"{ rating }"#,
+        r#"{data:{rating:8.6}}"#,
+    );
+    query_string(
+        r#"{ rating(percent: true) }"#,
+        r#"{data:{rating:86}}"#,
+    );
+}
+
+#[test]
+fn missing_required_args() {
+    // Skip validation, which would reject the query, to reach the resolver.
+    let schema = <Service as query::Root>::schema();
+    let doc = query::Document::parse("{ human { name } }").unwrap();
+    match doc.execute(None, HashMap::new(), &schema, Service::new()) {
+        Err(QlError::ExecutionError(ref msg)) => assert_eq!(msg, "Required non-null argument not supplied: id"),
+        r => panic!("Expected an execution error, found {:?}", r),
+    }
+}
+
+#[test]
+fn validation_errors() {
+    let q = "{\n  hero {\n    nam\n  }\n}";
+    let err = graphql::handle_query(q, None, HashMap::new(), Service::new()).unwrap_err();
+    assert_eq!(
+        err.to_response().unwrap().to_string(),
+        "{errors:[{message:\"Cannot query field \"nam\" on type \"Character\".\",\
+         locations:[{line:3,column:5}],path:[\"hero\",\"nam\"],\
+         extensions:{code:\"FIELDS_ON_CORRECT_TYPE\"}}]}"
+    );
+
+    let err = graphql::handle_query("{ hero", None, HashMap::new(), Service::new()).unwrap_err();
+    assert!(err.to_response().is_none());
+}
+
+#[test]
+fn descriptions() {
+    let schema = <Service as query::Root>::schema();
+    let human = &schema.items[&Name("Human".to_owned())];
+    assert_eq!(human.description(), Some("A humanoid creature in the Star Wars universe."));
+    let home_planet = human.assert_field(Name("homePlanet".to_owned()));
+    assert_eq!(home_planet.description.as_ref().unwrap(), "The home planet of the human, or null if unknown.");
+
+    // Block strings.
+    let droid = &schema.items[&Name("Droid".to_owned())];
+    assert_eq!(
+        droid.description(),
+        Some("A mechanical creature in the Star Wars universe.\n  Droids are built for a purpose.")
+    );
+    let primary_function = droid.assert_field(Name("primaryFunction".to_owned()));
+    assert_eq!(primary_function.description.as_ref().unwrap(), "What the droid was built for.");
+
+    let rating = schema.items[&Name("Query".to_owned())].assert_field(Name("rating".to_owned()));
+    assert_eq!(rating.args[1].description.as_ref().unwrap(), "If true, the rating is out of 100.");
+
+    match schema.items[&Name("Episode".to_owned())] {
+        schema::Item::Enum(ref e) => {
+            assert_eq!(e.description.as_ref().unwrap(), "One of the films in the original trilogy.");
+            assert_eq!(e.variants[0].description.as_ref().unwrap(), "Released in 1977.");
+        }
+        _ => panic!(),
+    }
+    assert_eq!(
+        schema.items[&Name("Date".to_owned())].description(),
+        Some("A date, written as `YYYY-MM-DD`.")
+    );
+
+    match schema.items[&Name("Character".to_owned())] {
+        schema::Item::Interface(ref i) => {
+            assert_eq!(i.description.as_ref().unwrap(), "A character in the films.");
+            assert_eq!(i.implements, vec![Name("Node".to_owned())]);
+        }
+        _ => panic!(),
+    }
+
+    query_string(
+        r#"{ __type(name: "Character") { kind, description, possibleTypes { name } } }"#,
+        r#"{data:{__type:{kind:"INTERFACE",description:"A character in the films.",possibleTypes:[{name:"Droid"},{name:"Human"}]}}}"#,
+    );
+    query_string(
+        r#"{ __type(name: "Human") { fields { name, description } } }"#,
+        concat!(
+            r#"{data:{__type:{fields:[{name:"id",description:null},{name:"name",description:null},"#,
+            r#"{name:"friends",description:null},{name:"appearsIn",description:null},"#,
+            r#"{name:"homePlanet",description:"The home planet of the human, or null if unknown."}]}}}"#
+        ),
+    );
+}
+
+#[test]
+fn deprecated_fields() {
+    let schema = <Service as query::Root>::schema();
+    let query_ty = &schema.items[&Name("Query".to_owned())];
+    assert_eq!(query_ty.assert_field(Name("droid".to_owned())).deprecation_reason(), Some("Use `search`."));
+    assert!(query_ty.assert_field(Name("human".to_owned())).deprecation_reason().is_none());
+
+    let search = query_ty.assert_field(Name("search".to_owned()));
+    assert_eq!(search.directives[0].name.0, "cost");
+    assert_eq!(schema.directives[0].name.0, "cost");
+
+    // Deprecated fields can still be queried.
+    query_string(r#"{ droid(id: "2001") { name } }"#, r#"{data:{droid:null}}"#);
+
+    // Deprecation of interface fields is reflected on the interface.
+    match schema.items[&Name("Character".to_owned())] {
+        ref character @ schema::Item::Interface(_) => {
+            let friends = character.assert_field(Name("friends".to_owned()));
+            assert_eq!(friends.deprecation_reason(), Some("Friendships are not tracked."));
+        }
+        _ => panic!(),
+    }
+
+    query_string(
+        r#"{ __type(name: "Query") { fields { name } } }"#,
+        concat!(
+            r#"{data:{__type:{fields:[{name:"hero"},{name:"human"},{name:"search"},"#,
+            r#"{name:"premiere"},{name:"rating"}]}}}"#
+        ),
+    );
+    query_string(
+        r#"{ __type(name: "Character") { fields(includeDeprecated: true) { name, isDeprecated, deprecationReason } } }"#,
+        concat!(
+            r#"{data:{__type:{fields:[{name:"id",isDeprecated:false,deprecationReason:null},"#,
+            r#"{name:"name",isDeprecated:false,deprecationReason:null},"#,
+            r#"{name:"friends",isDeprecated:true,deprecationReason:"Friendships are not tracked."},"#,
+            r#"{name:"appearsIn",isDeprecated:false,deprecationReason:null}]}}}"#
+        ),
+    );
+}
+
+#[test]
+fn print_schema() {
+    let printed = <Service as query::Root>::schema().print();
+    assert!(printed.starts_with("schema {\n  query: Query\n"));
+    assert!(printed.contains("\"The relative cost of resolving a field.\"\ndirective @cost(value: Int!) on FIELD_DEFINITION"));
+    assert!(printed.contains("  search(text: String!): [SearchResult] @cost(value: 10)\n"));
+    assert!(printed.contains("union SearchResult = Human | Droid"));
+    assert!(printed.contains("interface Node {\n  id: ID!\n}"));
+    assert!(printed.contains("\"A character in the films.\"\ninterface Character implements Node {\n"));
+    assert!(printed.contains("  friends: [Character] @deprecated(reason: \"Friendships are not tracked.\")\n"));
+    assert!(printed.contains("type Human implements Node & Character {\n"));
+    assert!(!printed.contains("type Character"));
+
+    let reparsed = graphql::parse_schema(&printed).unwrap();
+    assert_eq!(reparsed.print(), printed);
+}
@@ -1,29 +1,466 @@
 use QlResult;
-use query::{Field, Variables};
-use types::{query, result, schema};
+use query::{Directive, Document, Field, InlineFragment, Operation, Selection, Value, ValueKind, Variables};
+use types::{query, result, schema, Name};
+
+use std::collections::HashMap;
 
 pub fn select_fields<O: schema::ResolveObject>(
     object: &O,
-    fields: &[query::Field],
+    fields: &[query::Selection],
 ) -> QlResult<result::Value> {
     Ok(result::Value::Object(result::Object {
         fields: fields
             .iter()
-            .map(|f| Ok((f.name.clone(), object.resolve_field(f)?)))
+            .map(|f| {
+                let f = f.assert_field();
+                Ok((f.response_key().clone(), object.resolve_field(f)?))
+            })
             .collect::<QlResult<Vec<_>>>()?,
     }))
 }
 
 pub struct Context {
-    variables: Variables,
+    // Variable values supplied by the client, or the variable's default.
+    variables: HashMap<Name, Value>,
+    // The operation's root field with all variables replaced by their values
+    // and all fragments expanded.
     query: Field,
 }
 
 impl Context {
-    pub fn new(variables: Variables, query: Field) -> Context {
-        Context {
-            variables,
-            query,
+    // Assumes `variables` has been validated against `operation`.
+    pub fn new(mut variables: Variables, operation: &Operation, document: &Document, schema: &schema::Schema) -> Context {
+        let variables: HashMap<Name, Value> = operation
+            .variables
+            .iter()
+            .filter_map(|def| {
+                let value = variables.remove(&def.name.0).or_else(|| def.default.clone());
+                value.map(|v| (def.name.clone(), v))
+            })
+            .collect();
+        let query = {
+            let expander = Expander {
+                variables: &variables,
+                document,
+                schema,
+            };
+            expander.field(&operation.field, Some(&Name(schema::SCHEMA_NAME.to_owned())))
+        };
+        Context { variables, query }
+    }
+
+    pub fn query(&self) -> &Field {
+        &self.query
+    }
+
+    pub fn variable(&self, name: &Name) -> Option<&Value> {
+        self.variables.get(name)
+    }
+}
+
+struct Expander<'a> {
+    variables: &'a HashMap<Name, Value>,
+    document: &'a Document,
+    schema: &'a schema::Schema,
+}
+
+impl<'a> Expander<'a> {
+    // `parent` is the type which `field` is a field of, if we know it.
+    fn field(&self, field: &Field, parent: Option<&Name>) -> Field {
+        let ty = parent
+            .and_then(|p| self.schema.items.get(p))
+            .and_then(|item| item.fields().iter().find(|f| f.name == field.name))
+            .and_then(|f| f.ty.named_type());
+
+        // We don't know which object type the value of a union or interface
+        // will be until it is resolved, so we collect the fields for each
+        // possible type separately.
+        let mut selections = vec![];
+        match ty.and_then(|ty| self.schema.items.get(ty)) {
+            Some(&schema::Item::Union(_)) | Some(&schema::Item::Interface(_)) => {
+                for m in self.schema.possible_types(ty.unwrap()) {
+                    let mut fields = vec![];
+                    self.collect_fields(&field.selections, Some(m), &mut fields);
+                    selections.push(Selection::InlineFragment(InlineFragment {
+                        type_condition: Some(m.clone()),
+                        directives: vec![],
+                        selections: fields,
+                        span: field.span,
+                    }));
+                }
+            }
+            _ => self.collect_fields(&field.selections, ty, &mut selections),
+        }
+
+        Field {
+            name: field.name.clone(),
+            alias: field.alias.clone(),
+            args: substitute_args(&field.args, self.variables),
+            // Directives have been applied when collecting fields.
+            directives: vec![],
+            selections,
+            span: field.span,
+        }
+    }
+
+    // Flattens `selections` into a list of fields of `ty`.
+    fn collect_fields(&self, selections: &[Selection], ty: Option<&Name>, fields: &mut Vec<Selection>) {
+        for s in selections {
+            if !self.included(s.directives()) {
+                continue;
+            }
+
+            match *s {
+                Selection::Field(ref f) => merge_field(fields, self.field(f, ty)),
+                Selection::FragmentSpread(ref spread) => {
+                    if let Some(fragment) = self.document.find_fragment(&spread.name) {
+                        if self.applies(&fragment.type_condition, ty) {
+                            self.collect_fields(&fragment.selections, ty, fields);
+                        }
+                    }
+                }
+                Selection::InlineFragment(ref inline) => {
+                    let applies = match inline.type_condition {
+                        Some(ref cond) => self.applies(cond, ty),
+                        None => true,
+                    };
+                    if applies {
+                        self.collect_fields(&inline.selections, ty, fields);
+                    }
+                }
+            }
         }
     }
+
+    // `@skip(if: true)` or `@include(if: false)` removes a selection from the
+    // query. Directives have been validated, so `if` is always a Boolean.
+    fn included(&self, directives: &[Directive]) -> bool {
+        directives.iter().all(|d| {
+            let cond = d.find_arg(&Name("if".to_owned()))
+                .and_then(|v| substitute_value(v, self.variables))
+                .map(|v| v.kind == ValueKind::Boolean(true));
+            match (&*d.name.0, cond) {
+                ("skip", Some(cond)) => !cond,
+                ("include", Some(cond)) => cond,
+                _ => true,
+            }
+        })
+    }
+
+    // `ty` is the object type of the value (fields of union or interface type
+    // are expanded for each possible object type). A fragment applies if its
+    // type condition is that type, an interface which the type implements
+    // (directly or indirectly), or a union containing it.
+    fn applies(&self, condition: &Name, ty: Option<&Name>) -> bool {
+        let ty = match ty {
+            Some(ty) => ty,
+            None => return false,
+        };
+        if condition == ty || self.schema.implements(ty, condition) {
+            return true;
+        }
+        match self.schema.items.get(condition) {
+            Some(schema::Item::Union(u)) => u.members.contains(ty),
+            _ => false,
+        }
+    }
+}
+
+// Fields with the same response key are merged into a single field.
+fn merge_field(selections: &mut Vec<Selection>, field: Field) {
+    let existing = selections.iter().position(|s| match *s {
+        Selection::Field(ref f) => f.response_key() == field.response_key(),
+        _ => false,
+    });
+    match existing {
+        Some(i) => if let Selection::Field(ref mut existing) = selections[i] {
+            merge_selections(&mut existing.selections, field.selections);
+        },
+        None => selections.push(Selection::Field(field)),
+    }
+}
+
+// `new` has already been expanded, so the only fragments are the per-type
+// fragments of a union or interface, which are merged if they have the same
+// type condition.
+fn merge_selections(selections: &mut Vec<Selection>, new: Vec<Selection>) {
+    for s in new {
+        match s {
+            Selection::Field(f) => merge_field(selections, f),
+            Selection::InlineFragment(inline) => {
+                let existing = selections.iter().position(|s| match *s {
+                    Selection::InlineFragment(ref i) => i.type_condition == inline.type_condition,
+                    _ => false,
+                });
+                match existing {
+                    Some(i) => if let Selection::InlineFragment(ref mut existing) = selections[i] {
+                        merge_selections(&mut existing.selections, inline.selections);
+                    },
+                    None => selections.push(Selection::InlineFragment(inline)),
+                }
+            }
+            Selection::FragmentSpread(_) => unreachable!(),
+        }
+    }
+}
+
+// The selections which apply to a value of object type `ty`. `selections` must
+// be the expanded selections of a union- or interface-typed field.
+pub fn type_selections<'a>(selections: &'a [Selection], ty: &str) -> &'a [Selection] {
+    for s in selections {
+        if let Selection::InlineFragment(ref inline) = *s {
+            if inline.type_condition.as_ref().is_some_and(|c| c.0 == ty) {
+                return &inline.selections;
+            }
+        }
+    }
+    &[]
+}
+
+// An argument or input field whose variable has no value is treated as if it
+// were not supplied at all.
+fn substitute_args(args: &[(Name, Value)], variables: &HashMap<Name, Value>) -> Vec<(Name, Value)> {
+    args.iter()
+        .filter_map(|(n, v)| substitute_value(v, variables).map(|v| (n.clone(), v)))
+        .collect()
+}
+
+// Returns `None` if `value` is a variable which has no value.
+fn substitute_value(value: &Value, variables: &HashMap<Name, Value>) -> Option<Value> {
+    let kind = match value.kind {
+        ValueKind::Variable(ref n) => return variables.get(n).cloned(),
+        ValueKind::Array(ref values) => ValueKind::Array(
+            values
+                .iter()
+                .map(|v| substitute_value(v, variables).unwrap_or_else(|| Value::new(ValueKind::Null, v.span)))
+                .collect(),
+        ),
+        ValueKind::Object(ref fields) => ValueKind::Object(substitute_args(fields, variables)),
+        ref kind => kind.clone(),
+    };
+    Some(Value::new(kind, value.span))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::parse_idl::parse_schema;
+    use parser::parse_query::parse_query;
+    use types::Span;
+
+    fn value(kind: ValueKind) -> Value {
+        Value::new(kind, Span::dummy())
+    }
+
+    fn schema() -> schema::Schema {
+        parse_schema(
+            "schema { query: Query }
+            type Query {
+                f(a: Int, b: String, c: Int, d: [Int], e: Input): String,
+                hero: Character,
+                humans: [Human],
+                search: [Search],
+            }
+            union Search = Human | Droid
+            type Droid {
+                id: ID!,
+                primaryFunction: String,
+            }
+            input Input {
+                x: Int,
+                y: String,
+            }
+            interface Character {
+                id: ID!,
+                name: String!,
+            }
+            type Human implements Character {
+                id: ID!,
+                name: String!,
+                homePlanet: String,
+            }",
+        ).unwrap()
+    }
+
+    fn field_names(field: &Field) -> Vec<&str> {
+        field.selections.iter().map(|s| &*s.assert_field().name.0).collect()
+    }
+
+    // The fields selected on a union- or interface-typed `field` for values of
+    // type `ty`.
+    fn type_field_names<'a>(field: &'a Field, ty: &str) -> Vec<&'a str> {
+        type_selections(&field.selections, ty).iter().map(|s| &*s.assert_field().name.0).collect()
+    }
+
+    #[test]
+    fn test_substitute_variables() {
+        let doc = parse_query(
+            "query ($a: Int, $b: String = \"foo\", $c: Int) {
+                f(a: $a, b: $b, c: $c, d: [$a, $c], e: {x: $c, y: $b})
+            }",
+        ).unwrap();
+        let mut variables = HashMap::new();
+        variables.insert("a".to_owned(), value(ValueKind::Int(42)));
+        let ctxt = Context::new(variables, &doc.operations[0], &doc, &schema());
+
+        assert_eq!(ctxt.variable(&Name("a".to_owned())), Some(&value(ValueKind::Int(42))));
+        assert_eq!(ctxt.variable(&Name("c".to_owned())), None);
+        assert_eq!(
+            ctxt.query().selections[0].assert_field().args,
+            vec![
+                (Name("a".to_owned()), value(ValueKind::Int(42))),
+                (Name("b".to_owned()), value(ValueKind::String("foo".to_owned()))),
+                (
+                    Name("d".to_owned()),
+                    value(ValueKind::Array(vec![value(ValueKind::Int(42)), value(ValueKind::Null)])),
+                ),
+                (
+                    Name("e".to_owned()),
+                    value(ValueKind::Object(vec![
+                        (Name("y".to_owned()), value(ValueKind::String("foo".to_owned()))),
+                    ])),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_fragments() {
+        let doc = parse_query(
+            "{
+                hero {
+                    ...names
+                    ... on Human { homePlanet }
+                    ... { id }
+                }
+                humans {
+                    ...names
+                    ... on Character { id }
+                    homePlanet
+                }
+            }
+
+            fragment names on Character {
+                id
+                name
+            }",
+        ).unwrap();
+        let ctxt = Context::new(HashMap::new(), &doc.operations[0], &doc, &schema());
+
+        let query = ctxt.query();
+        assert_eq!(field_names(query), vec!["hero", "humans"]);
+        // `Human` is the only type which implements `Character`.
+        let hero = query.selections[0].assert_field();
+        assert_eq!(hero.selections.len(), 1);
+        assert_eq!(type_field_names(hero, "Human"), vec!["id", "name", "homePlanet"]);
+        assert_eq!(field_names(query.selections[1].assert_field()), vec!["id", "name", "homePlanet"]);
+    }
+
+    #[test]
+    fn test_aliases() {
+        let doc = parse_query("{ a: hero { name } b: hero { id } hero { id } a: hero { id } }").unwrap();
+        let ctxt = Context::new(HashMap::new(), &doc.operations[0], &doc, &schema());
+
+        let keys: Vec<_> = ctxt.query()
+            .selections
+            .iter()
+            .map(|s| &*s.assert_field().response_key().0)
+            .collect();
+        assert_eq!(keys, vec!["a", "b", "hero"]);
+        assert_eq!(type_field_names(ctxt.query().selections[0].assert_field(), "Human"), vec!["name", "id"]);
+    }
+
+    #[test]
+    fn test_skip_include() {
+        let doc = parse_query(
+            "query ($yes: Boolean!, $no: Boolean!) {
+                hero {
+                    id @skip(if: true)
+                    name @include(if: $yes)
+                    homePlanet @include(if: $no)
+                    ... @skip(if: $no) { id }
+                    ...ids @include(if: false)
+                }
+            }
+
+            fragment ids on Character { id }",
+        ).unwrap();
+        let mut variables = HashMap::new();
+        variables.insert("yes".to_owned(), value(ValueKind::Boolean(true)));
+        variables.insert("no".to_owned(), value(ValueKind::Boolean(false)));
+        let ctxt = Context::new(variables, &doc.operations[0], &doc, &schema());
+
+        let hero = ctxt.query().selections[0].assert_field();
+        assert_eq!(type_field_names(hero, "Human"), vec!["name", "id"]);
+    }
+
+    #[test]
+    fn test_expand_unions() {
+        let doc = parse_query(
+            "{
+                search {
+                    ... on Human { homePlanet }
+                    ...ids
+                    ... on Droid { primaryFunction }
+                }
+                search { ... on Droid { id } }
+            }
+
+            fragment ids on Search {
+                ... on Character { id }
+                ... on Droid { primaryFunction }
+            }",
+        ).unwrap();
+        let ctxt = Context::new(HashMap::new(), &doc.operations[0], &doc, &schema());
+
+        let search = ctxt.query().selections[0].assert_field();
+        assert_eq!(ctxt.query().selections.len(), 1);
+        assert_eq!(search.selections.len(), 2);
+        let names = |ty: &str| -> Vec<&str> {
+            type_selections(&search.selections, ty).iter().map(|s| &*s.assert_field().name.0).collect()
+        };
+        assert_eq!(names("Human"), vec!["homePlanet", "id"]);
+        assert_eq!(names("Droid"), vec!["primaryFunction", "id"]);
+        assert_eq!(names("Character"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_expand_interfaces() {
+        let schema = parse_schema(
+            "schema { query: Query }
+            type Query {
+                node: Node,
+            }
+            interface Node {
+                id: ID!,
+            }
+            interface Entity implements Node {
+                id: ID!,
+                name: String!,
+            }
+            type Ship implements Entity & Node {
+                id: ID!,
+                name: String!,
+            }
+            type Planet implements Node {
+                id: ID!,
+                diameter: Int,
+            }",
+        ).unwrap();
+        let doc = parse_query(
+            "{
+                node {
+                    id
+                    ... on Entity { name }
+                    ... on Planet { diameter }
+                }
+            }",
+        ).unwrap();
+        let ctxt = Context::new(HashMap::new(), &doc.operations[0], &doc, &schema);
+
+        let node = ctxt.query().selections[0].assert_field();
+        assert_eq!(node.selections.len(), 2);
+        assert_eq!(type_field_names(node, "Planet"), vec!["id", "diameter"]);
+        assert_eq!(type_field_names(node, "Ship"), vec!["id", "name"]);
+    }
 }
@@ -0,0 +1,702 @@
+// Introspection, i.e., the `__schema` and `__type` fields of the query type,
+// which describe the schema itself.
+//
+// The fields are resolved here from the dynamic schema, rather than by the
+// query root, so they work for any `query::Root`.
+use {QlError, QlResult};
+use parser::parse_idl::parse_schema;
+use printer::{print_value, Style};
+use query::{self, Field, Selection, ValueKind};
+use result::{Object, Resolve, Value};
+use schema::{self, Argument, Item, Schema, Type, TypeKind};
+use types::Name;
+
+use std::mem;
+
+// The types of the introspection fields, as in the GraphQL spec.
+const INTROSPECTION_TYPES: &str = "
+    type __Schema {
+        types: [__Type!]!,
+        queryType: __Type!,
+        mutationType: __Type,
+        subscriptionType: __Type,
+        directives: [__Directive!]!,
+    }
+
+    type __Type {
+        kind: __TypeKind!,
+        name: String,
+        description: String,
+        fields(includeDeprecated: Boolean = false): [__Field!],
+        interfaces: [__Type!],
+        possibleTypes: [__Type!],
+        enumValues(includeDeprecated: Boolean = false): [__EnumValue!],
+        inputFields: [__InputValue!],
+        ofType: __Type,
+    }
+
+    type __Field {
+        name: String!,
+        description: String,
+        args: [__InputValue!]!,
+        type: __Type!,
+        isDeprecated: Boolean!,
+        deprecationReason: String,
+    }
+
+    type __InputValue {
+        name: String!,
+        description: String,
+        type: __Type!,
+        defaultValue: String,
+    }
+
+    type __EnumValue {
+        name: String!,
+        description: String,
+        isDeprecated: Boolean!,
+        deprecationReason: String,
+    }
+
+    enum __TypeKind {
+        SCALAR,
+        OBJECT,
+        INTERFACE,
+        UNION,
+        ENUM,
+        INPUT_OBJECT,
+        LIST,
+        NON_NULL,
+    }
+
+    type __Directive {
+        name: String!,
+        description: String,
+        locations: [__DirectiveLocation!]!,
+        args: [__InputValue!]!,
+    }
+
+    enum __DirectiveLocation {
+        QUERY,
+        MUTATION,
+        SUBSCRIPTION,
+        FIELD,
+        FRAGMENT_DEFINITION,
+        FRAGMENT_SPREAD,
+        INLINE_FRAGMENT,
+        SCHEMA,
+        SCALAR,
+        OBJECT,
+        FIELD_DEFINITION,
+        ARGUMENT_DEFINITION,
+        INTERFACE,
+        UNION,
+        ENUM,
+        ENUM_VALUE,
+        INPUT_OBJECT,
+        INPUT_FIELD_DEFINITION,
+    }
+";
+
+const BUILTIN_SCALARS: [&str; 5] = ["Boolean", "Float", "ID", "Int", "String"];
+
+// Adds the introspection types to `schema`, and the `__schema` and `__type`
+// fields to its query type.
+pub fn add_to_schema(schema: &mut Schema) {
+    let types = parse_schema(INTROSPECTION_TYPES).expect("Invalid introspection types");
+    schema.items.extend(types.items);
+
+    let query = match root_type(schema, "query") {
+        Some(query) => Name(query.to_owned()),
+        None => return,
+    };
+    if let Some(&mut Item::Object(ref mut obj)) = schema.items.get_mut(&query) {
+        obj.fields.push(schema::Field::field(
+            Name("__schema".to_owned()),
+            Type::non_null(TypeKind::Name(Name("__Schema".to_owned()))),
+        ));
+        obj.fields.push(schema::Field::fun(
+            Name("__type".to_owned()),
+            vec![Argument::new(Name("name".to_owned()), Type::non_null(TypeKind::String))],
+            Type::name("__Type"),
+        ));
+    }
+}
+
+// True for the fields of the query type which are resolved here.
+pub fn is_introspection_field(field: &Field) -> bool {
+    field.name.0 == "__schema" || field.name.0 == "__type"
+}
+
+// Adds the results of the introspection fields of `query` (the root field of
+// a query operation) to `result`, which is the result of the rest of `query`.
+// Fields are kept in the order they were selected.
+pub fn complete_result(result: Value, query: &Field, schema: &Schema) -> QlResult<Value> {
+    let mut result = match result {
+        Value::Object(obj) => obj,
+        result => return Ok(result),
+    };
+    for &mut (ref name, ref mut data) in &mut result.fields {
+        let data = match *data {
+            Value::Object(ref mut data) if name.0 == "data" => data,
+            _ => continue,
+        };
+        let mut resolved = mem::take(&mut data.fields);
+        for s in &query.selections {
+            let field = s.assert_field();
+            if is_introspection_field(field) {
+                data.fields.push((field.response_key().clone(), resolve_field(field, schema)?));
+            } else if let Some(i) = resolved.iter().position(|r| &r.0 == field.response_key()) {
+                data.fields.push(resolved.remove(i));
+            }
+        }
+    }
+    Ok(Value::Object(result))
+}
+
+fn resolve_field(field: &Field, schema: &Schema) -> QlResult<Value> {
+    match &*field.name.0 {
+        "__schema" => SchemaValue(schema).resolve(&field.selections),
+        "__type" => {
+            let name = match field.find_arg(&Name("name".to_owned())) {
+                Some(&query::Value {
+                    kind: ValueKind::String(ref name),
+                    ..
+                }) => name,
+                _ => {
+                    return Err(QlError::ExecutionError(
+                        "Required non-null argument not supplied: name".to_owned(),
+                    ))
+                }
+            };
+            // Unknown types are null.
+            let ty = type_names(schema).into_iter().find(|n| n == name).map(|name| TypeValue {
+                schema,
+                ty: TypeRef::Named(name),
+            });
+            ty.resolve(&field.selections)
+        }
+        _ => Err(unknown_field("Query", field)),
+    }
+}
+
+// The name of the type of the `query`, `mutation`, or `subscription` field of
+// the `schema` item.
+fn root_type<'a>(schema: &'a Schema, field: &str) -> Option<&'a str> {
+    schema
+        .items
+        .get(&Name(schema::SCHEMA_NAME.to_owned()))
+        .and_then(|s| s.fields().iter().find(|f| f.name.0 == field))
+        .and_then(|f| f.ty.named_type())
+        .map(|n| &*n.0)
+}
+
+// The built-in scalars and every type in `schema`, in name order.
+fn type_names(schema: &Schema) -> Vec<&str> {
+    let mut names: Vec<&str> = schema
+        .items
+        .keys()
+        .filter(|n| n.0 != schema::SCHEMA_NAME)
+        .map(|n| &*n.0)
+        .chain(BUILTIN_SCALARS.iter().cloned())
+        .collect();
+    names.sort();
+    names
+}
+
+// Resolves each selected field using `resolve_field`.
+fn select<F>(fields: &[Selection], resolve_field: F) -> QlResult<Value>
+where
+    F: Fn(&Field) -> QlResult<Value>,
+{
+    Ok(Value::Object(Object {
+        fields: fields
+            .iter()
+            .map(|f| {
+                let f = f.assert_field();
+                Ok((f.response_key().clone(), resolve_field(f)?))
+            })
+            .collect::<QlResult<Vec<_>>>()?,
+    }))
+}
+
+fn unknown_field(ty: &str, field: &Field) -> QlError {
+    QlError::ResolveError("field", format!("{}.{}", ty, field.name), None)
+}
+
+// The `includeDeprecated` argument of `fields` and `enumValues`.
+fn include_deprecated(field: &Field) -> bool {
+    match field.find_arg(&Name("includeDeprecated".to_owned())) {
+        Some(&query::Value {
+            kind: ValueKind::Boolean(b),
+            ..
+        }) => b,
+        _ => false,
+    }
+}
+
+fn string(s: Option<&str>) -> Value {
+    match s {
+        Some(s) => Value::String(s.to_owned()),
+        None => Value::Null,
+    }
+}
+
+struct SchemaValue<'a>(&'a Schema);
+
+impl<'a> Resolve for SchemaValue<'a> {
+    fn resolve(&self, fields: &[Selection]) -> QlResult<Value> {
+        let schema = self.0;
+        let named = |name: &'a str| TypeValue {
+            schema,
+            ty: TypeRef::Named(name),
+        };
+        let directives: Vec<schema::Directive> = schema::builtin_directives()
+            .into_iter()
+            .chain(schema.directives.iter().cloned())
+            .collect();
+
+        select(fields, |f| match &*f.name.0 {
+            "types" => {
+                let types: Vec<_> = type_names(schema).into_iter().map(&named).collect();
+                types.resolve(&f.selections)
+            }
+            "queryType" => root_type(schema, "query").map(&named).resolve(&f.selections),
+            "mutationType" => root_type(schema, "mutation").map(&named).resolve(&f.selections),
+            "subscriptionType" => root_type(schema, "subscription").map(&named).resolve(&f.selections),
+            "directives" => {
+                let directives: Vec<_> = directives
+                    .iter()
+                    .map(|directive| DirectiveValue { schema, directive })
+                    .collect();
+                directives.resolve(&f.selections)
+            }
+            _ => Err(unknown_field("__Schema", f)),
+        })
+    }
+}
+
+// A reference to a type, named types are only ever `Named` so that they are
+// described the same however they are reached.
+#[derive(Clone, Copy)]
+enum TypeRef<'a> {
+    NonNull(&'a Type),
+    // The type of the elements.
+    List(&'a Type),
+    Named(&'a str),
+}
+
+impl<'a> TypeRef<'a> {
+    fn new(ty: &'a Type) -> TypeRef<'a> {
+        if ty.nullable {
+            TypeRef::nullable(ty)
+        } else {
+            TypeRef::NonNull(ty)
+        }
+    }
+
+    // `ty`, ignoring whether it is nullable.
+    fn nullable(ty: &'a Type) -> TypeRef<'a> {
+        match ty.kind {
+            TypeKind::String => TypeRef::Named("String"),
+            TypeKind::Id => TypeRef::Named("ID"),
+            TypeKind::Int => TypeRef::Named("Int"),
+            TypeKind::Float => TypeRef::Named("Float"),
+            TypeKind::Boolean => TypeRef::Named("Boolean"),
+            TypeKind::Name(ref n) => TypeRef::Named(&n.0),
+            TypeKind::Array(ref inner) => TypeRef::List(inner),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct TypeValue<'a> {
+    schema: &'a Schema,
+    ty: TypeRef<'a>,
+}
+
+impl<'a> TypeValue<'a> {
+    fn item(&self) -> Option<&'a Item> {
+        match self.ty {
+            TypeRef::Named(name) => self.schema.items.get(&Name(name.to_owned())),
+            _ => None,
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self.ty {
+            TypeRef::NonNull(_) => "NON_NULL",
+            TypeRef::List(_) => "LIST",
+            TypeRef::Named(_) => match self.item() {
+                Some(&Item::Object(_)) | Some(&Item::Schema(_)) => "OBJECT",
+                Some(&Item::Interface(_)) => "INTERFACE",
+                Some(&Item::Union(_)) => "UNION",
+                Some(&Item::Enum(_)) => "ENUM",
+                Some(&Item::InputObject(_)) => "INPUT_OBJECT",
+                Some(&Item::Scalar(_)) | None => "SCALAR",
+            },
+        }
+    }
+
+    fn of_type(&self) -> Option<TypeValue<'a>> {
+        let ty = match self.ty {
+            TypeRef::NonNull(ty) => TypeRef::nullable(ty),
+            TypeRef::List(ty) => TypeRef::new(ty),
+            TypeRef::Named(_) => return None,
+        };
+        Some(TypeValue {
+            schema: self.schema,
+            ty,
+        })
+    }
+
+    fn named(&self, name: &'a Name) -> TypeValue<'a> {
+        TypeValue {
+            schema: self.schema,
+            ty: TypeRef::Named(&name.0),
+        }
+    }
+}
+
+impl<'a> Resolve for TypeValue<'a> {
+    fn resolve(&self, fields: &[Selection]) -> QlResult<Value> {
+        let schema = self.schema;
+        let item = self.item();
+
+        select(fields, |f| match &*f.name.0 {
+            "kind" => Ok(Value::String(self.kind().to_owned())),
+            "name" => Ok(match self.ty {
+                TypeRef::Named(name) => Value::String(name.to_owned()),
+                _ => Value::Null,
+            }),
+            "description" => Ok(string(item.and_then(|i| i.description()))),
+            "fields" => {
+                let fields: Option<Vec<_>> = match item {
+                    Some(&Item::Object(_)) | Some(&Item::Interface(_)) => Some(
+                        item.unwrap()
+                            .fields()
+                            .iter()
+                            .filter(|field| !field.name.0.starts_with("__"))
+                            .filter(|field| include_deprecated(f) || field.deprecation_reason().is_none())
+                            .map(|field| FieldValue { schema, field })
+                            .collect(),
+                    ),
+                    _ => None,
+                };
+                fields.resolve(&f.selections)
+            }
+            "interfaces" => {
+                let interfaces: Option<Vec<_>> = match item {
+                    Some(&Item::Object(_)) | Some(&Item::Interface(_)) => {
+                        Some(item.unwrap().implements().iter().map(|n| self.named(n)).collect())
+                    }
+                    _ => None,
+                };
+                interfaces.resolve(&f.selections)
+            }
+            "possibleTypes" => {
+                let types: Option<Vec<_>> = match (item, self.ty) {
+                    (Some(&Item::Union(_)), TypeRef::Named(name))
+                    | (Some(&Item::Interface(_)), TypeRef::Named(name)) => Some(
+                        schema
+                            .possible_types(&Name(name.to_owned()))
+                            .into_iter()
+                            .map(|n| self.named(n))
+                            .collect(),
+                    ),
+                    _ => None,
+                };
+                types.resolve(&f.selections)
+            }
+            "enumValues" => {
+                let values: Option<Vec<_>> = match item {
+                    Some(Item::Enum(e)) => Some(
+                        e.variants
+                            .iter()
+                            .filter(|v| include_deprecated(f) || v.deprecation_reason().is_none())
+                            .map(EnumValue)
+                            .collect(),
+                    ),
+                    _ => None,
+                };
+                values.resolve(&f.selections)
+            }
+            "inputFields" => {
+                let fields: Option<Vec<_>> = match item {
+                    Some(Item::InputObject(i)) => Some(
+                        i.fields
+                            .iter()
+                            .map(|field| InputValue::field(schema, field))
+                            .collect(),
+                    ),
+                    _ => None,
+                };
+                fields.resolve(&f.selections)
+            }
+            "ofType" => self.of_type().resolve(&f.selections),
+            _ => Err(unknown_field("__Type", f)),
+        })
+    }
+}
+
+struct FieldValue<'a> {
+    schema: &'a Schema,
+    field: &'a schema::Field,
+}
+
+impl<'a> Resolve for FieldValue<'a> {
+    fn resolve(&self, fields: &[Selection]) -> QlResult<Value> {
+        let schema = self.schema;
+        let field = self.field;
+
+        select(fields, |f| match &*f.name.0 {
+            "name" => Ok(Value::String(field.name.0.clone())),
+            "description" => Ok(string(field.description.as_deref())),
+            "args" => {
+                let args: Vec<_> = field.args.iter().map(|a| InputValue::arg(schema, a)).collect();
+                args.resolve(&f.selections)
+            }
+            "type" => TypeValue {
+                schema,
+                ty: TypeRef::new(&field.ty),
+            }.resolve(&f.selections),
+            "isDeprecated" => Ok(Value::Boolean(field.deprecation_reason().is_some())),
+            "deprecationReason" => Ok(string(field.deprecation_reason())),
+            _ => Err(unknown_field("__Field", f)),
+        })
+    }
+}
+
+// An argument, or a field of an input object.
+struct InputValue<'a> {
+    schema: &'a Schema,
+    name: &'a Name,
+    description: Option<&'a str>,
+    ty: &'a Type,
+    default: Option<&'a query::Value>,
+}
+
+impl<'a> InputValue<'a> {
+    fn arg(schema: &'a Schema, arg: &'a Argument) -> InputValue<'a> {
+        InputValue {
+            schema,
+            name: &arg.name,
+            description: arg.description.as_deref(),
+            ty: &arg.ty,
+            default: arg.default.as_ref(),
+        }
+    }
+
+    fn field(schema: &'a Schema, field: &'a schema::Field) -> InputValue<'a> {
+        InputValue {
+            schema,
+            name: &field.name,
+            description: field.description.as_deref(),
+            ty: &field.ty,
+            default: None,
+        }
+    }
+}
+
+impl<'a> Resolve for InputValue<'a> {
+    fn resolve(&self, fields: &[Selection]) -> QlResult<Value> {
+        select(fields, |f| match &*f.name.0 {
+            "name" => Ok(Value::String(self.name.0.clone())),
+            "description" => Ok(string(self.description)),
+            "type" => TypeValue {
+                schema: self.schema,
+                ty: TypeRef::new(self.ty),
+            }.resolve(&f.selections),
+            // As written in a query, e.g., `"foo"` for a string.
+            "defaultValue" => Ok(match self.default {
+                Some(v) => Value::String(print_value(v, Style::Minified)),
+                None => Value::Null,
+            }),
+            _ => Err(unknown_field("__InputValue", f)),
+        })
+    }
+}
+
+struct EnumValue<'a>(&'a schema::Variant);
+
+impl<'a> Resolve for EnumValue<'a> {
+    fn resolve(&self, fields: &[Selection]) -> QlResult<Value> {
+        let variant = self.0;
+        select(fields, |f| match &*f.name.0 {
+            "name" => Ok(Value::String(variant.name.0.clone())),
+            "description" => Ok(string(variant.description.as_deref())),
+            "isDeprecated" => Ok(Value::Boolean(variant.deprecation_reason().is_some())),
+            "deprecationReason" => Ok(string(variant.deprecation_reason())),
+            _ => Err(unknown_field("__EnumValue", f)),
+        })
+    }
+}
+
+struct DirectiveValue<'a> {
+    schema: &'a Schema,
+    directive: &'a schema::Directive,
+}
+
+impl<'a> Resolve for DirectiveValue<'a> {
+    fn resolve(&self, fields: &[Selection]) -> QlResult<Value> {
+        let directive = self.directive;
+        select(fields, |f| match &*f.name.0 {
+            "name" => Ok(Value::String(directive.name.0.clone())),
+            "description" => Ok(string(directive.description.as_deref())),
+            "locations" => Ok(Value::Array(
+                directive
+                    .locations
+                    .iter()
+                    .map(|l| Value::String(l.name().to_owned()))
+                    .collect(),
+            )),
+            "args" => {
+                let args: Vec<_> = directive
+                    .args
+                    .iter()
+                    .map(|a| InputValue::arg(self.schema, a))
+                    .collect();
+                args.resolve(&f.selections)
+            }
+            _ => Err(unknown_field("__Directive", f)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use handle_query;
+    use std::collections::HashMap;
+
+    // Resolves nothing itself, so the only data is from introspection.
+    struct Root;
+
+    impl query::Root for Root {
+        fn schema() -> Schema {
+            parse_schema(
+                r#"schema { query: Query }
+                type Query {
+                    hero(episode: Episode = JEDI): Character,
+                    search(filter: Filter): [Human!]!,
+                }
+                "A character in the films."
+                interface Character {
+                        "The name of the character."
+                    name: String!,
+                    fullName: String @deprecated(reason: "Use `name`."),
+                }
+                type Human implements Character {
+                    name: String!,
+                    fullName: String,
+                }
+                enum Episode {
+                    "Released in 1977."
+                    NEWHOPE,
+                    JEDI,
+                    PREQUELS @deprecated,
+                }
+                input Filter {
+                    name: String,
+                }"#,
+            ).unwrap()
+        }
+    }
+
+    impl Resolve for Root {
+        fn resolve(&self, _fields: &[Selection]) -> QlResult<Value> {
+            let data = Value::Object(Object { fields: vec![] });
+            Ok(Value::Object(Object {
+                fields: vec![(Name("data".to_owned()), data)],
+            }))
+        }
+    }
+
+    fn query(q: &str) -> String {
+        handle_query(q, None, HashMap::new(), Root).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_type() {
+        assert_eq!(
+            query(r#"{ __type(name: "Character") { kind, name, description, fields { name, description } } }"#),
+            r#"{data:{__type:{kind:"INTERFACE",name:"Character",description:"A character in the films.",fields:[{name:"name",description:"The name of the character."}]}}}"#
+        );
+        assert_eq!(
+            query(r#"{ __type(name: "Character") { possibleTypes { name }, interfaces { name } } }"#),
+            r#"{data:{__type:{possibleTypes:[{name:"Human"}],interfaces:[]}}}"#
+        );
+        assert_eq!(
+            query(r#"{ t: __type(name: "Episode") { enumValues { name, description }, fields { name } } }"#),
+            r#"{data:{t:{enumValues:[{name:"NEWHOPE",description:"Released in 1977."},{name:"JEDI",description:null}],fields:null}}}"#
+        );
+        assert_eq!(
+            query(r#"{ __type(name: "Filter") { kind, inputFields { name, type { name } } } }"#),
+            r#"{data:{__type:{kind:"INPUT_OBJECT",inputFields:[{name:"name",type:{name:"String"}}]}}}"#
+        );
+        assert_eq!(query(r#"{ __type(name: "Wookiee") { name } }"#), r#"{data:{__type:null}}"#);
+    }
+
+    #[test]
+    fn test_deprecated() {
+        assert_eq!(
+            query(r#"{ __type(name: "Character") { fields { name, isDeprecated } } }"#),
+            r#"{data:{__type:{fields:[{name:"name",isDeprecated:false}]}}}"#
+        );
+        assert_eq!(
+            query(r#"{ __type(name: "Character") { fields(includeDeprecated: true) { name, deprecationReason } } }"#),
+            r#"{data:{__type:{fields:[{name:"name",deprecationReason:null},{name:"fullName",deprecationReason:"Use `name`."}]}}}"#
+        );
+        assert_eq!(
+            query(r#"{ __type(name: "Human") { fields(includeDeprecated: true) { name, isDeprecated } } }"#),
+            r#"{data:{__type:{fields:[{name:"name",isDeprecated:false},{name:"fullName",isDeprecated:false}]}}}"#
+        );
+        assert_eq!(
+            query(r#"{ __type(name: "Episode") { enumValues(includeDeprecated: true) { name, isDeprecated, deprecationReason } } }"#),
+            concat!(
+                r#"{data:{__type:{enumValues:[{name:"NEWHOPE",isDeprecated:false,deprecationReason:null},"#,
+                r#"{name:"JEDI",isDeprecated:false,deprecationReason:null},"#,
+                r#"{name:"PREQUELS",isDeprecated:true,deprecationReason:"No longer supported"}]}}}"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_wrapped_types() {
+        assert_eq!(
+            query(
+                r#"{ __type(name: "Query") {
+                    fields { name, args { name, defaultValue, type { name } }, type { kind, name, ofType { kind, ofType { kind, ofType { name } } } } }
+                } }"#
+            ),
+            concat!(
+                r#"{data:{__type:{fields:["#,
+                r#"{name:"hero",args:[{name:"episode",defaultValue:"JEDI",type:{name:"Episode"}}],"#,
+                r#"type:{kind:"INTERFACE",name:"Character",ofType:null}},"#,
+                r#"{name:"search",args:[{name:"filter",defaultValue:null,type:{name:"Filter"}}],"#,
+                r#"type:{kind:"NON_NULL",name:null,ofType:{kind:"LIST",ofType:{kind:"NON_NULL",ofType:{name:"Human"}}}}}"#,
+                r#"]}}}"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_schema() {
+        assert_eq!(
+            query("{ __schema { queryType { name }, mutationType { name }, directives { name, locations } } }"),
+            concat!(
+                r#"{data:{__schema:{queryType:{name:"Query"},mutationType:null,directives:["#,
+                r#"{name:"skip",locations:["FIELD","FRAGMENT_SPREAD","INLINE_FRAGMENT"]},"#,
+                r#"{name:"include",locations:["FIELD","FRAGMENT_SPREAD","INLINE_FRAGMENT"]},"#,
+                r#"{name:"deprecated",locations:["FIELD_DEFINITION","ENUM_VALUE"]}"#,
+                r#"]}}}"#
+            )
+        );
+
+        let types = query("{ __schema { types { name } } }");
+        for name in &["Boolean", "Character", "Episode", "Filter", "Human", "Query", "String", "__Type"] {
+            assert!(types.contains(&format!(r#"{{name:"{}"}}"#, name)), "missing {} in {}", name, types);
+        }
+        assert!(!types.contains(r#""schema""#));
+    }
+}
@@ -7,21 +7,26 @@ extern crate failure;
 extern crate rls_span;
 
 use query::Variables;
-use types::{query, result, schema};
+use types::{query, result, schema, Name};
 
 pub mod execution;
+mod introspection;
 mod parser;
+pub mod printer;
 pub mod types;
 pub mod validation;
+pub mod visit;
 
-pub use parser::parse_idl::parse_schema;
+pub use parser::{Diagnostic, DiagnosticKind};
+pub use parser::parse_idl::{parse_schema, parse_schema_with_diagnostics};
+pub use parser::parse_query::parse_query_with_diagnostics;
+pub use types::schema::merge_schemas;
 
 pub type QlResult<T> = Result<T, QlError>;
 
 #[derive(Debug, Fail)]
 pub enum QlError {
-    #[fail(display = "Parsing error: {}", 0)] LexError(parser::lexer::LexError),
-    #[fail(display = "Parsing error: {:?}", 0)] ParseError(parser::ParseError),
+    #[fail(display = "Parsing error: {:?}", 0)] ParseError(Vec<parser::Diagnostic>),
     #[fail(display = "Validation error: {:?}", 0)] ValidationError(Vec<validation::Error>),
     #[fail(display = "Execution error: {}", 0)] ExecutionError(String),
     // (from, to)
@@ -31,13 +36,47 @@ pub enum QlError {
     ResolveError(&'static str, String, Option<String>),
 }
 
+impl QlError {
+    // The response to a query which failed validation, as described by the
+    // spec: `{errors: [...]}`, with an entry for each error. `None` for other
+    // kinds of error.
+    pub fn to_response(&self) -> Option<result::Value> {
+        match *self {
+            QlError::ValidationError(ref errs) => {
+                let errors = errs.iter().map(|e| e.to_value()).collect();
+                Some(result::Value::Object(result::Object {
+                    fields: vec![(Name("errors".to_owned()), result::Value::Array(errors))],
+                }))
+            }
+            _ => None,
+        }
+    }
+}
+
+// `operation_name` selects the operation to execute if `input` contains more
+// than one.
 pub fn handle_query<R: query::Root>(
     input: &str,
+    operation_name: Option<&str>,
     variables: Variables,
     root: R,
 ) -> QlResult<result::Value> {
+    let schema = &R::schema().with_introspection();
+    let document = query::Document::parse(input)?;
+    document.validate(schema)?;
+    document.execute(operation_name, variables, schema, root)
+}
+
+// Like `handle_query`, but for subscriptions. Each item in the result is the
+// response to a single event.
+pub fn handle_subscription<R: query::Root>(
+    input: &str,
+    operation_name: Option<&str>,
+    variables: Variables,
+    root: R,
+) -> QlResult<result::Stream> {
     let schema = &R::schema();
-    let query = query::Operation::parse(input)?;
-    query.validate(schema)?;
-    query.execute(variables, schema, root)
+    let document = query::Document::parse(input)?;
+    document.validate(schema)?;
+    document.subscribe(operation_name, variables, schema, root)
 }
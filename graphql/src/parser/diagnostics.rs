@@ -0,0 +1,92 @@
+// Errors found by the lexer and parsers. Both keep going after an error, so
+// there may be many diagnostics for a single document.
+
+use parser::lexer::LexError;
+use types::Span;
+
+use std::fmt;
+
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub message: String,
+    pub span: Span,
+    // Descriptions of the tokens which would have been accepted at `span`, may
+    // be empty if we don't know.
+    pub expected: Vec<&'static str>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DiagnosticKind {
+    // Lexical errors.
+    UnexpectedChar,
+    UnmatchedDelimiter,
+    UnclosedDelimiter,
+    UnterminatedString,
+    BadEscape,
+    BadNumber,
+    // Syntax errors.
+    UnexpectedToken,
+    UnexpectedEof,
+    // Definitions which conflict with an earlier definition, e.g., two types
+    // with the same name.
+    Conflict,
+    // Any other error found while parsing, e.g., a value which can't be
+    // converted.
+    Other,
+}
+
+impl Diagnostic {
+    pub fn new(kind: DiagnosticKind, message: &str, span: Span, expected: Vec<&'static str>) -> Diagnostic {
+        Diagnostic {
+            kind,
+            message: message.to_owned(),
+            span,
+            expected,
+        }
+    }
+}
+
+impl From<LexError> for Diagnostic {
+    fn from(e: LexError) -> Diagnostic {
+        let (kind, span) = match e {
+            LexError::Unexpected(_, span) => (DiagnosticKind::UnexpectedChar, span),
+            LexError::Unmatched(_, _, span) => (DiagnosticKind::UnmatchedDelimiter, span),
+            LexError::Unclosed(_, span) => (DiagnosticKind::UnclosedDelimiter, span),
+            LexError::UnterminatedString(span) => (DiagnosticKind::UnterminatedString, span),
+            LexError::BadEscape(span) => (DiagnosticKind::BadEscape, span),
+            LexError::BadNumber(span) => (DiagnosticKind::BadNumber, span),
+        };
+        let expected = match e {
+            LexError::Unmatched(expected, ..) | LexError::Unclosed(expected, _) => {
+                vec![close_str(expected)]
+            }
+            _ => vec![],
+        };
+        Diagnostic {
+            kind,
+            message: e.message(),
+            span,
+            expected,
+        }
+    }
+}
+
+fn close_str(c: char) -> &'static str {
+    match c {
+        '}' => "`}`",
+        ')' => "`)`",
+        ']' => "`]`",
+        _ => unreachable!(),
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.span, self.message)?;
+        if !self.expected.is_empty() {
+            write!(f, ", expected: {}", self.expected.join(" or "))?;
+        }
+        Ok(())
+    }
+}
@@ -1,46 +1,171 @@
+#[cfg(test)]
 use {QlError, QlResult};
+#[cfg(test)]
+use parser::Diagnostic;
 use parser::token::{Atom, Bracket, Token, TokenKind};
+use types::{Position, Span};
+
+use rls_span::{Column, Row};
 
+use std::char;
 use std::fmt;
 use std::iter::Peekable;
 use std::mem;
 use std::str::{CharIndices, FromStr};
 
+// Fails if there are any lexical errors. The parsers use
+// `tokenise_with_errors` so that they can recover, this is for tests.
+#[cfg(test)]
 pub fn tokenise<'a>(input: &'a str) -> QlResult<Vec<Token<'a>>> {
+    let (tokens, errors) = tokenise_with_errors(input);
+    if errors.is_empty() {
+        Ok(tokens)
+    } else {
+        Err(QlError::ParseError(errors.into_iter().map(Diagnostic::from).collect()))
+    }
+}
+
+// Lexes the whole of `input`, even if there are errors. Where there is an error,
+// the returned tokens are a best guess at what was intended.
+pub fn tokenise_with_errors<'a>(input: &'a str) -> (Vec<Token<'a>>, Vec<LexError>) {
     let lexer = Lexer::new(input);
     lexer.tokenise()
 }
 
-// pub type Span = ::rls_span::Span<::rls_span::ZeroIndexed>;
-
-// TODO should be able to track multiple errors
 #[derive(Clone, Debug)]
-// TODO all variants should include info for the error position
 pub enum LexError {
-    Unexpected(char),
-    // TODO could include span info with first thing
-    Unmatched(char, char),
+    Unexpected(char, Span),
+    // (expected, found, span of found)
+    Unmatched(char, char, Span),
+    // (expected, span of the opening delimiter)
+    Unclosed(char, Span),
+    UnterminatedString(Span),
+    BadEscape(Span),
+    // A malformed or out of range number.
+    BadNumber(Span),
+}
+
+impl LexError {
+    pub fn message(&self) -> String {
+        match *self {
+            LexError::Unexpected(c, _) => format!("Unexpected token: {}", c),
+            LexError::Unmatched(expected, found, _) => format!("Unmatched delimiter: expected {}, found {}", expected, found),
+            LexError::Unclosed(expected, _) => format!("Unclosed delimiter: expected {}", expected),
+            LexError::UnterminatedString(_) => "Unterminated string".to_owned(),
+            LexError::BadEscape(_) => "Invalid escape sequence in string".to_owned(),
+            LexError::BadNumber(_) => "Invalid number".to_owned(),
+        }
+    }
 }
 
 impl fmt::Display for LexError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            LexError::Unexpected(c) => writeln!(f, "Unexpected token: {}", c),
-            LexError::Unmatched(expected, found) => writeln!(f, "Unmatched delimiter: expected {}, found {}", expected, found),
+        let span = match *self {
+            LexError::Unexpected(_, span)
+            | LexError::Unmatched(_, _, span)
+            | LexError::Unclosed(_, span)
+            | LexError::UnterminatedString(span)
+            | LexError::BadEscape(span)
+            | LexError::BadNumber(span) => span,
+        };
+        write!(f, "{} at {}", self.message(), span)
+    }
+}
+
+// The value of a string literal, `raw` is the text between the quotes. Returns
+// `None` if `raw` contains an invalid escape sequence.
+pub fn string_value(raw: &str) -> Option<String> {
+    let mut result = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
         }
+        let escaped = match chars.next()? {
+            '"' => '"',
+            '\\' => '\\',
+            '/' => '/',
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'u' => {
+                let code = read_hex(&mut chars)?;
+                match code {
+                    // A surrogate pair, e.g., `😀`.
+                    0xD800..=0xDBFF => {
+                        if chars.next()? != '\\' || chars.next()? != 'u' {
+                            return None;
+                        }
+                        let low = read_hex(&mut chars)?;
+                        if !(0xDC00..=0xDFFF).contains(&low) {
+                            return None;
+                        }
+                        char::from_u32(0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00))?
+                    }
+                    _ => char::from_u32(code)?,
+                }
+            }
+            _ => return None,
+        };
+        result.push(escaped);
+    }
+    Some(result)
+}
+
+fn read_hex<I: Iterator<Item = char>>(chars: &mut I) -> Option<u32> {
+    let mut result = 0;
+    for _ in 0..4 {
+        result = result * 16 + chars.next()?.to_digit(16)?;
     }
+    Some(result)
 }
 
-macro lex_err($kind: ident $(, $body: expr)*) {
-    Err(QlError::LexError(LexError::$kind($($body,)*)))
+// The value of a block string literal, `raw` is the text between the triple
+// quotes. Follows the `BlockStringValue` algorithm in the spec: common
+// indentation and leading and trailing blank lines are removed.
+pub fn block_string_value(raw: &str) -> String {
+    let raw = raw.replace("\\\"\"\"", "\"\"\"");
+    let raw = raw.replace("\r\n", "\n").replace('\r', "\n");
+    let mut lines: Vec<&str> = raw.split('\n').collect();
+
+    fn indent(line: &str) -> usize {
+        line.len() - line.trim_start_matches([' ', '\t']).len()
+    }
+
+    let common_indent = lines[1..]
+        .iter()
+        .filter(|l| indent(l) < l.len())
+        .map(|l| indent(l))
+        .min();
+    if let Some(common_indent) = common_indent {
+        for line in &mut lines[1..] {
+            *line = if line.len() < common_indent {
+                ""
+            } else {
+                &line[common_indent..]
+            };
+        }
+    }
+
+    let blank = |l: &&str| indent(l) == l.len();
+    let start = lines.iter().position(|l| !blank(l)).unwrap_or(lines.len());
+    let end = lines.iter().rposition(|l| !blank(l)).map(|i| i + 1).unwrap_or(start);
+    lines[start..end].join("\n")
 }
 
 struct Lexer<'a> {
     input: &'a str,
     iter: Peekable<CharIndices<'a>>,
+    // The position of the next char in `iter`.
+    pos: Position,
     result: Vec<Token<'a>>,
-    tree_stack: Vec<(Bracket, Vec<Token<'a>>)>,
-    string: Option<usize>,
+    // Each entry is an open bracket, its index and position, and the tokens
+    // lexed before it.
+    tree_stack: Vec<(Bracket, usize, Position, Vec<Token<'a>>)>,
+    errors: Vec<LexError>,
 }
 
 impl<'a> Lexer<'a> {
@@ -48,175 +173,345 @@ impl<'a> Lexer<'a> {
         Lexer {
             input,
             iter: input.char_indices().peekable(),
+            pos: Position::new(Row::new_zero_indexed(0), Column::new_zero_indexed(0)),
             result: vec![],
             tree_stack: vec![],
-            string: None,
+            errors: vec![],
         }
     }
 
-    fn tokenise(mut self) -> QlResult<Vec<Token<'a>>> {
-        while let Some((i, c)) = self.iter.next() {
-            // TODO escaped closing quote
-            if self.string.is_some() && c != '"' {
-                continue;
-            }
-
+    fn tokenise(mut self) -> (Vec<Token<'a>>, Vec<LexError>) {
+        while let Some((i, c, start)) = self.next_char() {
             match c {
                 '#' => self.comment(i),
-                '{' => self.open_bracket(Bracket::Brace),
-                br @ '}' | br @ ']' | br @ ')' => self.close_bracket(br)?,
-                '[' => self.open_bracket(Bracket::Square),
-                '(' => self.open_bracket(Bracket::Paren),
-
-                '"' => match self.string {
-                    Some(_) => self.close_string(i),
-                    None => self.open_string(i),
-                },
-
-                '\n' => {
-                    // TODO span stuff
-                    self.atom(Atom::NewLine);
+                '{' => self.open_bracket(Bracket::Brace, i, start),
+                br @ '}' | br @ ']' | br @ ')' => self.close_bracket(br, i, start),
+                '[' => self.open_bracket(Bracket::Square, i, start),
+                '(' => self.open_bracket(Bracket::Paren, i, start),
+
+                '"' => self.string(i, start),
+
+                '\n' => self.atom(Atom::NewLine, i, start),
+                '!' => self.atom(Atom::Bang, i, start),
+                ':' => self.atom(Atom::Colon, i, start),
+                ',' => self.atom(Atom::Comma, i, start),
+                '$' => self.atom(Atom::Dollar, i, start),
+                '@' => self.atom(Atom::At, i, start),
+                '=' => self.atom(Atom::Equals, i, start),
+                '|' => self.atom(Atom::Pipe, i, start),
+                '&' => self.atom(Atom::Amp, i, start),
+                '.' => {
+                    if self.eat_char('.') && self.eat_char('.') {
+                        self.atom(Atom::Spread, i, start);
+                    } else {
+                        let span = self.span_from(i, start);
+                        self.errors.push(LexError::Unexpected('.', span));
+                    }
                 }
-                '!' => self.atom(Atom::Bang),
-                ':' => self.atom(Atom::Colon),
-                ',' => self.atom(Atom::Comma),
 
-                '-' => self.number(i),
-                c if c.is_digit(10) => self.number(i),
+                '-' => self.number(i, start),
+                c if c.is_ascii_digit() => self.number(i, start),
 
-                c if c.is_alphabetic() => self.name(i),
+                c if is_name_start(c) => self.name(i, start),
 
-                // This includes \r
-                c if c.is_whitespace() => {}
+                // The byte order mark is insignificant, like whitespace.
+                ' ' | '\t' | '\r' | '\u{feff}' => {}
 
-                c => return lex_err!(Unexpected, c),
+                c => {
+                    let span = self.span_from(i, start);
+                    self.errors.push(LexError::Unexpected(c, span));
+                }
             }
         }
 
-        for _tree in &self.tree_stack {
-            // TODO each tree is an unclosed delimiter error
+        // Close any unclosed trees so that the parser sees as much of the input
+        // as possible.
+        while let Some(&(br, lo, start, _)) = self.tree_stack.last() {
+            let span = Span::new(lo, lo + 1, start, Position::new(start.row, Column::new_zero_indexed(start.col.0 + 1)));
+            self.errors.push(LexError::Unclosed(br.close(), span));
+            self.finish_tree();
+        }
+
+        (self.result, self.errors)
+    }
+
+    // Returns the next char, its index, and its position.
+    fn next_char(&mut self) -> Option<(usize, char, Position)> {
+        let (i, c) = self.iter.next()?;
+        let pos = self.pos;
+        self.pos = if c == '\n' {
+            Position::new(Row::new_zero_indexed(pos.row.0 + 1), Column::new_zero_indexed(0))
+        } else {
+            Position::new(pos.row, Column::new_zero_indexed(pos.col.0 + 1))
+        };
+        Some((i, c, pos))
+    }
+
+    fn peek_char(&mut self) -> Option<char> {
+        self.iter.peek().map(|&(_, c)| c)
+    }
+
+    // Consume the next char if it is `c`.
+    fn eat_char(&mut self, c: char) -> bool {
+        if self.peek_char() == Some(c) {
+            self.next_char();
+            true
+        } else {
+            false
         }
+    }
 
-        if let Some(_s) = self.string {
-            // TODO unclosed string
+    // The index of the next char in `iter`.
+    fn next_index(&mut self) -> usize {
+        match self.iter.peek() {
+            Some(&(i, _)) => i,
+            None => self.input.len(),
         }
+    }
 
-        Ok(self.result)
+    // A span from `start` to the next char in `iter`.
+    fn span_from(&mut self, lo: usize, start: Position) -> Span {
+        let hi = self.next_index();
+        Span::new(lo, hi, start, self.pos)
     }
 
-    fn atom(&mut self, atom: Atom<'a>) {
+    fn atom(&mut self, atom: Atom<'a>, lo: usize, start: Position) {
+        let span = self.span_from(lo, start);
         self.result.push(Token {
             kind: TokenKind::Atom(atom),
-            //span: panic!(), // TODO
+            span,
         })
     }
 
-    fn number(&mut self, start: usize) {
-        let src = self.read_while(start, |c| c.is_digit(10));
-        let value = isize::from_str(src).expect("Couldn't parse number");
-        let token = Token {
-            kind: TokenKind::Atom(Atom::Number(value)),
-            //span: panic!(), // TODO
+    // IntValue or FloatValue, the first char (`-` or a digit) has been consumed.
+    fn number(&mut self, lo: usize, start: Position) {
+        if self.input[lo..].starts_with('-') {
+            match self.peek_char() {
+                Some(c) if c.is_ascii_digit() => {}
+                _ => {
+                    let span = self.span_from(lo, start);
+                    self.errors.push(LexError::Unexpected('-', span));
+                    return;
+                }
+            }
+        }
+
+        let atom = match self.read_number(lo) {
+            Some(atom) => atom,
+            None => {
+                let span = self.span_from(lo, start);
+                self.errors.push(LexError::BadNumber(span));
+                // A placeholder so that the parser can carry on.
+                Atom::Int(0)
+            }
         };
-        self.result.push(token);
+        self.atom(atom, lo, start);
     }
 
-    fn name(&mut self, start: usize) {
-        let value = self.read_while(start, |c| c.is_alphabetic() || c.is_numeric());
-        let token = Token {
-            kind: TokenKind::Atom(Atom::Name(value)),
-            //span: panic!(), // TODO
-        };
-        self.result.push(token);
+    // Returns `None` if the number is malformed.
+    fn read_number(&mut self, lo: usize) -> Option<Atom<'a>> {
+        // No leading zeros.
+        let digits = self.read_while(lo, |c| c.is_ascii_digit()).trim_start_matches('-');
+        let mut ok = digits.len() == 1 || !digits.starts_with('0');
+
+        let mut is_float = false;
+        if self.eat_char('.') {
+            is_float = true;
+            ok &= self.read_digits();
+        }
+        if self.eat_char('e') || self.eat_char('E') {
+            is_float = true;
+            if !self.eat_char('+') {
+                self.eat_char('-');
+            }
+            ok &= self.read_digits();
+        }
+
+        // A number must not be immediately followed by a name or another number.
+        let end = self.next_index();
+        if !self.read_while(end, |c| c == '.' || is_name_continue(c)).is_empty() || !ok {
+            return None;
+        }
+
+        let src = &self.input[lo..end];
+        if is_float {
+            match f64::from_str(src) {
+                Ok(n) if n.is_finite() => Some(Atom::Float(n)),
+                _ => None,
+            }
+        } else {
+            i64::from_str(src).ok().map(Atom::Int)
+        }
+    }
+
+    // Returns false if there were no digits.
+    fn read_digits(&mut self) -> bool {
+        let lo = self.next_index();
+        !self.read_while(lo, |c| c.is_ascii_digit()).is_empty()
+    }
+
+    fn name(&mut self, lo: usize, start: Position) {
+        let value = self.read_while(lo, is_name_continue);
+        self.atom(Atom::Name(value), lo, start);
     }
 
     fn comment(&mut self, start: usize) {
-        self.read_while(start, |c| c != '\n');
+        self.read_while(start, |c| c != '\n' && c != '\r');
     }
 
     fn read_while<F>(&mut self, start: usize, f: F) -> &'a str
     where
         F: Fn(char) -> bool,
     {
-        while let Some(&(_, c)) = self.iter.peek() {
+        while let Some(c) = self.peek_char() {
             if !f(c) {
                 break;
             }
-            self.iter.next();
+            self.next_char();
         }
 
-        let end = match self.iter.peek() {
-            Some(&(i, _)) => i,
-            None => self.input.len(),
-        };
-
+        let end = self.next_index();
         &self.input[start..end]
     }
 
-    fn open_bracket(&mut self, br: Bracket) {
+    fn open_bracket(&mut self, br: Bracket, lo: usize, start: Position) {
         let mut new_result = Vec::new();
         mem::swap(&mut self.result, &mut new_result);
-        self.tree_stack.push((br, new_result));
+        self.tree_stack.push((br, lo, start, new_result));
     }
 
-    fn close_bracket(&mut self, closer: char) -> QlResult<()> {
-        let (br, mut prev_result) = match self.tree_stack.pop() {
-            Some(x) => x,
+    fn close_bracket(&mut self, closer: char, index: usize, pos: Position) {
+        let span = self.span_from(index, pos);
+        match self.tree_stack.last() {
+            Some(&(br, ..)) if br.close() == closer => {}
+            Some(&(br, ..)) => {
+                self.errors.push(LexError::Unmatched(br.close(), closer, span));
+                // If `closer` closes an outer tree, then assume the inner trees
+                // were left unclosed, otherwise ignore it.
+                if !self.tree_stack.iter().any(|&(br, ..)| br.close() == closer) {
+                    return;
+                }
+                while self.tree_stack.last().map(|&(br, ..)| br.close()) != Some(closer) {
+                    self.finish_tree();
+                }
+            }
             None => {
-                return lex_err!(Unexpected, closer);
+                self.errors.push(LexError::Unexpected(closer, span));
+                return;
             }
-        };
-
-        if br.close() != closer {
-            return lex_err!(Unmatched, br.close(), closer);
         }
 
+        self.finish_tree();
+    }
+
+    // Pops the innermost tree and adds it to the result, the tree ends at the
+    // next char in `iter`.
+    // Pre-condition: !self.tree_stack.is_empty()
+    fn finish_tree(&mut self) {
+        let (br, lo, start, mut prev_result) = self.tree_stack.pop().expect("Missing tree in lexer");
         mem::swap(&mut prev_result, &mut self.result);
         let token = Token {
             kind: TokenKind::Tree(br, prev_result),
-            //span: panic!(), // TODO
+            span: self.span_from(lo, start),
         };
-
         self.result.push(token);
-
-        Ok(())
     }
 
-    // Pre-condition: self.string.is_none()
-    fn open_string(&mut self, index: usize) {
-        self.string = Some(index + 1);
+    // StringValue or BlockString, the opening `"` has been consumed.
+    fn string(&mut self, lo: usize, start: Position) {
+        if self.input[lo..].starts_with("\"\"\"") {
+            self.next_char();
+            self.next_char();
+            return self.block_string(lo, start);
+        }
+
+        loop {
+            match self.peek_char() {
+                Some('"') => {
+                    let end = self.next_index();
+                    self.next_char();
+                    let raw = &self.input[lo + 1..end];
+                    if string_value(raw).is_none() {
+                        let span = self.span_from(lo, start);
+                        self.errors.push(LexError::BadEscape(span));
+                    }
+                    self.atom(Atom::String(raw), lo, start);
+                    return;
+                }
+                Some('\n') | Some('\r') | None => {
+                    // Treat the rest of the line as the string.
+                    let span = self.span_from(lo, start);
+                    self.errors.push(LexError::UnterminatedString(span));
+                    let raw = &self.input[lo + 1..span.hi];
+                    self.atom(Atom::String(raw), lo, start);
+                    return;
+                }
+                // Skip the escaped char so that `\"` does not end the string,
+                // escapes are checked by `string_value`.
+                Some('\\') => {
+                    self.next_char();
+                    match self.peek_char() {
+                        Some('\n') | Some('\r') | None => {}
+                        Some(_) => {
+                            self.next_char();
+                        }
+                    }
+                }
+                Some(_) => {
+                    self.next_char();
+                }
+            }
+        }
     }
 
-    // Pre-condition: self.string.is_some()
-    fn close_string(&mut self, index: usize) {
-        let start = self.string.take().expect("Missing string buffer in lexer");
-        let token = Token {
-            kind: TokenKind::Atom(Atom::String(&self.input[start..index])),
-            //span: panic!(), // TODO
-        };
-        self.result.push(token);
+    fn block_string(&mut self, lo: usize, start: Position) {
+        loop {
+            let i = self.next_index();
+            let rest = &self.input[i..];
+            if rest.starts_with("\\\"\"\"") {
+                for _ in 0..4 {
+                    self.next_char();
+                }
+            } else if rest.starts_with("\"\"\"") {
+                for _ in 0..3 {
+                    self.next_char();
+                }
+                self.atom(Atom::BlockString(&self.input[lo + 3..i]), lo, start);
+                return;
+            } else if self.next_char().is_none() {
+                let span = self.span_from(lo, start);
+                self.errors.push(LexError::UnterminatedString(span));
+                self.atom(Atom::BlockString(&self.input[lo + 3..]), lo, start);
+                return;
+            }
+        }
     }
 }
 
+fn is_name_start(c: char) -> bool {
+    c == '_' || c.is_ascii_alphabetic()
+}
+
+fn is_name_continue(c: char) -> bool {
+    c == '_' || c.is_ascii_alphanumeric()
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
     #[test]
     fn test_empty() {
-        let lexer = Lexer::new("");
-        let result = lexer.tokenise().unwrap();
+        let result = tokenise("").unwrap();
         assert!(result.is_empty());
 
-        let lexer = Lexer::new("   ");
-        let result = lexer.tokenise().unwrap();
+        let result = tokenise("   ").unwrap();
         assert!(result.is_empty());
     }
 
     #[test]
     fn test_numbers() {
-        let lexer = Lexer::new("0 42 -3 56785665657656");
-        let result = lexer.tokenise().unwrap();
+        let result = tokenise("0 42 -3 56785665657656").unwrap();
         assert_eq!(result.len(), 4);
         assert_eq!(assert_number(&result[0]), 0);
         assert_eq!(assert_number(&result[1]), 42);
@@ -224,18 +519,45 @@ mod test {
         assert_eq!(assert_number(&result[3]), 56785665657656);
     }
 
+    #[test]
+    fn test_floats() {
+        let result = tokenise("0.5 -3.25 1e3 6.02E+23 1.5e-3").unwrap();
+        assert_eq!(result.len(), 5);
+        assert_eq!(assert_float(&result[0]), 0.5);
+        assert_eq!(assert_float(&result[1]), -3.25);
+        assert_eq!(assert_float(&result[2]), 1000.0);
+        assert_eq!(assert_float(&result[3]), 6.02e23);
+        assert_eq!(assert_float(&result[4]), 0.0015);
+    }
+
+    #[test]
+    fn test_bad_numbers() {
+        for input in &["007", "1.", ".5", "1e", "1.5e+", "12abc", "1.2.3", "-", "- 1", "99999999999999999999"] {
+            match Lexer::new(input).tokenise().1.first() {
+                Some(_) => {}
+                result => panic!("Found: {:?} for {}", result, input),
+            }
+        }
+    }
+
     #[test]
     fn test_names() {
-        let lexer = Lexer::new("a foo bar42 ላዊዲሞክ");
-        let result = lexer.tokenise().unwrap();
-        assert_eq!(result.len(), 4);
+        let result = tokenise("a foo bar42 __typename home_planet _").unwrap();
+        assert_eq!(result.len(), 6);
         assert_eq!(assert_name(&result[0]), "a");
         assert_eq!(assert_name(&result[1]), "foo");
         assert_eq!(assert_name(&result[2]), "bar42");
-        assert_eq!(assert_name(&result[3]), "ላዊዲሞክ");
+        assert_eq!(assert_name(&result[3]), "__typename");
+        assert_eq!(assert_name(&result[4]), "home_planet");
+        assert_eq!(assert_name(&result[5]), "_");
+
+        // Names are ASCII only.
+        match Lexer::new("ላዊዲሞክ").tokenise().1.first() {
+            Some(&LexError::Unexpected('ላ', _)) => {}
+            result => panic!("Found: {:?}", result),
+        }
 
-        let lexer = Lexer::new("a\nb");
-        let result = lexer.tokenise().unwrap();
+        let result = tokenise("a\nb").unwrap();
         assert_eq!(result.len(), 3);
         assert_eq!(assert_name(&result[0]), "a");
         assert_eq!(assert_atom(&result[1]), Atom::NewLine);
@@ -244,26 +566,77 @@ mod test {
 
     #[test]
     fn test_atoms() {
-        let lexer = Lexer::new(":,! !");
-        let result = lexer.tokenise().unwrap();
+        let result = tokenise(":,! !").unwrap();
         assert_eq!(result.len(), 4);
         assert_eq!(assert_atom(&result[0]), Atom::Colon);
         assert_eq!(assert_atom(&result[1]), Atom::Comma);
         assert_eq!(assert_atom(&result[2]), Atom::Bang);
         assert_eq!(assert_atom(&result[3]), Atom::Bang);
+
+        let result = tokenise("$@=|&...").unwrap();
+        assert_eq!(result.len(), 6);
+        assert_eq!(assert_atom(&result[0]), Atom::Dollar);
+        assert_eq!(assert_atom(&result[1]), Atom::At);
+        assert_eq!(assert_atom(&result[2]), Atom::Equals);
+        assert_eq!(assert_atom(&result[3]), Atom::Pipe);
+        assert_eq!(assert_atom(&result[4]), Atom::Amp);
+        assert_eq!(assert_atom(&result[5]), Atom::Spread);
+
+        assert!(tokenise("..").is_err());
     }
 
     #[test]
     // Also tests newline/whitespace handling.
     fn test_strings() {
-        let lexer = Lexer::new("  \"foo\"\r\n    \"bar\"");
-        let result = lexer.tokenise().unwrap();
+        let result = tokenise("  \"foo\"\r\n    \"bar\"").unwrap();
         assert_eq!(result.len(), 3);
         assert_eq!(assert_string(&result[0]), "foo");
         assert_eq!(assert_atom(&result[1]), Atom::NewLine);
         assert_eq!(assert_string(&result[2]), "bar");
     }
 
+    #[test]
+    fn test_string_escapes() {
+        let result = tokenise(r#""a \"quoted\" \\ string" "\u00e9\n\t\/" "\ud83d\ude00""#).unwrap();
+        assert_eq!(result.len(), 3);
+        assert_eq!(assert_string(&result[0]), r#"a \"quoted\" \\ string"#);
+        assert_eq!(string_value(assert_string(&result[0])).unwrap(), r#"a "quoted" \ string"#);
+        assert_eq!(string_value(assert_string(&result[1])).unwrap(), "é\n\t/");
+        assert_eq!(string_value(assert_string(&result[2])).unwrap(), "😀");
+
+        for input in &[r#""\q""#, r#""\u12""#, r#""\ud83d""#] {
+            match Lexer::new(input).tokenise().1.first() {
+                Some(&LexError::BadEscape(_)) => {}
+                result => panic!("Found: {:?} for {}", result, input),
+            }
+        }
+        for input in &["\"foo", "\"foo\nbar\"", r#""foo\""#] {
+            match Lexer::new(input).tokenise().1.first() {
+                Some(&LexError::UnterminatedString(_)) => {}
+                result => panic!("Found: {:?} for {}", result, input),
+            }
+        }
+    }
+
+    #[test]
+    fn test_block_strings() {
+        let input = "\"\"\"\n    Hello,\n      \"World\"!\n\n    \\\"\"\"\n  \"\"\" \"\"\"\"\"\"";
+        let result = tokenise(input).unwrap();
+        assert_eq!(result.len(), 2);
+        let raw = match assert_atom(&result[0]) {
+            Atom::BlockString(s) => s,
+            _ => panic!("Non-block string token"),
+        };
+        assert_eq!(block_string_value(raw), "Hello,\n  \"World\"!\n\n\"\"\"");
+        assert_eq!(result[0].span.end.row.0, 5);
+        assert_eq!(assert_atom(&result[1]), Atom::BlockString(""));
+
+        match Lexer::new("\"\"\"foo\"\"").tokenise().1.first() {
+            Some(&LexError::UnterminatedString(_)) => {}
+            result => panic!("Found: {:?}", result),
+        }
+    }
+
     #[test]
     fn test_query() {
         let input = r"{
@@ -273,8 +646,7 @@ mod test {
             id
           }
         }";
-        let lexer = Lexer::new(input);
-        let result = lexer.tokenise().unwrap();
+        let result = tokenise(input).unwrap();
         assert_eq!(result.len(), 1);
         assert_eq!(
             result[0].to_string().replace(' ', ""),
@@ -301,8 +673,7 @@ mod test {
             id
           }
         }";
-        let lexer = Lexer::new(input);
-        let result = lexer.tokenise().unwrap();
+        let result = tokenise(input).unwrap();
         assert_eq!(result.len(), 1);
         assert_eq!(
             result[0].to_string().replace(' ', ""),
@@ -310,15 +681,81 @@ mod test {
         );
     }
 
-    // TODO test: errors
+    #[test]
+    fn test_spans() {
+        let result = tokenise("foo\n  (bar: \"baz\")").unwrap();
+        assert_eq!(result.len(), 3);
+        assert_eq!((result[0].span.lo, result[0].span.hi), (0, 3));
+        assert_eq!(result[2].span.lo, 6);
+        assert_eq!(result[2].span.hi, 18);
+        assert_eq!(result[2].span.start.row.0, 1);
+        assert_eq!(result[2].span.start.col.0, 2);
+        assert_eq!(result[2].span.end.col.0, 14);
+        match result[2].kind {
+            TokenKind::Tree(_, ref toks) => {
+                assert_eq!((toks[0].span.lo, toks[0].span.hi), (7, 10));
+                assert_eq!((toks[2].span.lo, toks[2].span.hi), (12, 17));
+                assert_eq!(toks[2].span.start.col.0, 8);
+            }
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_delimiter_errors() {
+        let (tokens, errors) = tokenise_with_errors("{ a ) b }");
+        assert_eq!(errors.len(), 1);
+        match errors[0] {
+            LexError::Unmatched('}', ')', span) => assert_eq!(span.lo, 4),
+            ref e => panic!("Found: {:?}", e),
+        }
+        assert_eq!(tokens.len(), 1);
+        match tokens[0].kind {
+            TokenKind::Tree(Bracket::Brace, ref toks) => assert_eq!(toks.len(), 2),
+            _ => panic!(),
+        }
+
+        // The inner tree is closed by the outer tree's closer.
+        let (tokens, errors) = tokenise_with_errors("{ a ( b }");
+        match errors[..] {
+            [LexError::Unmatched(')', '}', _)] => {}
+            ref e => panic!("Found: {:?}", e),
+        }
+        assert_eq!(tokens.len(), 1);
 
-    fn assert_number(tok: &Token) -> isize {
+        let (tokens, errors) = tokenise_with_errors("a }");
+        match errors[..] {
+            [LexError::Unexpected('}', span)] => assert_eq!(span.lo, 2),
+            ref e => panic!("Found: {:?}", e),
+        }
+        assert_eq!(tokens.len(), 1);
+
+        // Unclosed trees are reported at their opening delimiter, innermost
+        // first, and end at the end of the input.
+        let (tokens, errors) = tokenise_with_errors("{ a [ b\n");
+        match errors[..] {
+            [LexError::Unclosed(']', inner), LexError::Unclosed('}', outer)] => {
+                assert_eq!((inner.lo, outer.lo), (4, 0));
+            }
+            ref e => panic!("Found: {:?}", e),
+        }
+        assert_eq!((tokens[0].span.lo, tokens[0].span.hi), (0, 8));
+    }
+
+    fn assert_number(tok: &Token) -> i64 {
         match assert_atom(tok) {
-            Atom::Number(n) => n,
+            Atom::Int(n) => n,
             _ => panic!("Non-number token, expected number"),
         }
     }
 
+    fn assert_float(tok: &Token) -> f64 {
+        match assert_atom(tok) {
+            Atom::Float(n) => n,
+            _ => panic!("Non-float token, expected float"),
+        }
+    }
+
     fn assert_name<'a>(tok: &Token<'a>) -> &'a str {
         match assert_atom(tok) {
             Atom::Name(n) => n,
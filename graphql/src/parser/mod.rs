@@ -1,7 +1,8 @@
+mod diagnostics;
 mod parse_base;
 pub mod parse_idl;
 pub mod parse_query;
 pub mod lexer;
 mod token;
 
-pub use self::parse_base::ParseError;
+pub use self::diagnostics::{Diagnostic, DiagnosticKind};
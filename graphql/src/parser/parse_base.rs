@@ -1,25 +1,70 @@
 // Code which is shared between the IDL and query parsers
 
 use {QlError, QlResult};
+use parser::{Diagnostic, DiagnosticKind};
 use parser::token::{Atom, Bracket, Token, TokenKind};
-use types::Name;
+use schema::{Type, TypeKind};
+use types::{Name, Position, Span};
 
-#[derive(Debug)]
-pub struct ParseError(pub &'static str);
+use rls_span::Column;
+
+use std::cell::RefCell;
+use std::rc::Rc;
 
 #[derive(Debug)]
 pub struct TokenStream<'a> {
     tokens: &'a [Token<'a>],
+    // Where the stream ends, used to report unexpected ends of input.
+    end: Span,
+    // The span of the most recently consumed token.
+    prev: Span,
+    // Errors which we have recovered from. Shared between a stream and the
+    // streams for its token trees.
+    errors: Rc<RefCell<Vec<Diagnostic>>>,
 }
 
 impl<'a> TokenStream<'a> {
-    pub fn new(tokens: &'a [Token<'a>]) -> TokenStream<'a> {
-        TokenStream { tokens }
+    pub fn new(tokens: &'a [Token<'a>], end: Span) -> TokenStream<'a> {
+        TokenStream {
+            tokens,
+            end,
+            prev: Span::dummy(),
+            errors: Rc::new(RefCell::new(vec![])),
+        }
+    }
+
+    // A stream over the whole of `tokens`.
+    pub fn top_level(tokens: &'a [Token<'a>]) -> TokenStream<'a> {
+        let end = match tokens.last() {
+            Some(tok) => tok.span.shrink_to_hi(),
+            None => Span::dummy(),
+        };
+        TokenStream::new(tokens, end)
+    }
+
+    // A stream over the contents of a token tree, `tok`. The stream ends at the
+    // tree's closing bracket, or after its last token if the tree is unclosed
+    // (the lexer ends unclosed trees at the end of the input).
+    pub fn tree(&self, tok: &'a Token<'a>, tokens: &'a [Token<'a>]) -> TokenStream<'a> {
+        let span = tok.span;
+        let end = match tokens.last() {
+            Some(last) if last.span.hi == span.hi => last.span.shrink_to_hi(),
+            _ => {
+                let close = Position::new(span.end.row, Column::new_zero_indexed(span.end.col.0.saturating_sub(1)));
+                Span::new(span.hi.saturating_sub(1), span.hi, close, span.end)
+            }
+        };
+        TokenStream {
+            tokens,
+            end,
+            prev: Span::dummy(),
+            errors: self.errors.clone(),
+        }
     }
 
     pub fn next_tok(&mut self) -> QlResult<&'a Token<'a>> {
         if self.tokens.is_empty() {
-            return parse_err!("Unexpected end of stream");
+            return eof_err!(self.end);
         }
         let result = &self.tokens[0];
         self.bump();
@@ -28,6 +73,7 @@ impl<'a> TokenStream<'a> {
 
     // Precondition: !self.tokens.is_empty()
     pub fn bump(&mut self) {
+        self.prev = self.tokens[0].span;
         self.tokens = &self.tokens[1..];
     }
 
@@ -35,10 +81,23 @@ impl<'a> TokenStream<'a> {
         self.tokens.get(0)
     }
 
+    pub fn prev_span(&self) -> Span {
+        self.prev
+    }
+
+    // The span of the next token, or the end of the stream.
+    pub fn peek_span(&mut self) -> Span {
+        match self.peek_tok() {
+            Some(tok) => tok.span,
+            None => self.end,
+        }
+    }
+
     pub fn eat(&mut self, atom: Atom<'a>) -> QlResult<()> {
-        match self.next_tok()?.kind {
+        let tok = self.next_tok()?;
+        match tok.kind {
             TokenKind::Atom(a) if a == atom => Ok(()),
-            _ => parse_err!("Unexpected token"),
+            _ => parse_err!("Unexpected token", tok.span, atom.describe()),
         }
     }
 
@@ -65,9 +124,12 @@ impl<'a> TokenStream<'a> {
     where
         F: Fn(&mut TokenStream) -> QlResult<Option<T>>,
     {
-        f(self).and_then(|n| n.ok_or_else(|| QlError::ParseError(ParseError("Unexpected eof"))))
+        let end = self.end;
+        f(self).and_then(|n| n.map_or_else(|| eof_err!(end), Ok))
     }
 
+    // If a list item has an error, the error is recorded and we skip to the
+    // next item.
     pub fn parse_list<F, T>(&mut self, f: F) -> QlResult<Vec<T>>
     where
         F: Fn(&mut TokenStream) -> QlResult<Option<T>>,
@@ -75,8 +137,16 @@ impl<'a> TokenStream<'a> {
         self.ignore_newlines();
 
         let mut result = vec![];
-        while let Some(arg) = f(self)? {
-            result.push(arg);
+        loop {
+            let remaining = self.tokens.len();
+            match f(self) {
+                Ok(Some(arg)) => result.push(arg),
+                Ok(None) => break,
+                Err(e) => {
+                    self.record(e);
+                    self.skip_item(remaining);
+                }
+            }
             self.maybe_eat(Atom::Comma);
             self.ignore_newlines();
         }
@@ -84,6 +154,59 @@ impl<'a> TokenStream<'a> {
         Ok(result)
     }
 
+    // Skips to the end of the current list item, i.e., the next comma or newline.
+    // `remaining` is the number of tokens in the stream at the start of the item.
+    fn skip_item(&mut self, remaining: usize) {
+        if self.tokens.len() == remaining && !self.tokens.is_empty() {
+            self.bump();
+        }
+        while let Some(tok) = self.peek_tok() {
+            match tok.kind {
+                TokenKind::Atom(Atom::Comma) | TokenKind::Atom(Atom::NewLine) => return,
+                _ => self.bump(),
+            }
+        }
+    }
+
+    // Skips tokens until `f` returns true for the next token or the stream is
+    // empty. Always skips at least one token.
+    pub fn skip_until<F>(&mut self, f: F)
+    where
+        F: Fn(&Token) -> bool,
+    {
+        if !self.tokens.is_empty() {
+            self.bump();
+        }
+        while let Some(tok) = self.peek_tok() {
+            if f(tok) {
+                return;
+            }
+            self.bump();
+        }
+    }
+
+    // Record a parse error so that parsing can continue. Other errors are
+    // recorded at the most recently consumed token.
+    pub fn record(&mut self, e: QlError) {
+        match e {
+            QlError::ParseError(errs) => self.errors.borrow_mut().extend(errs),
+            e => {
+                let d = Diagnostic::new(DiagnosticKind::Other, &e.to_string(), self.prev, vec![]);
+                self.errors.borrow_mut().push(d);
+            }
+        }
+    }
+
+    pub fn record_all<I: IntoIterator<Item = Diagnostic>>(&mut self, errs: I) {
+        self.errors.borrow_mut().extend(errs);
+    }
+
+    pub fn take_errors(&mut self) -> Vec<Diagnostic> {
+        let mut errors = self.errors.borrow_mut();
+        errors.sort_by_key(|e| e.span.lo);
+        errors.drain(..).collect()
+    }
+
     pub fn maybe_parse_seq<F, T>(&mut self, opener: Bracket, f: F) -> QlResult<Vec<T>>
     where
         F: Fn(&mut TokenStream) -> QlResult<Vec<T>>,
@@ -92,7 +215,7 @@ impl<'a> TokenStream<'a> {
             if let TokenKind::Tree(br, ref toks) = tok.kind {
                 if br == opener {
                     self.bump();
-                    return f(&mut TokenStream::new(toks));
+                    return f(&mut self.tree(tok, toks));
                 }
             }
         }
@@ -101,19 +224,83 @@ impl<'a> TokenStream<'a> {
 }
 
 pub fn maybe_parse_name(stream: &mut TokenStream) -> QlResult<Option<Name>> {
-    match *none_ok!(stream.peek_tok()) {
-        Token {
-            kind: TokenKind::Atom(Atom::Name(s)),
-        } => {
+    let tok = none_ok!(stream.peek_tok());
+    match tok.kind {
+        TokenKind::Atom(Atom::Name(s)) => {
             stream.bump();
             Ok(Some(Name(s.to_owned())))
         }
-        _ => parse_err!("Unexpected token, expected: name"),
+        _ => parse_err!("Unexpected token", tok.span, "name"),
+    }
+}
+
+// T ::= "String" | "ID" | Name | [T*] | T!
+pub fn parse_type(stream: &mut TokenStream) -> QlResult<Type> {
+    let tok = stream.next_tok()?;
+    let mut result = match tok.kind {
+        TokenKind::Tree(Bracket::Square, ref toks) => Type {
+            span: tok.span,
+            ..Type::array(parse_type(&mut stream.tree(tok, toks))?)
+        },
+        TokenKind::Atom(Atom::Name(s)) => Type {
+            kind: match s {
+                KString::TEXT => TypeKind::String,
+                KId::TEXT => TypeKind::Id,
+                KInt::TEXT => TypeKind::Int,
+                KFloat::TEXT => TypeKind::Float,
+                KBoolean::TEXT => TypeKind::Boolean,
+                _ => TypeKind::Name(Name(s.to_owned())),
+            },
+            nullable: true,
+            span: tok.span,
+        },
+        _ => return parse_err!("Unexpected token", tok.span, "type"),
+    };
+
+    // Looks for `!`s (non-null types).
+    loop {
+        match stream.peek_tok() {
+            None => break,
+            Some(tok) => match tok.kind {
+                TokenKind::Atom(Atom::Bang) => {
+                    stream.bump();
+                    result.nullable = false;
+                    result.span = result.span.to(tok.span);
+                }
+                _ => break,
+            },
+        }
+    }
+
+    Ok(result)
+}
+
+// Converts the result of parsing a whole document into a result which fails
+// if there were any errors.
+pub fn finish<T>(result: T, errors: Vec<Diagnostic>) -> QlResult<T> {
+    if errors.is_empty() {
+        Ok(result)
+    } else {
+        Err(QlError::ParseError(errors))
     }
 }
 
-pub macro parse_err($s: expr) {
-    Err(QlError::ParseError(ParseError($s)))
+pub macro parse_err($s: expr, $span: expr $(, $expected: expr)*) {
+    Err(QlError::ParseError(vec![Diagnostic::new(
+        DiagnosticKind::UnexpectedToken,
+        $s,
+        $span,
+        vec![$($expected),*],
+    )]))
+}
+
+pub macro eof_err($span: expr) {
+    Err(QlError::ParseError(vec![Diagnostic::new(
+        DiagnosticKind::UnexpectedEof,
+        "Unexpected end of input",
+        $span,
+        vec![],
+    )]))
 }
 
 pub macro none_ok($e: expr) {
@@ -123,15 +310,41 @@ pub macro none_ok($e: expr) {
     }
 }
 
+pub trait Keyword {
+    const TEXT: &'static str;
+}
+
+pub struct KId;
+pub struct KString;
+pub struct KInt;
+pub struct KFloat;
+pub struct KBoolean;
+
+impl Keyword for KString {
+    const TEXT: &'static str = "String";
+}
+impl Keyword for KId {
+    const TEXT: &'static str = "ID";
+}
+impl Keyword for KInt {
+    const TEXT: &'static str = "Int";
+}
+impl Keyword for KFloat {
+    const TEXT: &'static str = "Float";
+}
+impl Keyword for KBoolean {
+    const TEXT: &'static str = "Boolean";
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use parser::lexer::tokenise;
+    use parser::lexer::{tokenise, tokenise_with_errors};
 
     #[test]
     fn test_bump() {
         let tokens = tokenise("foo bar").unwrap();
-        let mut parser = TokenStream::new(&tokens);
+        let mut parser = TokenStream::top_level(&tokens);
         assert_eq!(parser.tokens.len(), 2);
         parser.bump();
         assert_eq!(parser.tokens.len(), 1);
@@ -142,7 +355,7 @@ mod test {
     #[test]
     fn test_maybe_eat() {
         let tokens = tokenise("foo bar!").unwrap();
-        let mut parser = TokenStream::new(&tokens);
+        let mut parser = TokenStream::top_level(&tokens);
         assert_eq!(assert_atom(parser.peek_tok().unwrap()), Atom::Name("foo"));
         parser.maybe_eat(Atom::Name("bar"));
         assert_eq!(assert_atom(parser.next_tok().unwrap()), Atom::Name("foo"));
@@ -153,7 +366,7 @@ mod test {
     #[test]
     fn test_eat() {
         let tokens = tokenise("foo bar!").unwrap();
-        let mut parser = TokenStream::new(&tokens);
+        let mut parser = TokenStream::top_level(&tokens);
         assert_eq!(assert_atom(parser.next_tok().unwrap()), Atom::Name("foo"));
         parser.eat(Atom::Name("bar")).unwrap();
         parser.eat(Atom::Bang).unwrap();
@@ -162,17 +375,68 @@ mod test {
     #[test]
     fn test_bad_eat() {
         let tokens = tokenise("foo bar!").unwrap();
-        let mut parser = TokenStream::new(&tokens);
+        let mut parser = TokenStream::top_level(&tokens);
         match parser.eat(Atom::Name("bar")) {
-            Err(QlError::ParseError(ParseError(_))) => {}
+            Err(QlError::ParseError(ref errs)) => {
+                assert_eq!(errs.len(), 1);
+                assert_eq!(errs[0].kind, DiagnosticKind::UnexpectedToken);
+                assert_eq!(errs[0].span.lo, 0);
+                assert_eq!(errs[0].expected, vec!["name"]);
+            }
             result => panic!("Found: {:?}", result),
         }
     }
 
+    #[test]
+    fn test_tree_end() {
+        let tokens = tokenise("{ a }").unwrap();
+        let ts = TokenStream::top_level(&tokens);
+        match tokens[0].kind {
+            TokenKind::Tree(_, ref toks) => {
+                let end = ts.tree(&tokens[0], toks).end;
+                assert_eq!((end.lo, end.hi), (4, 5));
+                assert_eq!(end.start.col.0, 4);
+            }
+            _ => panic!(),
+        }
+
+        // An unclosed tree ending with a newline ends at column 0 of the next
+        // line.
+        for input in &["{\n", "{ a\n", "{ a {\n"] {
+            let (tokens, _) = tokenise_with_errors(input);
+            let ts = TokenStream::top_level(&tokens);
+            match tokens[0].kind {
+                TokenKind::Tree(_, ref toks) => {
+                    let end = ts.tree(&tokens[0], toks).end;
+                    assert_eq!((end.lo, end.hi), (input.len(), input.len()), "{:?}", input);
+                    assert_eq!((end.start.row.0, end.start.col.0), (1, 0), "{:?}", input);
+                }
+                _ => panic!(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_record() {
+        let tokens = tokenise("foo bar").unwrap();
+        let mut parser = TokenStream::top_level(&tokens);
+        parser.bump();
+        parser.record(QlError::TranslationError("bar".to_owned(), "Int".to_owned()));
+        let e = parser.eat(Atom::Bang).unwrap_err();
+        parser.record(e);
+
+        let errs = parser.take_errors();
+        assert_eq!(errs.len(), 2);
+        assert_eq!(errs[0].kind, DiagnosticKind::Other);
+        assert_eq!(errs[0].message, "Translation error: from bar to Int");
+        assert_eq!(errs[0].span.lo, 0);
+        assert_eq!(errs[1].kind, DiagnosticKind::UnexpectedToken);
+    }
+
     #[test]
     fn test_ignore_newlines() {
         let tokens = tokenise("foo \n\n\n\n\n bar").unwrap();
-        let mut parser = TokenStream::new(&tokens);
+        let mut parser = TokenStream::top_level(&tokens);
         assert_eq!(assert_atom(parser.peek_tok().unwrap()), Atom::Name("foo"));
         parser.ignore_newlines();
         assert_eq!(assert_atom(parser.next_tok().unwrap()), Atom::Name("foo"));
@@ -180,6 +444,50 @@ mod test {
         assert_eq!(assert_atom(parser.next_tok().unwrap()), Atom::Name("bar"));
     }
 
+    #[test]
+    fn test_parse_type() {
+        let tokens = tokenise("foo").unwrap();
+        let mut ts = TokenStream::top_level(&tokens);
+        let result = parse_type(&mut ts).unwrap();
+        assert!(result.nullable);
+        match result.kind {
+            TypeKind::Name(n) => assert_eq!(n.0, "foo"),
+            _ => panic!(),
+        }
+
+        let tokens = tokenise("[ID!]!").unwrap();
+        let mut ts = TokenStream::top_level(&tokens);
+        let result = parse_type(&mut ts).unwrap();
+        assert!(!result.nullable);
+        match result.kind {
+            TypeKind::Array(inner) => {
+                assert!(!inner.nullable);
+                match inner.kind {
+                    TypeKind::Id => {}
+                    _ => panic!(),
+                }
+            }
+            _ => panic!(),
+        }
+
+        let tokens = tokenise("Int Float! Boolean").unwrap();
+        let mut ts = TokenStream::top_level(&tokens);
+        match parse_type(&mut ts).unwrap().kind {
+            TypeKind::Int => {}
+            _ => panic!(),
+        }
+        let result = parse_type(&mut ts).unwrap();
+        assert!(!result.nullable);
+        match result.kind {
+            TypeKind::Float => {}
+            _ => panic!(),
+        }
+        match parse_type(&mut ts).unwrap().kind {
+            TypeKind::Boolean => {}
+            _ => panic!(),
+        }
+    }
+
     fn assert_atom<'a>(tok: &Token<'a>) -> Atom<'a> {
         match tok.kind {
             TokenKind::Atom(atom) => atom,
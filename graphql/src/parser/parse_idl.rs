@@ -1,177 +1,522 @@
 // Parses an IDL representation of a schema.
 
 use QlResult;
-use parser::lexer::tokenise;
-use parser::parse_base::{maybe_parse_name, none_ok, parse_err, TokenStream};
+use parser::{Diagnostic, DiagnosticKind};
+use parser::lexer::{block_string_value, string_value, tokenise_with_errors};
+use parser::parse_base::{finish, maybe_parse_name, none_ok, parse_err, parse_type, Keyword, TokenStream};
+use parser::parse_query::{parse_directives, parse_value};
 use parser::token::{Atom, Bracket, Token, TokenKind};
-use schema::{Enum, Field, Interface, Item, Object, Schema, Type, TypeKind};
-use types::Name;
-
-use std::collections::HashMap;
+use schema::{Argument, Directive, DirectiveLocation, Enum, Extension, Field, InputObject, Interface, Item, Object,
+             Scalar, Schema, Union, Variant, SCHEMA_NAME};
+use types::{query, Name, Span};
 
 pub fn parse_schema(input: &str) -> QlResult<Schema> {
-    let tokens = tokenise(input.trim())?;
-    let mut stream = TokenStream::new(&tokens);
-    parse_doc(&mut stream)
+    let (schema, errors) = parse_schema_with_diagnostics(input);
+    finish(schema, errors)
+}
+
+// Parses as much of `input` as possible, returning the schema and any errors.
+pub fn parse_schema_with_diagnostics(input: &str) -> (Schema, Vec<Diagnostic>) {
+    let (tokens, lex_errors) = tokenise_with_errors(input);
+    let mut stream = TokenStream::top_level(&tokens);
+    stream.record_all(lex_errors.into_iter().map(Diagnostic::from));
+    let schema = parse_doc(&mut stream);
+    (schema, stream.take_errors())
 }
 
-fn parse_doc(stream: &mut TokenStream) -> QlResult<Schema> {
+// A top-level definition in the IDL.
+enum Definition {
+    // The span is the span of the item's name.
+    Item(Name, Item, Span),
+    Directive(Directive),
+    Extension(Extension),
+}
+
+fn parse_doc(stream: &mut TokenStream) -> Schema {
     stream.ignore_newlines();
-    let mut items = HashMap::new();
-    while let Some((name, item)) = maybe_parse_item(stream)? {
+    let mut schema = Schema::new();
+    let mut conflicts = vec![];
+    loop {
+        match maybe_parse_definition(stream) {
+            Ok(Some(Definition::Item(name, item, span))) => {
+                if let Err(e) = schema.add_item(name, item, span) {
+                    conflicts.push(e);
+                }
+            }
+            Ok(Some(Definition::Directive(d))) => schema.directives.push(d),
+            Ok(Some(Definition::Extension(e))) => schema.extensions.push(e),
+            Ok(None) => break,
+            Err(e) => {
+                stream.record(e);
+                stream.skip_until(is_item_start);
+            }
+        }
         stream.ignore_newlines();
-        items.insert(name, item);
     }
-    Ok(Schema { items })
-    // TODO check there are no more tokens
+
+    // Done after parsing everything, since a type may be extended before it is
+    // defined. Extensions of types from other documents are left in the schema.
+    conflicts.extend(schema.apply_extensions());
+    stream.record_all(
+        conflicts
+            .into_iter()
+            .map(|e| Diagnostic::new(DiagnosticKind::Conflict, &e.message, e.span, vec![])),
+    );
+    schema
+}
+
+fn is_item_start(tok: &Token) -> bool {
+    match tok.kind {
+        // A description.
+        TokenKind::Atom(Atom::String(_)) | TokenKind::Atom(Atom::BlockString(_)) => true,
+        TokenKind::Atom(Atom::Name(s)) => {
+            [
+                KSchema::TEXT,
+                KType::TEXT,
+                KInterface::TEXT,
+                KEnum::TEXT,
+                KInput::TEXT,
+                KUnion::TEXT,
+                KScalar::TEXT,
+                KDirective::TEXT,
+                KExtend::TEXT,
+            ].contains(&s)
+        }
+        _ => false,
+    }
 }
 
-fn maybe_parse_item(stream: &mut TokenStream) -> QlResult<Option<(Name, Item)>> {
-    let kw = none_ok!(maybe_parse_name(stream)?);
+// (Description)? Keyword Name? ...
+fn maybe_parse_definition(stream: &mut TokenStream) -> QlResult<Option<Definition>> {
+    let description = maybe_parse_description(stream);
+    let kw_span = stream.peek_span();
+    let kw = match description {
+        Some(_) => stream.expect(maybe_parse_name)?,
+        None => none_ok!(maybe_parse_name(stream)?),
+    };
 
     if kw.0 == KSchema::TEXT {
-        let body = parse_interface(stream)?;
+        let directives = parse_directives(stream)?;
+        let body = parse_interface_body(stream, vec![], description, directives)?;
         let item = Item::Schema(body);
-        return Ok(Some((Name("schema".to_owned()), item)));
+        return Ok(Some(Definition::Item(Name(SCHEMA_NAME.to_owned()), item, kw_span)));
+    }
+    if kw.0 == KDirective::TEXT {
+        let directive = parse_directive_definition(stream, kw_span, description)?;
+        return Ok(Some(Definition::Directive(directive)));
+    }
+    if kw.0 == KExtend::TEXT {
+        // Extensions can't have descriptions.
+        if description.is_some() {
+            return parse_err!("Unexpected token", kw_span, "item");
+        }
+        let extension = parse_extension(stream, kw_span)?;
+        return Ok(Some(Definition::Extension(extension)));
     }
 
+    let name_span = stream.peek_span();
     let name = stream.expect(maybe_parse_name)?;
 
     let item = match &*kw.0 {
         KInterface::TEXT => {
-            let body = parse_interface(stream)?;
+            let body = parse_interface(stream, description)?;
             Item::Interface(body)
         }
         KType::TEXT => {
-            let body = parse_object(stream)?;
+            let body = parse_object(stream, description)?;
             Item::Object(body)
         }
         KEnum::TEXT => {
-            let body = parse_enum(stream)?;
+            let body = parse_enum(stream, description)?;
             Item::Enum(body)
         }
-        _ => return parse_err!("Unexpected item"),
+        KInput::TEXT => {
+            let body = parse_input_object(stream, description)?;
+            Item::InputObject(body)
+        }
+        KUnion::TEXT => {
+            let body = parse_union(stream, kw_span, description)?;
+            Item::Union(body)
+        }
+        KScalar::TEXT => {
+            let directives = parse_directives(stream)?;
+            Item::Scalar(Scalar {
+                description,
+                directives,
+            })
+        }
+        _ => return parse_err!("Unexpected item", kw_span, "item"),
+    };
+
+    Ok(Some(Definition::Item(name, item, name_span)))
+}
+
+// (type | interface | enum | schema) Name? ...
+// The `extend` keyword has already been parsed. Unlike definitions, the body
+// may be omitted, e.g., if the extension only adds directives.
+fn parse_extension(stream: &mut TokenStream, lo: Span) -> QlResult<Extension> {
+    let kw_span = stream.peek_span();
+    let kw = stream.expect(maybe_parse_name)?;
+
+    if kw.0 == KSchema::TEXT {
+        let directives = parse_directives(stream)?;
+        let fields = stream.maybe_parse_seq(Bracket::Brace, |s| s.parse_list(maybe_parse_field))?;
+        return Ok(Extension {
+            name: Name(SCHEMA_NAME.to_owned()),
+            item: Item::Schema(Interface {
+                implements: vec![],
+                fields,
+                description: None,
+                directives,
+            }),
+            span: lo.to(stream.prev_span()),
+        });
+    }
+
+    let name = stream.expect(maybe_parse_name)?;
+    let item = match &*kw.0 {
+        KType::TEXT | KInterface::TEXT => {
+            let implements = parse_implements(stream)?;
+            let directives = parse_directives(stream)?;
+            let fields = stream.maybe_parse_seq(Bracket::Brace, |s| s.parse_list(maybe_parse_field))?;
+            if kw.0 == KType::TEXT {
+                Item::Object(Object {
+                    implements,
+                    fields,
+                    description: None,
+                    directives,
+                })
+            } else {
+                Item::Interface(Interface {
+                    implements,
+                    fields,
+                    description: None,
+                    directives,
+                })
+            }
+        }
+        KEnum::TEXT => {
+            let directives = parse_directives(stream)?;
+            let variants = stream.maybe_parse_seq(Bracket::Brace, |s| s.parse_list(maybe_parse_variant))?;
+            Item::Enum(Enum {
+                variants,
+                description: None,
+                directives,
+            })
+        }
+        _ => return parse_err!("Unexpected token", kw_span, "`type`", "`interface`", "`enum`", "`schema`"),
+    };
+
+    Ok(Extension {
+        name,
+        item,
+        span: lo.to(stream.prev_span()),
+    })
+}
+
+// @Name (args)? on `|`? Location (`|` Location)*
+// The `directive` keyword has already been parsed.
+fn parse_directive_definition(
+    stream: &mut TokenStream,
+    lo: Span,
+    description: Option<String>,
+) -> QlResult<Directive> {
+    stream.eat(Atom::At)?;
+    let name = stream.expect(maybe_parse_name)?;
+    let args = stream.maybe_parse_seq(Bracket::Paren, |s| s.parse_list(maybe_parse_arg))?;
+
+    let tok = stream.next_tok()?;
+    match tok.kind {
+        TokenKind::Atom(Atom::Name(KOn::TEXT)) => {}
+        _ => return parse_err!("Unexpected token", tok.span, "`on`"),
+    }
+    stream.ignore_newlines();
+    if let Some(&Token { kind: TokenKind::Atom(Atom::Pipe), .. }) = stream.peek_tok() {
+        stream.bump();
+    }
+
+    let mut locations = vec![parse_directive_location(stream)?];
+    loop {
+        stream.ignore_newlines();
+        match stream.peek_tok() {
+            Some(&Token { kind: TokenKind::Atom(Atom::Pipe), .. }) => stream.bump(),
+            _ => break,
+        }
+        locations.push(parse_directive_location(stream)?);
+    }
+
+    Ok(Directive {
+        name,
+        args,
+        locations,
+        description,
+        span: lo.to(stream.prev_span()),
+    })
+}
+
+fn parse_directive_location(stream: &mut TokenStream) -> QlResult<DirectiveLocation> {
+    let lo = stream.peek_span();
+    let name = stream.expect(maybe_parse_name)?;
+    match DirectiveLocation::from_name(&name.0) {
+        Some(l) => Ok(l),
+        None => parse_err!("Unknown directive location", lo, "directive location"),
+    }
+}
+
+// A string or block string, followed by any number of newlines.
+fn maybe_parse_description(stream: &mut TokenStream) -> Option<String> {
+    let description = match stream.peek_tok() {
+        // Invalid escapes have already been reported by the lexer.
+        Some(&Token { kind: TokenKind::Atom(Atom::String(s)), .. }) => {
+            string_value(s).unwrap_or_else(|| s.to_owned())
+        }
+        Some(&Token { kind: TokenKind::Atom(Atom::BlockString(s)), .. }) => block_string_value(s),
+        _ => return None,
+    };
+    stream.bump();
+    stream.ignore_newlines();
+    Some(description)
+}
+
+// (Description)? Name
+// If there is a description, then there must be a name.
+fn maybe_parse_described_name(stream: &mut TokenStream) -> QlResult<Option<(Option<String>, Name)>> {
+    let description = maybe_parse_description(stream);
+    let name = match description {
+        Some(_) => stream.expect(maybe_parse_name)?,
+        None => none_ok!(maybe_parse_name(stream)?),
     };
+    Ok(Some((description, name)))
+}
 
-    Ok(Some((name, item)))
+fn parse_interface(stream: &mut TokenStream, description: Option<String>) -> QlResult<Interface> {
+    let implements = parse_implements(stream)?;
+    let directives = parse_directives(stream)?;
+    parse_interface_body(stream, implements, description, directives)
 }
 
-fn parse_interface(stream: &mut TokenStream) -> QlResult<Interface> {
-    let fields = match stream.next_tok()?.kind {
+fn parse_interface_body(
+    stream: &mut TokenStream,
+    implements: Vec<Name>,
+    description: Option<String>,
+    directives: Vec<query::Directive>,
+) -> QlResult<Interface> {
+    let tok = stream.next_tok()?;
+    let fields = match tok.kind {
         TokenKind::Tree(Bracket::Brace, ref toks) => {
-            TokenStream::new(toks).parse_list(maybe_parse_field)?
+            stream.tree(tok, toks).parse_list(maybe_parse_field)?
         }
-        _ => return parse_err!("Expected `{`"),
+        _ => return parse_err!("Unexpected token", tok.span, "`{`"),
     };
-    Ok(Interface { fields })
+    Ok(Interface {
+        implements,
+        fields,
+        description,
+        directives,
+    })
 }
 
-fn parse_object(stream: &mut TokenStream) -> QlResult<Object> {
-    let implements = match stream.peek_tok() {
-        Some(tok) => match tok.kind {
-            TokenKind::Atom(Atom::Name(KImplements::TEXT)) => {
+// (implements `&`? Name (`&` Name)*)?
+// Names may also be separated by commas or whitespace, as in older versions of
+// the spec. A whitespace separated list ends at the keyword of the next
+// definition, since an extension may have no body.
+fn parse_implements(stream: &mut TokenStream) -> QlResult<Vec<Name>> {
+    match stream.peek_tok() {
+        Some(&Token { kind: TokenKind::Atom(Atom::Name(KImplements::TEXT)), .. }) => stream.bump(),
+        _ => return Ok(vec![]),
+    }
+    if let Some(&Token { kind: TokenKind::Atom(Atom::Amp), .. }) = stream.peek_tok() {
+        stream.bump();
+    }
+
+    let mut result = vec![stream.expect(maybe_parse_name)?];
+    loop {
+        match stream.peek_tok() {
+            Some(&Token { kind: TokenKind::Atom(Atom::Amp), .. })
+            | Some(&Token { kind: TokenKind::Atom(Atom::Comma), .. }) => {
                 stream.bump();
-                let name = stream.expect(maybe_parse_name)?;
-                // TODO handle a list of names, not just one.
-                vec![name]
+                result.push(stream.expect(maybe_parse_name)?);
             }
-            _ => vec![],
-        },
-        None => vec![],
+            Some(&Token { kind: TokenKind::Atom(Atom::Name(n)), .. }) if !DEFINITION_KEYWORDS.contains(&n) => {
+                result.push(stream.expect(maybe_parse_name)?);
+            }
+            _ => return Ok(result),
+        }
+    }
+}
+
+fn parse_object(stream: &mut TokenStream, description: Option<String>) -> QlResult<Object> {
+    let implements = parse_implements(stream)?;
+    let directives = parse_directives(stream)?;
+    let tok = stream.next_tok()?;
+    let fields = match tok.kind {
+        TokenKind::Tree(Bracket::Brace, ref toks) => {
+            stream.tree(tok, toks).parse_list(maybe_parse_field)?
+        }
+        _ => return parse_err!("Unexpected token", tok.span, "`{`"),
     };
-    let fields = match stream.next_tok()?.kind {
+    Ok(Object {
+        implements,
+        fields,
+        description,
+        directives,
+    })
+}
+
+fn parse_enum(stream: &mut TokenStream, description: Option<String>) -> QlResult<Enum> {
+    let directives = parse_directives(stream)?;
+    let tok = stream.next_tok()?;
+    let variants = match tok.kind {
         TokenKind::Tree(Bracket::Brace, ref toks) => {
-            TokenStream::new(toks).parse_list(maybe_parse_field)?
+            stream.tree(tok, toks).parse_list(maybe_parse_variant)?
         }
-        _ => return parse_err!("Expected `{`"),
+        _ => return parse_err!("Unexpected token", tok.span, "`{`"),
     };
-    Ok(Object { implements, fields })
+    Ok(Enum {
+        variants,
+        description,
+        directives,
+    })
 }
 
-fn parse_enum(stream: &mut TokenStream) -> QlResult<Enum> {
-    let variants = match stream.next_tok()?.kind {
+fn parse_input_object(stream: &mut TokenStream, description: Option<String>) -> QlResult<InputObject> {
+    let directives = parse_directives(stream)?;
+    let tok = stream.next_tok()?;
+    let fields = match tok.kind {
         TokenKind::Tree(Bracket::Brace, ref toks) => {
-            TokenStream::new(toks).parse_list(maybe_parse_variant)?
+            stream.tree(tok, toks).parse_list(maybe_parse_input_field)?
         }
-        _ => return parse_err!("Expected `{`"),
+        _ => return parse_err!("Unexpected token", tok.span, "`{`"),
     };
-    Ok(Enum { variants })
+    Ok(InputObject {
+        fields,
+        description,
+        directives,
+    })
 }
 
-fn maybe_parse_field(stream: &mut TokenStream) -> QlResult<Option<Field>> {
-    let name = none_ok!(maybe_parse_name(stream)?);
-    let args = if let Some(&Token {
-        kind: TokenKind::Tree(Bracket::Paren, ref toks),
-    }) = stream.peek_tok()
-    {
+// = `|`? Name (`|` Name)*
+// Members may be written on separate lines.
+fn parse_union(stream: &mut TokenStream, lo: Span, description: Option<String>) -> QlResult<Union> {
+    let directives = parse_directives(stream)?;
+    stream.eat(Atom::Equals)?;
+    stream.ignore_newlines();
+    if let Some(&Token { kind: TokenKind::Atom(Atom::Pipe), .. }) = stream.peek_tok() {
         stream.bump();
-        TokenStream::new(toks).parse_list(maybe_parse_arg)?
-    } else {
-        vec![]
-    };
+    }
+
+    let mut members = vec![stream.expect(maybe_parse_name)?];
+    let mut hi = stream.prev_span();
+    loop {
+        stream.ignore_newlines();
+        match stream.peek_tok() {
+            Some(&Token { kind: TokenKind::Atom(Atom::Pipe), .. }) => stream.bump(),
+            _ => break,
+        }
+        members.push(stream.expect(maybe_parse_name)?);
+        hi = stream.prev_span();
+    }
+    Ok(Union {
+        members,
+        span: lo.to(hi),
+        description,
+        directives,
+    })
+}
+
+// (Description)? Name (args)? : Type Directives
+fn maybe_parse_field(stream: &mut TokenStream) -> QlResult<Option<Field>> {
+    let lo = stream.peek_span();
+    let (description, name) = none_ok!(maybe_parse_described_name(stream)?);
+    let args = stream.maybe_parse_seq(Bracket::Paren, |s| s.parse_list(maybe_parse_arg))?;
+    stream.eat(Atom::Colon)?;
+    let ty = parse_type(stream)?;
+    let span = lo.to(ty.span);
+    let directives = parse_directives(stream)?;
+    Ok(Some(Field {
+        name,
+        args,
+        ty,
+        span,
+        description,
+        directives,
+    }))
+}
+
+// (Description)? Name : Type Directives
+fn maybe_parse_input_field(stream: &mut TokenStream) -> QlResult<Option<Field>> {
+    let lo = stream.peek_span();
+    let (description, name) = none_ok!(maybe_parse_described_name(stream)?);
     stream.eat(Atom::Colon)?;
     let ty = parse_type(stream)?;
-    Ok(Some(Field { name, args, ty }))
+    let span = lo.to(ty.span);
+    let directives = parse_directives(stream)?;
+    Ok(Some(Field {
+        name,
+        args: vec![],
+        ty,
+        span,
+        description,
+        directives,
+    }))
 }
 
-fn maybe_parse_variant(stream: &mut TokenStream) -> QlResult<Option<Name>> {
-    let name = none_ok!(maybe_parse_name(stream)?);
-    Ok(Some(name))
+// (Description)? Name Directives
+fn maybe_parse_variant(stream: &mut TokenStream) -> QlResult<Option<Variant>> {
+    let (description, name) = none_ok!(maybe_parse_described_name(stream)?);
+    let directives = parse_directives(stream)?;
+    Ok(Some(Variant {
+        name,
+        description,
+        directives,
+    }))
 }
 
-// Name : Type
-fn maybe_parse_arg(stream: &mut TokenStream) -> QlResult<Option<(Name, Type)>> {
-    let name = none_ok!(maybe_parse_name(stream)?);
+// (Description)? Name : Type (= Value)? Directives
+fn maybe_parse_arg(stream: &mut TokenStream) -> QlResult<Option<Argument>> {
+    let (description, name) = none_ok!(maybe_parse_described_name(stream)?);
     stream.eat(Atom::Colon)?;
     let ty = parse_type(stream)?;
-    Ok(Some((name, ty)))
-}
-
-// T ::= "String" | "ID" | Name | [T*] | T!
-fn parse_type(stream: &mut TokenStream) -> QlResult<Type> {
-    let mut result = match stream.next_tok()?.kind {
-        TokenKind::Tree(Bracket::Square, ref toks) => {
-            Type::array(parse_type(&mut TokenStream::new(toks))?)
-        }
-        TokenKind::Atom(Atom::Name(s)) => Type {
-            kind: match s {
-                KString::TEXT => TypeKind::String,
-                KId::TEXT => TypeKind::Id,
-                _ => TypeKind::Name(Name(s.to_owned())),
-            },
-            nullable: true,
-        },
-        _ => return parse_err!("Unexpected token, expected type"),
-    };
-
-    // Looks for `!`s (non-null types).
-    loop {
-        match stream.peek_tok() {
-            None => break,
-            Some(tok) => match tok.kind {
-                TokenKind::Atom(Atom::Bang) => {
-                    stream.bump();
-                    result.nullable = false;
-                }
-                _ => break,
-            },
+    let default = match stream.peek_tok() {
+        Some(&Token { kind: TokenKind::Atom(Atom::Equals), .. }) => {
+            stream.bump();
+            Some(parse_value(stream)?)
         }
-    }
-
-    Ok(result)
+        _ => None,
+    };
+    let directives = parse_directives(stream)?;
+    Ok(Some(Argument {
+        name,
+        ty,
+        default,
+        description,
+        directives,
+    }))
 }
 
-trait Keyword {
-    const TEXT: &'static str;
-}
+const DEFINITION_KEYWORDS: [&str; 9] = [
+    KSchema::TEXT,
+    KType::TEXT,
+    KEnum::TEXT,
+    KInterface::TEXT,
+    KInput::TEXT,
+    KUnion::TEXT,
+    KScalar::TEXT,
+    KDirective::TEXT,
+    KExtend::TEXT,
+];
 
 struct KSchema;
 struct KType;
 struct KEnum;
 struct KInterface;
+struct KInput;
 struct KImplements;
-struct KId;
-struct KString;
+struct KUnion;
+struct KScalar;
+struct KDirective;
+struct KOn;
+struct KExtend;
 
 impl Keyword for KSchema {
     const TEXT: &'static str = "schema";
@@ -185,46 +530,33 @@ impl Keyword for KEnum {
 impl Keyword for KInterface {
     const TEXT: &'static str = "interface";
 }
+impl Keyword for KInput {
+    const TEXT: &'static str = "input";
+}
 impl Keyword for KImplements {
     const TEXT: &'static str = "implements";
 }
-impl Keyword for KString {
-    const TEXT: &'static str = "String";
+impl Keyword for KUnion {
+    const TEXT: &'static str = "union";
+}
+impl Keyword for KScalar {
+    const TEXT: &'static str = "scalar";
+}
+impl Keyword for KDirective {
+    const TEXT: &'static str = "directive";
 }
-impl Keyword for KId {
-    const TEXT: &'static str = "ID";
+impl Keyword for KOn {
+    const TEXT: &'static str = "on";
+}
+impl Keyword for KExtend {
+    const TEXT: &'static str = "extend";
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-
-    #[test]
-    fn test_parse_type() {
-        let tokens = tokenise("foo").unwrap();
-        let mut ts = TokenStream::new(&tokens);
-        let result = parse_type(&mut ts).unwrap();
-        assert_eq!(result.nullable, true);
-        match result.kind {
-            TypeKind::Name(n) => assert_eq!(n.0, "foo"),
-            _ => panic!(),
-        }
-
-        let tokens = tokenise("[ID!]!").unwrap();
-        let mut ts = TokenStream::new(&tokens);
-        let result = parse_type(&mut ts).unwrap();
-        assert_eq!(result.nullable, false);
-        match result.kind {
-            TypeKind::Array(inner) => {
-                assert_eq!(inner.nullable, false);
-                match inner.kind {
-                    TypeKind::Id => {}
-                    _ => panic!(),
-                }
-            }
-            _ => panic!(),
-        }
-    }
+    use parser::lexer::tokenise;
+    use schema::TypeKind;
 
     #[test]
     fn test_parse_doc() {
@@ -260,11 +592,17 @@ mod test {
                 appearsIn: [Episode]!
                 homePlanet: String
             }
+
+            input ReviewInput {
+                stars: Int!
+                commentary: String
+            }
         ",
         ).unwrap();
-        let mut ts = TokenStream::new(&tokens);
-        let result = parse_doc(&mut ts).unwrap();
-        assert_eq!(result.items.len(), 5);
+        let mut ts = TokenStream::top_level(&tokens);
+        let result = parse_doc(&mut ts);
+        assert!(ts.take_errors().is_empty());
+        assert_eq!(result.items.len(), 6);
         let schema = &result.items[&Name("schema".to_owned())];
         match *schema {
             Item::Schema(ref i) => {
@@ -276,9 +614,9 @@ mod test {
         match *epsiode {
             Item::Enum(ref e) => {
                 assert_eq!(e.variants.len(), 3);
-                assert_eq!(e.variants[0].0, "NEWHOPE");
-                assert_eq!(e.variants[1].0, "EMPIRE");
-                assert_eq!(e.variants[2].0, "JEDI");
+                assert_eq!(e.variants[0].name.0, "NEWHOPE");
+                assert_eq!(e.variants[1].name.0, "EMPIRE");
+                assert_eq!(e.variants[2].name.0, "JEDI");
             }
             _ => panic!(),
         }
@@ -301,5 +639,285 @@ mod test {
             }
             _ => panic!(),
         }
+        let review = &result.items[&Name("ReviewInput".to_owned())];
+        match *review {
+            Item::InputObject(ref i) => {
+                assert_eq!(i.fields.len(), 2);
+                assert_eq!(i.fields[0].name.0, "stars");
+                assert!(!i.fields[0].ty.nullable);
+                assert_eq!(i.fields[1].name.0, "commentary");
+            }
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_parse_implements() {
+        let schema = parse_schema(
+            "interface Node { id: ID! }
+            interface Character implements Node { id: ID!, name: String }
+            type Human implements Node & Character { id: ID!, name: String }
+            type Droid implements & Character & Node { id: ID!, name: String }
+            type Ship implements Node, Character { id: ID!, name: String }
+            type Planet { id: ID! }",
+        ).unwrap();
+
+        let implements = |n: &str| -> Vec<&str> {
+            schema.items[&Name(n.to_owned())].implements().iter().map(|n| &*n.0).collect()
+        };
+        assert_eq!(implements("Node"), Vec::<&str>::new());
+        assert_eq!(implements("Character"), vec!["Node"]);
+        assert_eq!(implements("Human"), vec!["Node", "Character"]);
+        assert_eq!(implements("Droid"), vec!["Character", "Node"]);
+        assert_eq!(implements("Ship"), vec!["Node", "Character"]);
+        assert_eq!(implements("Planet"), Vec::<&str>::new());
+
+        let name = |n: &str| Name(n.to_owned());
+        assert!(schema.implements(&name("Human"), &name("Node")));
+        assert!(schema.implements(&name("Character"), &name("Node")));
+        assert!(!schema.implements(&name("Node"), &name("Character")));
+        assert!(!schema.implements(&name("Planet"), &name("Node")));
+
+        // On a single line, as `schema!` gives it to us.
+        let single_line = parse_schema(
+            "interface Node { id: ID! } interface Named { name: String } \
+             type Human implements Node Named { id: ID!, name: String } \
+             type Droid implements Node { id: ID!, name: String } \
+             extend type Droid implements Named \
+             type Ship implements Node { id: ID! }",
+        ).unwrap();
+        let implements = |n: &str| -> Vec<&str> {
+            single_line.items[&Name(n.to_owned())].implements().iter().map(|n| &*n.0).collect()
+        };
+        assert_eq!(implements("Human"), vec!["Node", "Named"]);
+        assert_eq!(implements("Droid"), vec!["Node", "Named"]);
+        assert_eq!(implements("Ship"), vec!["Node"]);
+
+        assert!(parse_schema("type Human implements { id: ID! }").is_err());
+        assert!(parse_schema("type Human implements Node & { id: ID! }").is_err());
+    }
+
+    #[test]
+    fn test_parse_union() {
+        let schema = parse_schema(
+            "union SearchResult = Human | Droid
+            union Single = | Human
+            union Multi =
+                | Human
+                | Droid
+            type Human { name: String }",
+        ).unwrap();
+        match schema.items[&Name("SearchResult".to_owned())] {
+            Item::Union(ref u) => {
+                let members: Vec<&str> = u.members.iter().map(|n| &*n.0).collect();
+                assert_eq!(members, vec!["Human", "Droid"]);
+                assert_eq!((u.span.lo, u.span.hi), (0, 34));
+            }
+            _ => panic!(),
+        }
+        match schema.items[&Name("Single".to_owned())] {
+            Item::Union(ref u) => assert_eq!(u.members.len(), 1),
+            _ => panic!(),
+        }
+        match schema.items[&Name("Multi".to_owned())] {
+            Item::Union(ref u) => assert_eq!(u.members.len(), 2),
+            _ => panic!(),
+        }
+
+        assert!(parse_schema("union U Human | Droid").is_err());
+        assert!(parse_schema("union U = Human |").is_err());
+    }
+
+    #[test]
+    fn test_parse_scalar() {
+        let schema = parse_schema(
+            "scalar DateTime
+            type Event { at: DateTime! }",
+        ).unwrap();
+        assert_eq!(schema.items.len(), 2);
+        match schema.items[&Name("DateTime".to_owned())] {
+            Item::Scalar(_) => {}
+            _ => panic!(),
+        }
+        let event = &schema.items[&Name("Event".to_owned())];
+        assert_eq!(event.fields()[0].ty.assert_name().0, "DateTime");
+
+        assert!(parse_schema("scalar").is_err());
+    }
+
+    #[test]
+    fn test_parse_default_args() {
+        use query::ValueKind;
+
+        let schema = parse_schema(
+            "type Query {
+                hero(episode: Episode = JEDI, limit: Int = 10): Character,
+                droid(id: ID!): Droid,
+            }",
+        ).unwrap();
+        let fields = schema.items[&Name("Query".to_owned())].fields();
+        let args = &fields[0].args;
+        assert_eq!(args.len(), 2);
+        assert_eq!(args[0].name.0, "episode");
+        assert_eq!(args[0].default.as_ref().unwrap().kind, ValueKind::Enum(Name("JEDI".to_owned())));
+        match args[1].ty.kind {
+            TypeKind::Int => {}
+            _ => panic!(),
+        }
+        assert_eq!(args[1].default.as_ref().unwrap().kind, ValueKind::Int(10));
+        assert!(fields[1].args[0].default.is_none());
+
+        assert!(parse_schema("type Query { hero(episode: Episode =): Character }").is_err());
+    }
+
+    #[test]
+    fn test_parse_descriptions() {
+        let schema = parse_schema(
+            r#""""
+            The root of all queries.
+            """
+            type Query {
+                "Finds a hero."
+                hero(
+                    "Defaults to the most recent episode."
+                    episode: Episode,
+                ): Character,
+                droid(id: ID!): Droid,
+            }
+            "A film." enum Episode { "A New Hope" NEWHOPE, EMPIRE }
+            "Dates are written as `YYYY-MM-DD`."
+            scalar Date"#,
+        ).unwrap();
+        assert_eq!(schema.items.len(), 3);
+
+        let query = &schema.items[&Name("Query".to_owned())];
+        assert_eq!(query.description(), Some("The root of all queries."));
+        let fields = query.fields();
+        assert_eq!(fields[0].description.as_ref().unwrap(), "Finds a hero.");
+        assert_eq!(fields[0].args[0].description.as_ref().unwrap(), "Defaults to the most recent episode.");
+        assert!(fields[1].description.is_none());
+
+        match schema.items[&Name("Episode".to_owned())] {
+            Item::Enum(ref e) => {
+                assert_eq!(e.description.as_ref().unwrap(), "A film.");
+                assert_eq!(e.variants[0].name.0, "NEWHOPE");
+                assert_eq!(e.variants[0].description.as_ref().unwrap(), "A New Hope");
+                assert!(e.variants[1].description.is_none());
+            }
+            _ => panic!(),
+        }
+        let date = &schema.items[&Name("Date".to_owned())];
+        assert_eq!(date.description(), Some("Dates are written as `YYYY-MM-DD`."));
+
+        // A description must be followed by the thing it describes.
+        assert!(parse_schema("type Query { \"dangling\" }").is_err());
+        assert!(parse_schema("\"dangling\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_directives() {
+        use schema::DirectiveLocation;
+
+        let schema = parse_schema(
+            r#""Caches the result of a field."
+            directive @cached(seconds: Int = 60) on
+                | FIELD_DEFINITION
+                | OBJECT
+            directive @tag on FIELD
+            type Query @cached {
+                hero(episode: Episode @tag): Character @cached(seconds: 10),
+                oldHero: Character @deprecated(reason: "Use `hero`."),
+            }
+            enum Episode { NEWHOPE, EMPIRE @deprecated, JEDI }"#,
+        ).unwrap();
+        assert_eq!(schema.items.len(), 2);
+        assert_eq!(schema.directives.len(), 2);
+        let cached = &schema.directives[0];
+        assert_eq!(cached.name.0, "cached");
+        assert_eq!(cached.description.as_ref().unwrap(), "Caches the result of a field.");
+        assert_eq!(cached.args[0].name.0, "seconds");
+        assert_eq!(cached.locations, vec![DirectiveLocation::FieldDefinition, DirectiveLocation::Object]);
+        assert_eq!(schema.directives[1].locations, vec![DirectiveLocation::Field]);
+
+        let query = &schema.items[&Name("Query".to_owned())];
+        assert_eq!(query.directives()[0].name.0, "cached");
+        let fields = query.fields();
+        assert_eq!(fields[0].directives[0].name.0, "cached");
+        assert_eq!(fields[0].args[0].directives[0].name.0, "tag");
+        assert!(fields[0].deprecation_reason().is_none());
+        assert_eq!(fields[1].deprecation_reason(), Some("Use `hero`."));
+
+        match schema.items[&Name("Episode".to_owned())] {
+            Item::Enum(ref e) => {
+                assert!(e.variants[0].deprecation_reason().is_none());
+                assert_eq!(e.variants[1].deprecation_reason(), Some("No longer supported"));
+            }
+            _ => panic!(),
+        }
+
+        assert!(parse_schema("directive @foo on WHEREVER").is_err());
+        assert!(parse_schema("directive foo on FIELD").is_err());
+        assert!(parse_schema("directive @foo FIELD").is_err());
+    }
+
+    #[test]
+    fn test_parse_extensions() {
+        let schema = parse_schema(
+            "extend type Query implements Node @cached {
+                droid(id: ID!): Droid
+            }
+            type Query { hero: Character }
+            extend enum Episode { JEDI }
+            extend interface Character @cached
+            extend schema { mutation: Mutation }
+            enum Episode { NEWHOPE, EMPIRE }",
+        ).unwrap();
+
+        let query = &schema.items[&Name("Query".to_owned())];
+        let names: Vec<_> = query.fields().iter().map(|f| &*f.name.0).collect();
+        assert_eq!(names, vec!["hero", "droid"]);
+        assert_eq!(query.implements(), &[Name("Node".to_owned())]);
+        assert_eq!(query.directives()[0].name.0, "cached");
+        match schema.items[&Name("Episode".to_owned())] {
+            Item::Enum(ref e) => assert_eq!(e.variants.len(), 3),
+            _ => panic!(),
+        }
+        // `Character` and `schema` are not defined in this document.
+        assert_eq!(schema.extensions.len(), 2);
+        assert_eq!(schema.extensions[0].name.0, "Character");
+        assert_eq!(schema.extensions[1].name.0, "schema");
+
+        let (_, errs) = parse_schema_with_diagnostics(
+            "type Query { hero: Character }
+            type Query { droid: Droid }
+            extend type Query { hero: Character, droid: Human }
+            extend enum Query { JEDI }",
+        );
+        let mut msgs: Vec<_> = errs.iter().map(|e| (e.kind, &*e.message)).collect();
+        msgs.sort_by_key(|m| m.1);
+        assert_eq!(
+            msgs,
+            vec![
+                (DiagnosticKind::Conflict, "Cannot extend type \"Query\" with an extension of a different kind."),
+                (DiagnosticKind::Conflict, "Field \"Query.hero\" already exists."),
+                (DiagnosticKind::Conflict, "There can be only one type named \"Query\"."),
+            ]
+        );
+
+        assert!(parse_schema("extend union U = A").is_err());
+        assert!(parse_schema("\"desc\" extend type Query { a: Int }").is_err());
+    }
+
+    #[test]
+    fn test_parse_unclosed() {
+        for input in &["type Query {\n", "type Query {\n  a: Int\n", "type Query { a(x: [Int\n"] {
+            let (_, errs) = parse_schema_with_diagnostics(input);
+            assert!(!errs.is_empty(), "{:?}", input);
+            assert_eq!(errs[0].kind, DiagnosticKind::UnclosedDelimiter, "{:?}", input);
+        }
+
+        let (schema, errs) = parse_schema_with_diagnostics("type Query {\n  a: Int\n  b: String\n");
+        assert_eq!(errs.len(), 1);
+        assert_eq!(schema.items[&Name("Query".to_owned())].fields().len(), 2);
     }
 }
@@ -1,53 +1,188 @@
 use QlResult;
-use parser::lexer::tokenise;
-use parser::parse_base::{maybe_parse_name, none_ok, parse_err, TokenStream};
-use parser::token::{Atom, Bracket, TokenKind};
-use query::{Field, Operation, Value};
-use types::Name;
-
-pub fn parse_query(input: &str) -> QlResult<Operation> {
-    let tokens = tokenise(input.trim())?;
-    let mut stream = TokenStream::new(&tokens);
-    parse_operation(&mut stream)
+use parser::{Diagnostic, DiagnosticKind};
+use parser::lexer::{block_string_value, string_value, tokenise_with_errors};
+use parser::parse_base::{finish, maybe_parse_name, none_ok, parse_err, parse_type, Keyword, TokenStream};
+use parser::token::{Atom, Bracket, Token, TokenKind};
+use query::{Directive, Document, Field, Fragment, FragmentSpread, InlineFragment, Operation,
+            OperationKind, Selection, Value, ValueKind, VariableDefinition};
+use types::{Name, Span};
+
+pub fn parse_query(input: &str) -> QlResult<Document> {
+    let (doc, errors) = parse_query_with_diagnostics(input);
+    finish(doc, errors)
 }
 
-fn parse_operation(stream: &mut TokenStream) -> QlResult<Operation> {
-    match stream.next_tok()?.kind {
-        // TODO abstract out keywords
-        TokenKind::Atom(Atom::Name(n)) if n == "query" => {
-            let body = match stream.next_tok()?.kind {
-                TokenKind::Tree(Bracket::Brace, ref toks) => {
-                    parse_field_list(&mut TokenStream::new(toks))?
-                }
-                _ => return parse_err!("Unexpected token, expected: `{`"),
-            };
-            Ok(Operation::Query(Field {
-                name: Name("query".to_owned()),
-                alias: None,
-                args: vec![],
-                fields: body,
-            }))
+// Parses as much of `input` as possible, returning the document and any errors.
+pub fn parse_query_with_diagnostics(input: &str) -> (Document, Vec<Diagnostic>) {
+    let (tokens, lex_errors) = tokenise_with_errors(input);
+    let mut stream = TokenStream::top_level(&tokens);
+    stream.record_all(lex_errors.into_iter().map(Diagnostic::from));
+    let doc = parse_document(&mut stream);
+    (doc, stream.take_errors())
+}
+
+enum Definition {
+    Operation(Operation),
+    Fragment(Fragment),
+}
+
+fn parse_document(stream: &mut TokenStream) -> Document {
+    let mut operations = vec![];
+    let mut fragments = vec![];
+    let mut recovered = false;
+    loop {
+        stream.ignore_newlines();
+        match maybe_parse_definition(stream) {
+            Ok(Some(Definition::Operation(op))) => operations.push(op),
+            Ok(Some(Definition::Fragment(f))) => fragments.push(f),
+            Ok(None) => break,
+            Err(e) => {
+                stream.record(e);
+                stream.skip_until(is_operation_start);
+                recovered = true;
+            }
+        }
+    }
+
+    // A document must have at least one operation.
+    if operations.is_empty() && !recovered {
+        let span = stream.peek_span();
+        stream.record_all(Some(Diagnostic::new(
+            DiagnosticKind::UnexpectedEof,
+            "Unexpected end of input",
+            span,
+            vec!["operation"],
+        )));
+    }
+
+    Document { operations, fragments }
+}
+
+fn is_operation_start(tok: &Token) -> bool {
+    match tok.kind {
+        TokenKind::Atom(Atom::Name(s)) => {
+            [KQuery::TEXT, KMutation::TEXT, KSubscription::TEXT, KFragment::TEXT].contains(&s)
+        }
+        TokenKind::Tree(Bracket::Brace, _) => true,
+        _ => false,
+    }
+}
+
+fn maybe_parse_definition(stream: &mut TokenStream) -> QlResult<Option<Definition>> {
+    match none_ok!(stream.peek_tok()).kind {
+        TokenKind::Atom(Atom::Name(KFragment::TEXT)) => {
+            Ok(Some(Definition::Fragment(parse_fragment(stream)?)))
         }
-        TokenKind::Atom(Atom::Name(n)) if n == "mutation" => {
-            // TODO parse the body of the mutation
-            Ok(Operation::Mutation)
+        _ => Ok(maybe_parse_operation(stream)?.map(Definition::Operation)),
+    }
+}
+
+// fragment Name on Type { selections }
+fn parse_fragment(stream: &mut TokenStream) -> QlResult<Fragment> {
+    let lo = stream.peek_span();
+    stream.eat(Atom::Name(KFragment::TEXT))?;
+    let name_span = stream.peek_span();
+    let name = parse_name(stream)?;
+    if name.0 == KOn::TEXT {
+        return parse_err!("Unexpected token", name_span, "name");
+    }
+    stream.eat(Atom::Name(KOn::TEXT))?;
+    let type_condition = parse_name(stream)?;
+    let selections = parse_selection_set(stream)?;
+    Ok(Fragment {
+        name,
+        type_condition,
+        selections,
+        span: lo.to(stream.prev_span()),
+    })
+}
+
+// (query | mutation | subscription) Name? (variables)? { selections }
+// | { selections }
+fn maybe_parse_operation(stream: &mut TokenStream) -> QlResult<Option<Operation>> {
+    let tok = none_ok!(stream.peek_tok());
+    stream.bump();
+    let (kind, name, variables, directives) = match tok.kind {
+        TokenKind::Atom(Atom::Name(n)) if [KQuery::TEXT, KMutation::TEXT, KSubscription::TEXT].contains(&n) => {
+            let kind = match n {
+                KQuery::TEXT => OperationKind::Query,
+                KMutation::TEXT => OperationKind::Mutation,
+                _ => OperationKind::Subscription,
+            };
+            let name = match stream.peek_tok() {
+                Some(&Token { kind: TokenKind::Atom(Atom::Name(_)), .. }) => maybe_parse_name(stream)?,
+                _ => None,
+            };
+            let variables = stream.maybe_parse_seq(Bracket::Paren, |s| s.parse_list(maybe_parse_variable_def))?;
+            let directives = parse_directives(stream)?;
+            (kind, name, variables, directives)
         }
         TokenKind::Tree(Bracket::Brace, ref toks) => {
-            let body = parse_field_list(&mut TokenStream::new(toks))?;
-            Ok(Operation::Query(Field {
-                name: Name("query".to_owned()),
-                alias: None,
-                args: vec![],
-                fields: body,
-            }))
+            let body = parse_selection_list(&mut stream.tree(tok, toks))?;
+            return Ok(Some(Operation {
+                kind: OperationKind::Query,
+                name: None,
+                variables: vec![],
+                directives: vec![],
+                field: root_field(OperationKind::Query, body, tok.span),
+            }));
         }
-        _ => parse_err!("Unexpected token, expected: identifier or `{`"),
+        _ => return parse_err!("Unexpected token", tok.span, "`query`", "`mutation`", "`subscription`", "`{`"),
+    };
+
+    let body = parse_selection_set(stream)?;
+    Ok(Some(Operation {
+        kind,
+        name,
+        variables,
+        directives,
+        field: root_field(kind, body, tok.span.to(stream.prev_span())),
+    }))
+}
+
+fn root_field(kind: OperationKind, selections: Vec<Selection>, span: Span) -> Field {
+    let name = match kind {
+        OperationKind::Query => KQuery::TEXT,
+        OperationKind::Mutation => KMutation::TEXT,
+        OperationKind::Subscription => KSubscription::TEXT,
+    };
+    Field {
+        name: Name(name.to_owned()),
+        alias: None,
+        args: vec![],
+        directives: vec![],
+        selections,
+        span,
     }
-    // TODO assert no more tokens
 }
 
-fn parse_field_list(stream: &mut TokenStream) -> QlResult<Vec<Field>> {
-    stream.parse_list(maybe_parse_field)
+// $Name : Type (= Value)?
+fn maybe_parse_variable_def(stream: &mut TokenStream) -> QlResult<Option<VariableDefinition>> {
+    let tok = none_ok!(stream.peek_tok());
+    match tok.kind {
+        TokenKind::Atom(Atom::Dollar) => stream.bump(),
+        _ => return parse_err!("Unexpected token", tok.span, "`$`"),
+    }
+    let name = stream.expect(maybe_parse_name)?;
+    stream.eat(Atom::Colon)?;
+    let ty = parse_type(stream)?;
+    let default = match stream.peek_tok() {
+        Some(&Token { kind: TokenKind::Atom(Atom::Equals), .. }) => {
+            stream.bump();
+            Some(parse_value(stream)?)
+        }
+        _ => None,
+    };
+    Ok(Some(VariableDefinition {
+        name,
+        ty,
+        default,
+        span: tok.span.to(stream.prev_span()),
+    }))
+}
+
+fn parse_selection_list(stream: &mut TokenStream) -> QlResult<Vec<Selection>> {
+    stream.parse_list(maybe_parse_selection)
 }
 
 fn parse_arg_list(stream: &mut TokenStream) -> QlResult<Vec<(Name, Value)>> {
@@ -62,15 +197,59 @@ fn parse_name(stream: &mut TokenStream) -> QlResult<Name> {
     stream.expect(maybe_parse_name)
 }
 
-fn parse_value(stream: &mut TokenStream) -> QlResult<Value> {
+pub fn parse_value(stream: &mut TokenStream) -> QlResult<Value> {
     stream.expect(maybe_parse_value)
 }
 
-// Name (: Name)? (args)? { field list }?
+// { selections }
+fn parse_selection_set(stream: &mut TokenStream) -> QlResult<Vec<Selection>> {
+    let tok = stream.next_tok()?;
+    match tok.kind {
+        TokenKind::Tree(Bracket::Brace, ref toks) => parse_selection_list(&mut stream.tree(tok, toks)),
+        _ => parse_err!("Unexpected token", tok.span, "`{`"),
+    }
+}
+
+// Field | ...Name | ... (on Name)? { selections }
+fn maybe_parse_selection(stream: &mut TokenStream) -> QlResult<Option<Selection>> {
+    let tok = none_ok!(stream.peek_tok());
+    match tok.kind {
+        TokenKind::Atom(Atom::Spread) => stream.bump(),
+        _ => return Ok(maybe_parse_field(stream)?.map(Selection::Field)),
+    }
+
+    let type_condition = match stream.peek_tok() {
+        Some(&Token { kind: TokenKind::Atom(Atom::Name(KOn::TEXT)), .. }) => {
+            stream.bump();
+            Some(parse_name(stream)?)
+        }
+        Some(&Token { kind: TokenKind::Atom(Atom::Name(_)), .. }) => {
+            let name = parse_name(stream)?;
+            let directives = parse_directives(stream)?;
+            return Ok(Some(Selection::FragmentSpread(FragmentSpread {
+                name,
+                directives,
+                span: tok.span.to(stream.prev_span()),
+            })));
+        }
+        _ => None,
+    };
+    let directives = parse_directives(stream)?;
+    let selections = parse_selection_set(stream)?;
+    Ok(Some(Selection::InlineFragment(InlineFragment {
+        type_condition,
+        directives,
+        selections,
+        span: tok.span.to(stream.prev_span()),
+    })))
+}
+
+// Name (: Name)? (args)? { selections }?
 fn maybe_parse_field(stream: &mut TokenStream) -> QlResult<Option<Field>> {
+    let lo = stream.peek_span();
     let name_or_alias = none_ok!(maybe_parse_name(stream)?);
-    let (alias, name) = match none_ok!(stream.peek_tok()).kind {
-        TokenKind::Atom(Atom::Colon) => {
+    let (alias, name) = match stream.peek_tok() {
+        Some(&Token { kind: TokenKind::Atom(Atom::Colon), .. }) => {
             stream.bump();
             (Some(name_or_alias), parse_name(stream)?)
         },
@@ -78,17 +257,46 @@ fn maybe_parse_field(stream: &mut TokenStream) -> QlResult<Option<Field>> {
     };
 
     let args = maybe_parse_args(stream)?;
-    let fields = maybe_parse_fields(stream)?;
+    let directives = parse_directives(stream)?;
+    let selections = maybe_parse_selections(stream)?;
+    let span = lo.to(stream.prev_span());
 
     Ok(Some(Field {
         name,
         alias,
         args,
-        fields,
+        directives,
+        selections,
+        span,
     }))
 }
 
 // Name : Value
+// Zero or more directives, e.g., `@skip(if: true) @foo`.
+pub fn parse_directives(stream: &mut TokenStream) -> QlResult<Vec<Directive>> {
+    let mut result = vec![];
+    while let Some(d) = maybe_parse_directive(stream)? {
+        result.push(d);
+    }
+    Ok(result)
+}
+
+fn maybe_parse_directive(stream: &mut TokenStream) -> QlResult<Option<Directive>> {
+    let tok = none_ok!(stream.peek_tok());
+    match tok.kind {
+        TokenKind::Atom(Atom::At) => stream.bump(),
+        _ => return Ok(None),
+    }
+
+    let name = parse_name(stream)?;
+    let args = maybe_parse_args(stream)?;
+    Ok(Some(Directive {
+        name,
+        args,
+        span: tok.span.to(stream.prev_span()),
+    }))
+}
+
 fn maybe_parse_arg(stream: &mut TokenStream) -> QlResult<Option<(Name, Value)>> {
     let name = none_ok!(maybe_parse_name(stream)?);
     stream.eat(Atom::Colon)?;
@@ -97,40 +305,82 @@ fn maybe_parse_arg(stream: &mut TokenStream) -> QlResult<Option<(Name, Value)>>
 }
 
 fn maybe_parse_value(stream: &mut TokenStream) -> QlResult<Option<Value>> {
-    let result = match none_ok!(stream.peek_tok()).kind {
-        TokenKind::Atom(Atom::Name("null")) => Value::Null,
-        TokenKind::Atom(Atom::Name(s)) => Value::Name(Name(s.to_owned())),
-        // TODO this is dumb - we parse a string to a number in the tokeniser, then
-        // convert it back to a string here. Perhaps we'll add a Number value later?
-        // If not we should treat numbers as Names in the tokeniser.
-        TokenKind::Atom(Atom::Number(n)) => Value::Name(Name(n.to_string())),
-        TokenKind::Atom(Atom::String(s)) => Value::String(s.to_owned()),
+    let tok = none_ok!(stream.peek_tok());
+    let kind = match tok.kind {
+        TokenKind::Atom(Atom::Dollar) => {
+            stream.bump();
+            let name = stream.expect(maybe_parse_name)?;
+            let span = tok.span.to(stream.prev_span());
+            return Ok(Some(Value::new(ValueKind::Variable(name), span)));
+        }
+        TokenKind::Atom(Atom::Name("null")) => ValueKind::Null,
+        TokenKind::Atom(Atom::Name("true")) => ValueKind::Boolean(true),
+        TokenKind::Atom(Atom::Name("false")) => ValueKind::Boolean(false),
+        TokenKind::Atom(Atom::Name(s)) => ValueKind::Enum(Name(s.to_owned())),
+        TokenKind::Atom(Atom::Int(n)) => ValueKind::Int(n),
+        TokenKind::Atom(Atom::Float(n)) => ValueKind::Float(n),
+        // Invalid escapes have already been reported by the lexer.
+        TokenKind::Atom(Atom::String(s)) => {
+            ValueKind::String(string_value(s).unwrap_or_else(|| s.to_owned()))
+        }
+        TokenKind::Atom(Atom::BlockString(s)) => ValueKind::String(block_string_value(s)),
         TokenKind::Tree(Bracket::Square, ref toks) => {
-            Value::Array(parse_value_list(&mut TokenStream::new(toks))?)
+            ValueKind::Array(parse_value_list(&mut stream.tree(tok, toks))?)
+        }
+        TokenKind::Tree(Bracket::Brace, ref toks) => {
+            ValueKind::Object(parse_arg_list(&mut stream.tree(tok, toks))?)
         }
-        _ => return parse_err!("Unexpected token, expected: value"),
+        _ => return parse_err!("Unexpected token", tok.span, "value"),
     };
 
     stream.bump();
-    Ok(Some(result))
+    Ok(Some(Value::new(kind, tok.span)))
 }
 
 fn maybe_parse_args(stream: &mut TokenStream) -> QlResult<Vec<(Name, Value)>> {
     stream.maybe_parse_seq(Bracket::Paren, parse_arg_list)
 }
 
-fn maybe_parse_fields(stream: &mut TokenStream) -> QlResult<Vec<Field>> {
-    stream.maybe_parse_seq(Bracket::Brace, parse_field_list)
+fn maybe_parse_selections(stream: &mut TokenStream) -> QlResult<Vec<Selection>> {
+    stream.maybe_parse_seq(Bracket::Brace, parse_selection_list)
+}
+
+struct KQuery;
+struct KMutation;
+struct KSubscription;
+struct KFragment;
+struct KOn;
+
+impl Keyword for KQuery {
+    const TEXT: &'static str = "query";
+}
+impl Keyword for KMutation {
+    const TEXT: &'static str = "mutation";
+}
+impl Keyword for KSubscription {
+    const TEXT: &'static str = "subscription";
+}
+impl Keyword for KFragment {
+    const TEXT: &'static str = "fragment";
+}
+impl Keyword for KOn {
+    const TEXT: &'static str = "on";
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use parser::lexer::tokenise;
+    use types::Span;
+
+    fn value(kind: ValueKind) -> Value {
+        Value::new(kind, Span::dummy())
+    }
 
     #[test]
     fn test_parse_name() {
         let tokens = tokenise("foo bar!").unwrap();
-        let mut ts = TokenStream::new(&tokens);
+        let mut ts = TokenStream::top_level(&tokens);
         assert_eq!(parse_name(&mut ts).unwrap().0, "foo");
         assert_eq!(parse_name(&mut ts).unwrap().0, "bar");
         ts.eat(Atom::Bang).unwrap();
@@ -138,66 +388,87 @@ mod test {
 
     #[test]
     fn test_parse_value() {
-        let tokens = tokenise("null \"foo\" 42 bar [null, null, foo, \"bar\"]").unwrap();
-        let mut ts = TokenStream::new(&tokens);
-        assert_eq!(parse_value(&mut ts).unwrap(), Value::Null);
+        let tokens = tokenise("null \"foo\" 42 -1.5e3 true false bar [null, null, foo, \"bar\"]").unwrap();
+        let mut ts = TokenStream::top_level(&tokens);
+        assert_eq!(parse_value(&mut ts).unwrap(), value(ValueKind::Null));
         assert_eq!(
             parse_value(&mut ts).unwrap(),
-            Value::String("foo".to_owned())
+            value(ValueKind::String("foo".to_owned()))
         );
         assert_eq!(
             parse_value(&mut ts).unwrap(),
-            Value::Name(Name("42".to_owned()))
+            value(ValueKind::Int(42))
         );
+        assert_eq!(parse_value(&mut ts).unwrap(), value(ValueKind::Float(-1500.0)));
+        assert_eq!(parse_value(&mut ts).unwrap(), value(ValueKind::Boolean(true)));
+        assert_eq!(parse_value(&mut ts).unwrap(), value(ValueKind::Boolean(false)));
         assert_eq!(
             parse_value(&mut ts).unwrap(),
-            Value::Name(Name("bar".to_owned()))
+            value(ValueKind::Enum(Name("bar".to_owned())))
         );
         assert_eq!(
             parse_value(&mut ts).unwrap(),
-            Value::Array(vec![
-                Value::Null,
-                Value::Null,
-                Value::Name(Name("foo".to_owned())),
-                Value::String("bar".to_owned()),
-            ])
+            value(ValueKind::Array(vec![
+                value(ValueKind::Null),
+                value(ValueKind::Null),
+                value(ValueKind::Enum(Name("foo".to_owned()))),
+                value(ValueKind::String("bar".to_owned())),
+            ]))
         );
+
+        let tokens = tokenise("{a: 1, b: {c: \"x\"}} {}").unwrap();
+        let mut ts = TokenStream::top_level(&tokens);
+        assert_eq!(
+            parse_value(&mut ts).unwrap(),
+            value(ValueKind::Object(vec![
+                (Name("a".to_owned()), value(ValueKind::Int(1))),
+                (
+                    Name("b".to_owned()),
+                    value(ValueKind::Object(vec![
+                        (Name("c".to_owned()), value(ValueKind::String("x".to_owned()))),
+                    ])),
+                ),
+            ]))
+        );
+        assert_eq!(parse_value(&mut ts).unwrap(), value(ValueKind::Object(vec![])));
     }
 
     #[test]
     fn test_parse_args() {
         let tokens = tokenise("  ").unwrap();
-        let mut ts = TokenStream::new(&tokens);
+        let mut ts = TokenStream::top_level(&tokens);
         assert_eq!(maybe_parse_args(&mut ts).unwrap(), vec![]);
 
         let tokens = tokenise("(x: 42, foo: \"bar\")").unwrap();
-        let mut ts = TokenStream::new(&tokens);
+        let mut ts = TokenStream::top_level(&tokens);
         assert_eq!(
             maybe_parse_args(&mut ts).unwrap(),
             vec![
-                (Name("x".to_owned()), Value::Name(Name("42".to_owned()))),
-                (Name("foo".to_owned()), Value::String("bar".to_owned())),
+                (Name("x".to_owned()), value(ValueKind::Int(42))),
+                (Name("foo".to_owned()), value(ValueKind::String("bar".to_owned()))),
             ]
         );
     }
 
     #[test]
-    fn test_parse_fields() {
+    fn test_parse_selections() {
         let tokens = tokenise("").unwrap();
-        let mut ts = TokenStream::new(&tokens);
-        assert_eq!(maybe_parse_fields(&mut ts).unwrap(), vec![]);
+        let mut ts = TokenStream::top_level(&tokens);
+        assert_eq!(maybe_parse_selections(&mut ts).unwrap(), vec![]);
 
         let tokens = tokenise("{}").unwrap();
-        let mut ts = TokenStream::new(&tokens);
-        assert_eq!(maybe_parse_fields(&mut ts).unwrap(), vec![]);
+        let mut ts = TokenStream::top_level(&tokens);
+        assert_eq!(maybe_parse_selections(&mut ts).unwrap(), vec![]);
 
-        fn name_field(s: &str) -> Field {
-            Field {
+        fn name_field(s: &str) -> Selection {
+            Selection::Field(Field {
                 name: Name(s.to_owned()),
                 alias: None,
                 args: vec![],
-                fields: vec![],
-            }
+                directives: vec![],
+                selections: vec![],
+                span: Span::dummy(),
+            })
         }
 
         let tokens = tokenise(
@@ -208,24 +479,54 @@ mod test {
                 b
             }}",
         ).unwrap();
-        let mut ts = TokenStream::new(&tokens);
+        let mut ts = TokenStream::top_level(&tokens);
         assert_eq!(
-            maybe_parse_fields(&mut ts).unwrap(),
+            maybe_parse_selections(&mut ts).unwrap(),
             vec![
                 name_field("a"),
                 name_field("foo"),
-                Field {
+                Selection::Field(Field {
                     name: Name("bar".to_owned()),
                     alias: None,
-                    args: vec![(Name("x".to_owned()), Value::Name(Name("42".to_owned())))],
-                    fields: vec![],
-                },
-                Field {
+                    args: vec![(Name("x".to_owned()), value(ValueKind::Int(42)))],
+                    directives: vec![],
+                    selections: vec![],
+                    span: Span::dummy(),
+                }),
+                Selection::Field(Field {
                     name: Name("baz".to_owned()),
                     alias: None,
                     args: vec![],
-                    fields: vec![name_field("a"), name_field("b")],
-                },
+                    directives: vec![],
+                    selections: vec![name_field("a"), name_field("b")],
+                    span: Span::dummy(),
+                }),
+            ]
+        );
+
+        let tokens = tokenise("{ a ...foo ... on Human { b } ... { c } }").unwrap();
+        let mut ts = TokenStream::top_level(&tokens);
+        assert_eq!(
+            maybe_parse_selections(&mut ts).unwrap(),
+            vec![
+                name_field("a"),
+                Selection::FragmentSpread(FragmentSpread {
+                    name: Name("foo".to_owned()),
+                    directives: vec![],
+                    span: Span::dummy(),
+                }),
+                Selection::InlineFragment(InlineFragment {
+                    type_condition: Some(Name("Human".to_owned())),
+                    directives: vec![],
+                    selections: vec![name_field("b")],
+                    span: Span::dummy(),
+                }),
+                Selection::InlineFragment(InlineFragment {
+                    type_condition: None,
+                    directives: vec![],
+                    selections: vec![name_field("c")],
+                    span: Span::dummy(),
+                }),
             ]
         );
     }
@@ -241,29 +542,26 @@ mod test {
           }
         }",
         ).unwrap();
-        let mut ts = TokenStream::new(&tokens);
-        let result = parse_operation(&mut ts).unwrap();
-        if let Operation::Query(f) = result {
-            println!("{:?}", f);
-            assert_eq!(f.name.0, "query");
-            assert_eq!(f.alias, None);
-            assert_eq!(f.args.len(), 0);
-            assert_eq!(f.fields.len(), 1);
-            assert_eq!(f.fields[0].name.0, "human");
-            assert_eq!(
-                &f.fields[0].args[0],
-                &(Name("id".to_owned()), Value::Name(Name("1002".to_owned())))
-            );
-            assert_eq!(f.fields[0].fields.len(), 3);
-            assert_eq!(f.fields[0].fields[0].name.0, "name");
-            assert_eq!(f.fields[0].fields[0].alias, None);
-            assert_eq!(f.fields[0].fields[1].name.0, "appearsIn");
-            assert_eq!(f.fields[0].fields[1].alias, None);
-            assert_eq!(f.fields[0].fields[2].name.0, "id");
-            assert_eq!(f.fields[0].fields[2].alias, None);
-        } else {
-            panic!();
-        }
+        let mut ts = TokenStream::top_level(&tokens);
+        let result = maybe_parse_operation(&mut ts).unwrap().unwrap();
+        assert_eq!(result.kind, OperationKind::Query);
+        let f = result.field;
+        assert_eq!(f.name.0, "query");
+        assert_eq!(f.alias, None);
+        assert_eq!(f.args.len(), 0);
+        assert_eq!(f.selections.len(), 1);
+        assert_eq!(f.selections[0].assert_field().name.0, "human");
+        assert_eq!(
+            &f.selections[0].assert_field().args[0],
+            &(Name("id".to_owned()), value(ValueKind::Int(1002)))
+        );
+        assert_eq!(f.selections[0].assert_field().selections.len(), 3);
+        assert_eq!(f.selections[0].assert_field().selections[0].assert_field().name.0, "name");
+        assert_eq!(f.selections[0].assert_field().selections[0].assert_field().alias, None);
+        assert_eq!(f.selections[0].assert_field().selections[1].assert_field().name.0, "appearsIn");
+        assert_eq!(f.selections[0].assert_field().selections[1].assert_field().alias, None);
+        assert_eq!(f.selections[0].assert_field().selections[2].assert_field().name.0, "id");
+        assert_eq!(f.selections[0].assert_field().selections[2].assert_field().alias, None);
     }
 
     #[test]
@@ -273,23 +571,192 @@ mod test {
             called: name
           }
         }").unwrap();
-        let mut ts = TokenStream::new(&tokens);
-        let result = parse_operation(&mut ts).unwrap();
-        if let Operation::Query(f) = result {
-            assert_eq!(f.name.0, "query");
-            assert_eq!(f.args.len(), 0);
-            assert_eq!(f.fields.len(), 1);
-            assert_eq!(f.fields[0].name.0, "human");
-            assert_eq!(f.fields[0].alias, Some(Name("character".to_owned())));
-            assert_eq!(
-                &f.fields[0].args[0],
-                &(Name("id".to_owned()), Value::Name(Name("1002".to_owned())))
-            );
-            assert_eq!(f.fields[0].fields.len(), 1);
-            assert_eq!(f.fields[0].fields[0].name.0, "name");
-            assert_eq!(f.fields[0].fields[0].alias, Some(Name("called".to_owned())));
-        } else {
-            panic!();
+        let mut ts = TokenStream::top_level(&tokens);
+        let result = maybe_parse_operation(&mut ts).unwrap().unwrap();
+        assert_eq!(result.kind, OperationKind::Query);
+        let f = result.field;
+        assert_eq!(f.name.0, "query");
+        assert_eq!(f.args.len(), 0);
+        assert_eq!(f.selections.len(), 1);
+        assert_eq!(f.selections[0].assert_field().name.0, "human");
+        assert_eq!(f.selections[0].assert_field().alias, Some(Name("character".to_owned())));
+        assert_eq!(
+            &f.selections[0].assert_field().args[0],
+            &(Name("id".to_owned()), value(ValueKind::Int(1002)))
+        );
+        assert_eq!(f.selections[0].assert_field().selections.len(), 1);
+        assert_eq!(f.selections[0].assert_field().selections[0].assert_field().name.0, "name");
+        assert_eq!(f.selections[0].assert_field().selections[0].assert_field().alias, Some(Name("called".to_owned())));
+    }
+
+    #[test]
+    fn test_parse_spans() {
+        let tokens = tokenise("{\n  human(id: 1002) {\n    name\n  }\n}").unwrap();
+        let mut ts = TokenStream::top_level(&tokens);
+        let result = maybe_parse_operation(&mut ts).unwrap().unwrap();
+        let human = &result.get_field().selections[0].assert_field();
+        assert_eq!((human.span.lo, human.span.hi), (4, 34));
+        assert_eq!(human.span.start.row.0, 1);
+        assert_eq!(human.span.end.row.0, 3);
+        assert_eq!((human.args[0].1.span.lo, human.args[0].1.span.hi), (14, 18));
+        let name = &human.selections[0].assert_field();
+        assert_eq!((name.span.lo, name.span.hi), (26, 30));
+        assert_eq!(name.span.start.col.0, 4);
+
+        let (doc, errs) = parse_query_with_diagnostics("{ human(id: ) }");
+        assert_eq!(doc.operations.len(), 1);
+        assert_eq!(errs.len(), 1);
+        assert_eq!(errs[0].span.lo, 12);
+    }
+
+    #[test]
+    fn test_parse_variables() {
+        let tokens = tokenise(
+            r#"query HumanQuery($id: ID!, $names: [String] = ["a", "b"]) {
+          human(id: $id) {
+            name
+          }
+        }"#,
+        ).unwrap();
+        let mut ts = TokenStream::top_level(&tokens);
+        let result = maybe_parse_operation(&mut ts).unwrap().unwrap();
+        assert_eq!(result.kind, OperationKind::Query);
+        assert_eq!(result.variables.len(), 2);
+        assert_eq!(result.variables[0].name.0, "id");
+        assert!(!result.variables[0].ty.nullable);
+        assert!(result.variables[0].default.is_none());
+        assert_eq!((result.variables[0].span.lo, result.variables[0].span.hi), (17, 25));
+        assert_eq!(result.variables[1].name.0, "names");
+        assert_eq!(
+            result.variables[1].default,
+            Some(value(ValueKind::Array(vec![
+                value(ValueKind::String("a".to_owned())),
+                value(ValueKind::String("b".to_owned())),
+            ])))
+        );
+        let arg = &result.field.selections[0].assert_field().args[0];
+        assert_eq!(arg, &(Name("id".to_owned()), value(ValueKind::Variable(Name("id".to_owned())))));
+        assert_eq!((arg.1.span.lo, arg.1.span.hi), (80, 83));
+
+        let tokens = tokenise("query Traced($a: Int) @traced(level: 1) { a }").unwrap();
+        let mut ts = TokenStream::top_level(&tokens);
+        let result = maybe_parse_operation(&mut ts).unwrap().unwrap();
+        assert_eq!(result.directives.len(), 1);
+        assert_eq!(result.directives[0].name.0, "traced");
+        assert_eq!((result.directives[0].span.lo, result.directives[0].span.hi), (22, 39));
+
+        let (_, errs) = parse_query_with_diagnostics("query ($id: ID!, id: ID) { a }");
+        assert_eq!(errs.len(), 1);
+        assert_eq!(errs[0].expected, vec!["`$`"]);
+    }
+
+    #[test]
+    fn test_parse_document() {
+        let doc = parse_query(
+            r"query A { a }
+
+            mutation B($x: Int) { b(x: $x) }
+            query { c }
+            subscription D { d }",
+        ).unwrap();
+        assert_eq!(doc.operations.len(), 4);
+        assert_eq!(doc.operations[0].name, Some(Name("A".to_owned())));
+        assert_eq!(doc.operations[0].kind, OperationKind::Query);
+        assert_eq!(doc.operations[1].name, Some(Name("B".to_owned())));
+        assert_eq!(doc.operations[1].kind, OperationKind::Mutation);
+        assert_eq!(doc.operations[1].field.name.0, "mutation");
+        assert_eq!(doc.operations[1].variables.len(), 1);
+        assert_eq!(doc.operations[2].name, None);
+        assert_eq!(doc.operations[2].field.selections[0].assert_field().name.0, "c");
+        assert_eq!(doc.operations[3].kind, OperationKind::Subscription);
+        assert_eq!(doc.operations[3].field.name.0, "subscription");
+
+        // Trailing tokens are an error, but parsing carries on afterwards.
+        let (doc, errs) = parse_query_with_diagnostics("{ a } b c { d }");
+        assert_eq!(doc.operations.len(), 2);
+        assert_eq!(errs.len(), 1);
+        assert_eq!(errs[0].span.lo, 6);
+        assert_eq!(errs[0].expected, vec!["`query`", "`mutation`", "`subscription`", "`{`"]);
+
+        let (doc, errs) = parse_query_with_diagnostics("  \n ");
+        assert!(doc.operations.is_empty());
+        assert_eq!(errs.len(), 1);
+        assert_eq!(errs[0].kind, DiagnosticKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_parse_unclosed() {
+        for input in &["{\n", "{ hero {\n", "query {\n  a\n", "{ a(x: [1\n"] {
+            let (_, errs) = parse_query_with_diagnostics(input);
+            assert!(!errs.is_empty(), "{:?}", input);
+            assert_eq!(errs[0].kind, DiagnosticKind::UnclosedDelimiter, "{:?}", input);
+            assert_eq!(errs[0].span.lo, input.find('{').unwrap(), "{:?}", input);
+            assert!(parse_query(input).is_err());
+        }
+
+        // The contents of an unclosed tree are still parsed.
+        let (doc, errs) = parse_query_with_diagnostics("query {\n  a\n  b { c }\n");
+        assert_eq!(errs.len(), 1);
+        assert_eq!(errs[0].message, "Unclosed delimiter: expected }");
+        let names: Vec<_> = doc.operations[0].field.selections.iter().map(|s| &*s.assert_field().name.0).collect();
+        assert_eq!(names, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_parse_fragments() {
+        let doc = parse_query(
+            r"{ hero { ...names } }
+
+            fragment names on Character {
+                name
+                ... on Human { homePlanet }
+            }",
+        ).unwrap();
+        assert_eq!(doc.operations.len(), 1);
+        assert_eq!(doc.fragments.len(), 1);
+        let fragment = &doc.fragments[0];
+        assert_eq!(fragment.name.0, "names");
+        assert_eq!(fragment.type_condition.0, "Character");
+        assert_eq!(fragment.selections.len(), 2);
+        assert_eq!((fragment.span.lo, fragment.span.hi), (35, 143));
+
+        let (_, errs) = parse_query_with_diagnostics("{ a } fragment on on T { a } fragment f T { a }");
+        assert_eq!(errs.len(), 2);
+        assert_eq!(errs[0].span.lo, 15);
+        assert_eq!(errs[1].span.lo, 40);
+    }
+
+    #[test]
+    fn test_parse_directives() {
+        let tokens = tokenise(
+            "{ a @skip(if: true) ...foo @include(if: $x) ... on Human @foo @bar { b } }",
+        ).unwrap();
+        let mut ts = TokenStream::top_level(&tokens);
+        let selections = maybe_parse_selections(&mut ts).unwrap();
+        assert_eq!(selections.len(), 3);
+
+        let directives = selections[0].directives();
+        assert_eq!(directives.len(), 1);
+        assert_eq!(directives[0].name.0, "skip");
+        assert_eq!(
+            directives[0].args,
+            vec![(Name("if".to_owned()), value(ValueKind::Boolean(true)))]
+        );
+        assert_eq!((directives[0].span.lo, directives[0].span.hi), (4, 19));
+
+        let directives = selections[1].directives();
+        assert_eq!(directives.len(), 1);
+        assert_eq!(directives[0].name.0, "include");
+        assert_eq!(
+            directives[0].args,
+            vec![(Name("if".to_owned()), value(ValueKind::Variable(Name("x".to_owned()))))]
+        );
+
+        let names: Vec<&str> = selections[2].directives().iter().map(|d| &*d.name.0).collect();
+        assert_eq!(names, vec!["foo", "bar"]);
+        match selections[2] {
+            Selection::InlineFragment(ref inline) => assert_eq!(inline.selections.len(), 1),
+            ref s => panic!("Expected inline fragment, found {:?}", s),
         }
     }
 }
@@ -1,9 +1,9 @@
+use types::Span;
+
 #[derive(Clone, Debug)]
 pub struct Token<'a> {
     pub kind: TokenKind<'a>,
-    // FIXME cfg this
-    // TODO spans
-    // pub span: ::lexer::Span,
+    pub span: Span,
 }
 
 #[derive(Clone, Debug)]
@@ -37,15 +37,49 @@ impl Bracket {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Copy)]
+#[derive(Clone, Debug, PartialEq, Copy)]
 pub enum Atom<'a> {
     NewLine,
     Comma,
     Colon,
     Bang,
+    Dollar,
+    At,
+    Equals,
+    Pipe,
+    Amp,
+    // `...`
+    Spread,
     Name(&'a str),
-    Number(isize),
+    Int(i64),
+    Float(f64),
+    // Strings are kept as written in the source (i.e., escape sequences are not
+    // processed), use `lexer::string_value` and `lexer::block_string_value` to
+    // get their values.
     String(&'a str),
+    BlockString(&'a str),
+}
+
+impl<'a> Atom<'a> {
+    // A description of the kind of atom, for error messages.
+    pub fn describe(&self) -> &'static str {
+        match *self {
+            Atom::NewLine => "newline",
+            Atom::Comma => "`,`",
+            Atom::Colon => "`:`",
+            Atom::Bang => "`!`",
+            Atom::Dollar => "`$`",
+            Atom::At => "`@`",
+            Atom::Equals => "`=`",
+            Atom::Pipe => "`|`",
+            Atom::Amp => "`&`",
+            Atom::Spread => "`...`",
+            Atom::Name(_) => "name",
+            Atom::Int(_) => "integer",
+            Atom::Float(_) => "float",
+            Atom::String(_) | Atom::BlockString(_) => "string",
+        }
+    }
 }
 
 mod display {
@@ -85,9 +119,17 @@ mod display {
                 Atom::Comma => write!(f, ","),
                 Atom::Colon => write!(f, ":"),
                 Atom::Bang => write!(f, "!"),
+                Atom::Dollar => write!(f, "$"),
+                Atom::At => write!(f, "@"),
+                Atom::Equals => write!(f, "="),
+                Atom::Pipe => write!(f, "|"),
+                Atom::Amp => write!(f, "&"),
+                Atom::Spread => write!(f, "..."),
                 Atom::Name(n) => write!(f, "{}", n),
-                Atom::Number(n) => write!(f, "{}", n),
+                Atom::Int(n) => write!(f, "{}", n),
+                Atom::Float(n) => write!(f, "{:?}", n),
                 Atom::String(s) => write!(f, "\"{}\"", s),
+                Atom::BlockString(s) => write!(f, "\"\"\"{}\"\"\"", s),
             }
         }
     }
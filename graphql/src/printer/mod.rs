@@ -0,0 +1,126 @@
+// Prints query documents and schemas back to GraphQL text.
+
+mod print_idl;
+mod print_query;
+
+pub use self::print_idl::print_schema;
+pub use self::print_query::{print_document, print_operation, print_value};
+
+use schema;
+
+// Schemas are always pretty printed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Style {
+    // One selection per line, indented by two spaces.
+    Pretty,
+    // No whitespace except where it is needed to separate tokens.
+    Minified,
+}
+
+// Accumulates output and keeps track of indentation.
+struct Printer {
+    buf: String,
+    style: Style,
+    indent: usize,
+}
+
+impl Printer {
+    fn new(style: Style) -> Printer {
+        Printer {
+            buf: String::new(),
+            style,
+            indent: 0,
+        }
+    }
+
+    fn finish(self) -> String {
+        self.buf
+    }
+
+    fn write(&mut self, s: &str) {
+        self.buf.push_str(s);
+    }
+
+    // Only written when pretty printing, e.g., the space after a colon.
+    fn space(&mut self) {
+        if self.style == Style::Pretty {
+            self.buf.push(' ');
+        }
+    }
+
+    fn newline(&mut self) {
+        self.buf.push('\n');
+        for _ in 0..self.indent {
+            self.buf.push_str("  ");
+        }
+    }
+
+    // Separates top-level definitions with a blank line.
+    fn definition_break(&mut self) {
+        if self.style == Style::Pretty && !self.buf.is_empty() {
+            self.buf.push_str("\n\n");
+        }
+    }
+
+    // `{ items }`, pretty printed with each item on its own line.
+    fn block<T, F>(&mut self, items: &[T], f: F)
+    where
+        F: Fn(&mut Printer, &T),
+    {
+        self.write("{");
+        self.indent += 1;
+        for (i, item) in items.iter().enumerate() {
+            match self.style {
+                Style::Pretty => self.newline(),
+                Style::Minified => if i > 0 {
+                    self.write(",");
+                },
+            }
+            f(self, item);
+        }
+        self.indent -= 1;
+        if self.style == Style::Pretty {
+            self.newline();
+        }
+        self.write("}");
+    }
+
+    // `open items close` on one line, e.g., an argument list.
+    fn list<T, F>(&mut self, open: &str, close: &str, items: &[T], f: F)
+    where
+        F: Fn(&mut Printer, &T),
+    {
+        self.write(open);
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                self.write(",");
+                self.space();
+            }
+            f(self, item);
+        }
+        self.write(close);
+    }
+
+    fn ty(&mut self, ty: &schema::Type) {
+        self.write(&ty.to_string());
+    }
+
+    // A quoted string, escaped so that the lexer reads back the same value.
+    fn string(&mut self, s: &str) {
+        self.buf.push('"');
+        for c in s.chars() {
+            match c {
+                '"' => self.buf.push_str("\\\""),
+                '\\' => self.buf.push_str("\\\\"),
+                '\n' => self.buf.push_str("\\n"),
+                '\r' => self.buf.push_str("\\r"),
+                '\t' => self.buf.push_str("\\t"),
+                '\u{8}' => self.buf.push_str("\\b"),
+                '\u{c}' => self.buf.push_str("\\f"),
+                c if (c as u32) < 0x20 => self.buf.push_str(&format!("\\u{:04X}", c as u32)),
+                c => self.buf.push(c),
+            }
+        }
+        self.buf.push('"');
+    }
+}
@@ -0,0 +1,338 @@
+use printer::{Printer, Style};
+use printer::print_query::{directives, value};
+use schema::{Argument, Directive, Extension, Field, Item, Schema, Variant, SCHEMA_NAME};
+use types::Name;
+
+// The schema definition comes first, then directive definitions in the order
+// they were defined, then types sorted by name. Extensions which have not been
+// applied are printed last.
+pub fn print_schema(schema: &Schema) -> String {
+    let mut p = Printer::new(Style::Pretty);
+    if let Some(item) = schema.items.get(&Name(SCHEMA_NAME.to_owned())) {
+        p.definition_break();
+        description(&mut p, item.description());
+        p.write(SCHEMA_NAME);
+        directives(&mut p, item.directives());
+        p.space();
+        p.block(item.fields(), field);
+    }
+
+    for d in &schema.directives {
+        p.definition_break();
+        directive_def(&mut p, d);
+    }
+
+    let mut names: Vec<_> = schema.items.keys().filter(|n| n.0 != SCHEMA_NAME).collect();
+    names.sort_by(|a, b| a.0.cmp(&b.0));
+    for name in names {
+        p.definition_break();
+        self::item(&mut p, name, &schema.items[name]);
+    }
+
+    for ext in &schema.extensions {
+        p.definition_break();
+        extension(&mut p, ext);
+    }
+    p.finish()
+}
+
+fn item(p: &mut Printer, name: &Name, item: &Item) {
+    description(p, item.description());
+    match *item {
+        // Only named `schema`, which is printed separately.
+        Item::Schema(_) => unreachable!(),
+        Item::Object(ref o) => {
+            p.write("type ");
+            p.write(&name.0);
+            implements(p, &o.implements);
+            directives(p, &o.directives);
+            p.space();
+            p.block(&o.fields, field);
+        }
+        Item::Interface(ref i) => {
+            p.write("interface ");
+            p.write(&name.0);
+            implements(p, &i.implements);
+            directives(p, &i.directives);
+            p.space();
+            p.block(&i.fields, field);
+        }
+        Item::Enum(ref e) => {
+            p.write("enum ");
+            p.write(&name.0);
+            directives(p, &e.directives);
+            p.space();
+            p.block(&e.variants, variant);
+        }
+        Item::InputObject(ref i) => {
+            p.write("input ");
+            p.write(&name.0);
+            directives(p, &i.directives);
+            p.space();
+            p.block(&i.fields, field);
+        }
+        Item::Union(ref u) => {
+            p.write("union ");
+            p.write(&name.0);
+            directives(p, &u.directives);
+            p.write(" = ");
+            let members: Vec<_> = u.members.iter().map(|m| &*m.0).collect();
+            p.write(&members.join(" | "));
+        }
+        Item::Scalar(ref s) => {
+            p.write("scalar ");
+            p.write(&name.0);
+            directives(p, &s.directives);
+        }
+    }
+}
+
+fn extension(p: &mut Printer, ext: &Extension) {
+    p.write("extend ");
+    match ext.item {
+        Item::Schema(ref i) => {
+            p.write(SCHEMA_NAME);
+            directives(p, &i.directives);
+            extension_block(p, &i.fields, field);
+            return;
+        }
+        Item::Object(_) => p.write("type "),
+        Item::Interface(_) => p.write("interface "),
+        Item::Enum(_) => p.write("enum "),
+        // Only the above kinds of items can be extended.
+        _ => unreachable!(),
+    }
+    p.write(&ext.name.0);
+    implements(p, ext.item.implements());
+    directives(p, ext.item.directives());
+    match ext.item {
+        Item::Enum(ref e) => extension_block(p, &e.variants, variant),
+        ref item => extension_block(p, item.fields(), field),
+    }
+}
+
+// The body of an extension may be omitted.
+fn extension_block<T, F>(p: &mut Printer, items: &[T], f: F)
+where
+    F: Fn(&mut Printer, &T),
+{
+    if !items.is_empty() {
+        p.space();
+        p.block(items, f);
+    }
+}
+
+// `directive @name(args) on LOCATION | ...`
+fn directive_def(p: &mut Printer, d: &Directive) {
+    description(p, d.description.as_deref());
+    p.write("directive @");
+    p.write(&d.name.0);
+    args(p, &d.args);
+    p.write(" on ");
+    let locations: Vec<_> = d.locations.iter().map(|l| l.name()).collect();
+    p.write(&locations.join(" | "));
+}
+
+fn implements(p: &mut Printer, names: &[Name]) {
+    if !names.is_empty() {
+        p.write(" implements ");
+        let names: Vec<_> = names.iter().map(|n| &*n.0).collect();
+        p.write(&names.join(" & "));
+    }
+}
+
+fn field(p: &mut Printer, f: &Field) {
+    description(p, f.description.as_deref());
+    p.write(&f.name.0);
+    args(p, &f.args);
+    p.write(": ");
+    p.ty(&f.ty);
+    directives(p, &f.directives);
+}
+
+fn variant(p: &mut Printer, v: &Variant) {
+    description(p, v.description.as_deref());
+    p.write(&v.name.0);
+    directives(p, &v.directives);
+}
+
+// Arguments are printed on one line, unless any of them have a description.
+fn args(p: &mut Printer, args: &[Argument]) {
+    if args.is_empty() {
+        return;
+    }
+    if !args.iter().any(|a| a.description.is_some()) {
+        p.list("(", ")", args, arg);
+        return;
+    }
+
+    p.write("(");
+    p.indent += 1;
+    for a in args {
+        p.newline();
+        description(p, a.description.as_deref());
+        arg(p, a);
+    }
+    p.indent -= 1;
+    p.newline();
+    p.write(")");
+}
+
+fn arg(p: &mut Printer, a: &Argument) {
+    p.write(&a.name.0);
+    p.write(": ");
+    p.ty(&a.ty);
+    if let Some(ref default) = a.default {
+        p.write(" = ");
+        value(p, default);
+    }
+    directives(p, &a.directives);
+}
+
+// A description is followed by a newline, so the item it describes starts on
+// the next line.
+fn description(p: &mut Printer, description: Option<&str>) {
+    let description = match description {
+        Some(d) => d,
+        None => return,
+    };
+    if block_string_safe(description) {
+        p.write("\"\"\"");
+        for line in description.split('\n') {
+            if line.is_empty() {
+                p.write("\n");
+            } else {
+                p.newline();
+                p.write(&line.replace("\"\"\"", "\\\"\"\""));
+            }
+        }
+        p.newline();
+        p.write("\"\"\"");
+    } else {
+        p.string(description);
+    }
+    p.newline();
+}
+
+// Multi-line descriptions are printed as block strings if reading them back
+// (which strips common indentation and blank lines at either end) gives the
+// same text. Otherwise, they are printed as strings with escaped newlines.
+fn block_string_safe(s: &str) -> bool {
+    let is_blank = |l: &str| l.trim_matches(|c| c == ' ' || c == '\t').is_empty();
+    let lines: Vec<_> = s.split('\n').collect();
+    lines.len() > 1 && !s.contains('\r') && !is_blank(lines[0]) && !is_blank(lines[lines.len() - 1])
+        && lines.iter().all(|l| l.is_empty() || !is_blank(l))
+        && lines.iter().any(|l| !l.is_empty() && !l.starts_with([' ', '\t']))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::parse_idl::parse_schema;
+
+    const SCHEMA: &str = r#"
+        schema { query: Query, mutation: Mutation }
+        "Caches the result."
+        directive @cached(seconds: Int = 60) on FIELD_DEFINITION | OBJECT
+        type Query @cached {
+            hero(episode: Episode = JEDI): Character @cached(seconds: 10)
+            """
+            Search for anything.
+
+              Indented lines are kept.
+            """
+            search(
+                "Matched against names."
+                text: String!
+                first: Int
+            ): [SearchResult!]
+        }
+        type Mutation { noop: Boolean }
+        interface Character { id: ID!, name: String }
+        type Human implements Character { id: ID!, name: String, height: Float }
+        enum Episode { NEWHOPE, EMPIRE @deprecated(reason: "Not \"canon\"."), JEDI }
+        input ReviewInput { stars: Int!, tags: [String] }
+        union SearchResult = Human | Droid
+        "  Leading spaces."
+        scalar Date
+        extend type Droid { name: String }"#;
+
+    #[test]
+    fn test_print_schema() {
+        let schema = parse_schema(SCHEMA).unwrap();
+        assert_eq!(
+            print_schema(&schema),
+            r#"schema {
+  query: Query
+  mutation: Mutation
+}
+
+"Caches the result."
+directive @cached(seconds: Int = 60) on FIELD_DEFINITION | OBJECT
+
+interface Character {
+  id: ID!
+  name: String
+}
+
+"  Leading spaces."
+scalar Date
+
+enum Episode {
+  NEWHOPE
+  EMPIRE @deprecated(reason: "Not \"canon\".")
+  JEDI
+}
+
+type Human implements Character {
+  id: ID!
+  name: String
+  height: Float
+}
+
+type Mutation {
+  noop: Boolean
+}
+
+type Query @cached {
+  hero(episode: Episode = JEDI): Character @cached(seconds: 10)
+  """
+  Search for anything.
+
+    Indented lines are kept.
+  """
+  search(
+    "Matched against names."
+    text: String!
+    first: Int
+  ): [SearchResult!]
+}
+
+input ReviewInput {
+  stars: Int!
+  tags: [String]
+}
+
+union SearchResult = Human | Droid
+
+extend type Droid {
+  name: String
+}"#
+        );
+    }
+
+    #[test]
+    fn test_print_schema_round_trip() {
+        let schema = parse_schema(SCHEMA).unwrap();
+        let printed = print_schema(&schema);
+        let reparsed = parse_schema(&printed).unwrap();
+        assert_eq!(print_schema(&reparsed), printed);
+
+        let query = &reparsed.items[&Name("Query".to_owned())];
+        assert_eq!(
+            query.fields()[1].description.as_ref().unwrap(),
+            "Search for anything.\n\n  Indented lines are kept."
+        );
+        assert_eq!(reparsed.items[&Name("Date".to_owned())].description(), Some("  Leading spaces."));
+    }
+}
@@ -0,0 +1,240 @@
+use printer::{Printer, Style};
+use query::{Directive, Document, Field, Operation, OperationKind, Selection, Value, ValueKind, VariableDefinition};
+use types::Name;
+
+// Operations are printed before fragments, each in the order they were parsed.
+pub fn print_document(doc: &Document, style: Style) -> String {
+    let mut p = Printer::new(style);
+    for op in &doc.operations {
+        p.definition_break();
+        operation(&mut p, op);
+    }
+    for f in &doc.fragments {
+        p.definition_break();
+        p.write("fragment ");
+        p.write(&f.name.0);
+        p.write(" on ");
+        p.write(&f.type_condition.0);
+        p.space();
+        p.block(&f.selections, selection);
+    }
+    p.finish()
+}
+
+pub fn print_operation(op: &Operation, style: Style) -> String {
+    let mut p = Printer::new(style);
+    operation(&mut p, op);
+    p.finish()
+}
+
+pub fn print_value(value: &Value, style: Style) -> String {
+    let mut p = Printer::new(style);
+    self::value(&mut p, value);
+    p.finish()
+}
+
+fn operation(p: &mut Printer, op: &Operation) {
+    // An anonymous query without variables or directives uses the `{ ... }`
+    // shorthand.
+    if op.kind != OperationKind::Query || op.name.is_some() || !op.variables.is_empty()
+        || !op.directives.is_empty()
+    {
+        p.write(match op.kind {
+            OperationKind::Query => "query",
+            OperationKind::Mutation => "mutation",
+            OperationKind::Subscription => "subscription",
+        });
+        if let Some(ref name) = op.name {
+            p.write(" ");
+            p.write(&name.0);
+        }
+        if !op.variables.is_empty() {
+            p.list("(", ")", &op.variables, variable_def);
+        }
+        directives(p, &op.directives);
+        p.space();
+    }
+    p.block(&op.field.selections, selection);
+}
+
+fn variable_def(p: &mut Printer, var: &VariableDefinition) {
+    p.write("$");
+    p.write(&var.name.0);
+    p.write(":");
+    p.space();
+    p.ty(&var.ty);
+    if let Some(ref default) = var.default {
+        p.space();
+        p.write("=");
+        p.space();
+        value(p, default);
+    }
+}
+
+fn selection(p: &mut Printer, sel: &Selection) {
+    match *sel {
+        Selection::Field(ref f) => field(p, f),
+        Selection::FragmentSpread(ref s) => {
+            p.write("...");
+            p.write(&s.name.0);
+            directives(p, &s.directives);
+        }
+        Selection::InlineFragment(ref i) => {
+            p.write("...");
+            if let Some(ref ty) = i.type_condition {
+                p.space();
+                p.write("on ");
+                p.write(&ty.0);
+            }
+            directives(p, &i.directives);
+            p.space();
+            p.block(&i.selections, selection);
+        }
+    }
+}
+
+fn field(p: &mut Printer, f: &Field) {
+    if let Some(ref alias) = f.alias {
+        p.write(&alias.0);
+        p.write(":");
+        p.space();
+    }
+    p.write(&f.name.0);
+    if !f.args.is_empty() {
+        p.list("(", ")", &f.args, arg);
+    }
+    directives(p, &f.directives);
+    if !f.selections.is_empty() {
+        p.space();
+        p.block(&f.selections, selection);
+    }
+}
+
+pub(super) fn directives(p: &mut Printer, directives: &[Directive]) {
+    for d in directives {
+        p.space();
+        p.write("@");
+        p.write(&d.name.0);
+        if !d.args.is_empty() {
+            p.list("(", ")", &d.args, arg);
+        }
+    }
+}
+
+fn arg(p: &mut Printer, (name, v): &(Name, Value)) {
+    p.write(&name.0);
+    p.write(":");
+    p.space();
+    value(p, v);
+}
+
+pub(super) fn value(p: &mut Printer, v: &Value) {
+    match v.kind {
+        ValueKind::Null => p.write("null"),
+        ValueKind::Int(n) => p.write(&n.to_string()),
+        // `{:?}` always includes a `.` or exponent, so the value is read back
+        // as a float.
+        ValueKind::Float(n) => p.write(&format!("{:?}", n)),
+        ValueKind::Boolean(b) => p.write(if b { "true" } else { "false" }),
+        ValueKind::String(ref s) => p.string(s),
+        ValueKind::Enum(ref n) => p.write(&n.0),
+        ValueKind::Array(ref vs) => p.list("[", "]", vs, value),
+        ValueKind::Object(ref fields) => p.list("{", "}", fields, arg),
+        ValueKind::Variable(ref n) => {
+            p.write("$");
+            p.write(&n.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::parse_query::parse_query;
+
+    const QUERY: &str = r#"
+        query Hero($episode: Episode = JEDI, $ids: [ID!]!, $ratio: Float = 0.5) {
+            hero(episode: $episode) {
+                name,
+                friends @include(if: true) {
+                    ...HumanFields
+                    ... on Droid @skip(if: false) { primaryFunction }
+                }
+            }
+            search: search(text: "say \"hi\"\n", filter: {kinds: [HUMAN, DROID], limit: -3})
+        }
+        mutation @traced { addReview(stars: 5, commentary: null) }
+        fragment HumanFields on Human {
+            homePlanet
+            ... { name }
+        }"#;
+
+    #[test]
+    fn test_print_pretty() {
+        let doc = parse_query(QUERY).unwrap();
+        assert_eq!(
+            print_document(&doc, Style::Pretty),
+            r#"query Hero($episode: Episode = JEDI, $ids: [ID!]!, $ratio: Float = 0.5) {
+  hero(episode: $episode) {
+    name
+    friends @include(if: true) {
+      ...HumanFields
+      ... on Droid @skip(if: false) {
+        primaryFunction
+      }
+    }
+  }
+  search: search(text: "say \"hi\"\n", filter: {kinds: [HUMAN, DROID], limit: -3})
+}
+
+mutation @traced {
+  addReview(stars: 5, commentary: null)
+}
+
+fragment HumanFields on Human {
+  homePlanet
+  ... {
+    name
+  }
+}"#
+        );
+
+        let doc = parse_query("{ hero { name } }").unwrap();
+        assert_eq!(print_operation(&doc.operations[0], Style::Pretty), "{\n  hero {\n    name\n  }\n}");
+    }
+
+    #[test]
+    fn test_print_minified() {
+        let doc = parse_query(QUERY).unwrap();
+        assert_eq!(
+            print_document(&doc, Style::Minified),
+            "query Hero($episode:Episode=JEDI,$ids:[ID!]!,$ratio:Float=0.5){hero(episode:$episode){name,\
+             friends@include(if:true){...HumanFields,...on Droid@skip(if:false){primaryFunction}}},\
+             search:search(text:\"say \\\"hi\\\"\\n\",filter:{kinds:[HUMAN,DROID],limit:-3})}\
+             mutation@traced{addReview(stars:5,commentary:null)}\
+             fragment HumanFields on Human{homePlanet,...{name}}"
+        );
+    }
+
+    #[test]
+    fn test_print_round_trip() {
+        let doc = parse_query(QUERY).unwrap();
+        for &style in &[Style::Pretty, Style::Minified] {
+            let printed = print_document(&doc, style);
+            let reparsed = parse_query(&printed).unwrap();
+            assert_eq!(reparsed.operations.len(), doc.operations.len());
+            for (a, b) in reparsed.operations.iter().zip(doc.operations.iter()) {
+                assert_eq!(a.field, b.field);
+                assert_eq!(a.name, b.name);
+                assert_eq!(a.directives, b.directives);
+            }
+            assert_eq!(reparsed.fragments[0].selections, doc.fragments[0].selections);
+            assert_eq!(print_document(&reparsed, style), printed);
+        }
+
+        let value = parse_query("{ a(x: \"tab\\there \\u0001 \\u00e9\", y: 1e3) }").unwrap();
+        let printed = print_document(&value, Style::Minified);
+        assert_eq!(printed, "{a(x:\"tab\\there \\u0001 \u{e9}\",y:1000.0)}");
+        assert_eq!(parse_query(&printed).unwrap().operations[0].field, value.operations[0].field);
+    }
+}
@@ -8,6 +8,59 @@ pub struct Name(pub String);
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Id(pub String);
 
+pub type Position = ::rls_span::Position<::rls_span::ZeroIndexed>;
+
+// A region of the source text. `lo` and `hi` are byte offsets into the input,
+// `start` and `end` are the rows and columns of those offsets.
+//
+// Spans are not semantically significant, so two nodes which differ only in where
+// they were written compare (and hash) as equal.
+#[derive(Debug, Clone, Copy)]
+pub struct Span {
+    pub lo: usize,
+    pub hi: usize,
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    pub fn new(lo: usize, hi: usize, start: Position, end: Position) -> Span {
+        Span { lo, hi, start, end }
+    }
+
+    // For nodes which do not come from source text, e.g., schemas generated by
+    // the `schema` macro.
+    pub fn dummy() -> Span {
+        let pos = Position::new(
+            ::rls_span::Row::new_zero_indexed(0),
+            ::rls_span::Column::new_zero_indexed(0),
+        );
+        Span::new(0, 0, pos, pos)
+    }
+
+    // A span from the start of `self` to the end of `other`.
+    pub fn to(&self, other: Span) -> Span {
+        Span::new(self.lo, other.hi, self.start, other.end)
+    }
+
+    // An empty span at the end of `self`.
+    pub fn shrink_to_hi(&self) -> Span {
+        Span::new(self.hi, self.hi, self.end, self.end)
+    }
+}
+
+impl PartialEq for Span {
+    fn eq(&self, _other: &Span) -> bool {
+        true
+    }
+}
+
+impl Eq for Span {}
+
+impl ::std::hash::Hash for Span {
+    fn hash<H: ::std::hash::Hasher>(&self, _state: &mut H) {}
+}
+
 use std::fmt::{Display, Formatter, Result};
 
 impl Display for Name {
@@ -21,3 +74,10 @@ impl Display for Id {
         self.0.fmt(f)
     }
 }
+
+// Rows and columns are displayed one-indexed, as an editor would.
+impl Display for Span {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}:{}", self.start.row.0 + 1, self.start.col.0 + 1)
+    }
+}
@@ -10,45 +10,134 @@ query {
 */
 use {QlError, QlResult};
 use execution::Context;
+use introspection;
 use parser::parse_query::parse_query;
-use types::{result, schema, Id, Name};
+use printer::{self, Style};
+use types::{result, schema, Id, Name, Span};
 
 use std::collections::HashMap;
+use std::convert::TryFrom;
 
 pub type Variables = HashMap<String, Value>;
 
-// TODO variables, directives
 #[derive(Clone, Debug)]
-pub enum Operation {
-    Query(Field),
-    // TODO
+pub struct Document {
+    pub operations: Vec<Operation>,
+    pub fragments: Vec<Fragment>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Operation {
+    pub kind: OperationKind,
+    // `None` for anonymous operations, including the `{ ... }` shorthand.
+    pub name: Option<Name>,
+    pub variables: Vec<VariableDefinition>,
+    pub directives: Vec<Directive>,
+    // The root of the operation, named after its kind (e.g., `query`).
+    pub field: Field,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OperationKind {
+    Query,
     Mutation,
+    Subscription,
+}
+
+// `$name: ty = default`
+#[derive(Clone, Debug)]
+pub struct VariableDefinition {
+    pub name: Name,
+    pub ty: schema::Type,
+    pub default: Option<Value>,
+    pub span: Span,
+}
+
+// `fragment Name on Type { selections }`
+#[derive(Clone, Debug)]
+pub struct Fragment {
+    pub name: Name,
+    pub type_condition: Name,
+    pub selections: Vec<Selection>,
+    pub span: Span,
+}
+
+// Fragments are expanded before execution, so resolvers only see fields.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Selection {
+    Field(Field),
+    FragmentSpread(FragmentSpread),
+    InlineFragment(InlineFragment),
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Field {
     pub name: Name,
     pub alias: Option<Name>,
     pub args: Vec<(Name, Value)>,
-    pub fields: Vec<Field>,
+    pub directives: Vec<Directive>,
+    pub selections: Vec<Selection>,
+    pub span: Span,
+}
+
+// `...Name`
+#[derive(Clone, Debug, PartialEq)]
+pub struct FragmentSpread {
+    pub name: Name,
+    pub directives: Vec<Directive>,
+    pub span: Span,
+}
+
+// `... on Type { selections }`, the type condition is optional.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InlineFragment {
+    pub type_condition: Option<Name>,
+    pub directives: Vec<Directive>,
+    pub selections: Vec<Selection>,
+    pub span: Span,
+}
+
+// `@name(args)`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Directive {
+    pub name: Name,
+    pub args: Vec<(Name, Value)>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Value {
+    pub kind: ValueKind,
+    pub span: Span,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub enum Value {
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValueKind {
     Null,
+    Int(i64),
+    Float(f64),
+    Boolean(bool),
     String(String),
-    // A Name is basically any unquoted string, it might get used as an Id or whatever.
-    Name(Name),
+    // Any unquoted name other than `null`, `true`, or `false`.
+    Enum(Name),
     Array(Vec<Value>),
-    // TODO input object types
+    // An input object literal, e.g., `{a: 1, b: "x"}`.
+    Object(Vec<(Name, Value)>),
+    // `$name`, replaced by the variable's value before execution.
+    Variable(Name),
 }
 
 pub trait Root: result::Resolve {
     fn schema() -> schema::Schema;
+
+    // `field` is the single top-level field of a subscription.
+    fn subscribe(&self, _field: &Field) -> QlResult<result::Stream> {
+        Err(QlError::ExecutionError("Subscriptions are not supported".to_owned()))
+    }
 }
 
-impl Operation {
-    pub fn parse(input: &str) -> QlResult<Operation> {
+impl Document {
+    pub fn parse(input: &str) -> QlResult<Document> {
         parse_query(input)
     }
 
@@ -58,26 +147,136 @@ impl Operation {
         ::validation::validate_query(self, schema)
     }
 
-    // TODO don't need schema to execute?
-    pub fn execute<R: Root>(&self, variables: Variables, _schema: &schema::Schema, root: R) -> QlResult<result::Value> {
-        let ctxt = Context::new(variables, self.get_field().clone());
-        // TODO use ctxt
-        match *self {
-            // TODO change resolve sig to just take a single field.
-            Operation::Query(ref f) => root.resolve(&[f.clone()]),
-            _ => unimplemented!(),
+    // Picks the operation to execute. If there is only one operation then
+    // `name` may be omitted.
+    pub fn operation(&self, name: Option<&str>) -> QlResult<&Operation> {
+        match name {
+            Some(name) => self.operations
+                .iter()
+                .find(|op| op.name.as_ref().is_some_and(|n| n.0 == name))
+                .ok_or_else(|| QlError::ResolveError("operation", name.to_owned(), None)),
+            None if self.operations.len() == 1 => Ok(&self.operations[0]),
+            None => Err(QlError::ExecutionError(
+                "An operation name is required when a document has multiple operations".to_owned(),
+            )),
+        }
+    }
+
+    // Prints the document as GraphQL, which parses back to the same document.
+    pub fn print(&self, style: Style) -> String {
+        printer::print_document(self, style)
+    }
+
+    pub fn find_fragment(&self, name: &Name) -> Option<&Fragment> {
+        self.fragments.iter().find(|f| &f.name == name)
+    }
+
+    pub fn execute<R: Root>(
+        &self,
+        operation_name: Option<&str>,
+        variables: Variables,
+        schema: &schema::Schema,
+        root: R,
+    ) -> QlResult<result::Value> {
+        let operation = self.operation(operation_name)?;
+        if operation.kind == OperationKind::Subscription {
+            return Err(QlError::ExecutionError(
+                "Subscriptions must be executed with `subscribe`".to_owned(),
+            ));
+        }
+        ::validation::validate_variables(operation, &variables, schema)?;
+        let ctxt = Context::new(variables, operation, self, schema);
+        let mut query = ctxt.query().clone();
+        if operation.kind == OperationKind::Query {
+            // Introspection fields are resolved from the schema, not by the root.
+            query.selections.retain(|s| !introspection::is_introspection_field(s.assert_field()));
+        }
+        // Fields are resolved one at a time in document order, so the top-level
+        // fields of a mutation are executed serially.
+        // TODO change resolve sig to just take a single field.
+        let introspected = query.selections.len() < ctxt.query().selections.len();
+        let result = root.resolve(&[Selection::Field(query)])?;
+        if introspected {
+            introspection::complete_result(result, ctxt.query(), schema)
+        } else {
+            Ok(result)
+        }
+    }
+
+    // Like `execute`, but for subscriptions. Returns one result for each event.
+    pub fn subscribe<R: Root>(
+        &self,
+        operation_name: Option<&str>,
+        variables: Variables,
+        schema: &schema::Schema,
+        root: R,
+    ) -> QlResult<result::Stream> {
+        let operation = self.operation(operation_name)?;
+        if operation.kind != OperationKind::Subscription {
+            return Err(QlError::ExecutionError(
+                "Only subscriptions can be executed with `subscribe`".to_owned(),
+            ));
+        }
+        ::validation::validate_variables(operation, &variables, schema)?;
+        let ctxt = Context::new(variables, operation, self, schema);
+        // Validation checks there is a single root field, but it might have
+        // been skipped.
+        match ctxt.query().selections.len() {
+            1 => root.subscribe(ctxt.query().selections[0].assert_field()),
+            _ => Err(QlError::ExecutionError(
+                "A subscription must select exactly one field".to_owned(),
+            )),
         }
     }
+}
+
+impl Operation {
+    pub fn print(&self, style: Style) -> String {
+        printer::print_operation(self, style)
+    }
 
     pub fn get_field(&self) -> &Field {
+        &self.field
+    }
+}
+
+impl Value {
+    pub fn new(kind: ValueKind, span: Span) -> Value {
+        Value { kind, span }
+    }
+
+    // Returns `None` if `self` is not an object or has no field called `name`.
+    pub fn find_field(&self, name: &Name) -> Option<&Value> {
+        match self.kind {
+            ValueKind::Object(ref fields) => fields.iter().find(|f| &f.0 == name).map(|f| &f.1),
+            _ => None,
+        }
+    }
+}
+
+impl Selection {
+    pub fn assert_field(&self) -> &Field {
+        match *self {
+            Selection::Field(ref f) => f,
+            _ => panic!("Selection::assert_field called on unexpanded fragment: {:?}", self),
+        }
+    }
+
+    pub fn directives(&self) -> &[Directive] {
         match *self {
-            Operation::Query(ref f) => f,
-            Operation::Mutation => unreachable!(),
+            Selection::Field(ref f) => &f.directives,
+            Selection::FragmentSpread(ref s) => &s.directives,
+            Selection::InlineFragment(ref i) => &i.directives,
         }
     }
 }
 
 impl Field {
+    // The name of the field in the result.
+    pub fn response_key(&self) -> &Name {
+        self.alias.as_ref().unwrap_or(&self.name)
+    }
+
     pub fn find_arg(&self, name: &Name) -> Option<&Value> {
         for a in &self.args {
             if &a.0 == name {
@@ -89,17 +288,23 @@ impl Field {
     }
 }
 
+impl Directive {
+    pub fn find_arg(&self, name: &Name) -> Option<&Value> {
+        self.args.iter().find(|a| &a.0 == name).map(|a| &a.1)
+    }
+}
+
 pub trait FromValue: Sized {
     fn from(value: &Value) -> QlResult<Self>;
 }
 
 impl FromValue for String {
     fn from(value: &Value) -> QlResult<String> {
-        if let Value::String(ref s) = *value {
+        if let ValueKind::String(ref s) = value.kind {
             Ok(s.clone())
         } else {
             Err(QlError::TranslationError(
-                format!("{:?}", value),
+                format!("{:?}", value.kind),
                 "String".to_owned(),
             ))
         }
@@ -107,35 +312,83 @@ impl FromValue for String {
 }
 impl FromValue for Id {
     fn from(value: &Value) -> QlResult<Id> {
-        if let Value::Name(ref n) = *value {
-            Ok(Id(n.0.clone()))
-        } else {
-            Err(QlError::TranslationError(
-                format!("{:?}", value),
+        match value.kind {
+            ValueKind::String(ref s) => Ok(Id(s.clone())),
+            ValueKind::Int(n) => Ok(Id(n.to_string())),
+            _ => Err(QlError::TranslationError(
+                format!("{:?}", value.kind),
                 "Id".to_owned(),
-            ))
+            )),
         }
     }
 }
 impl FromValue for Name {
     fn from(value: &Value) -> QlResult<Name> {
-        if let Value::Name(ref n) = *value {
+        if let ValueKind::Enum(ref n) = value.kind {
             Ok(n.clone())
         } else {
             Err(QlError::TranslationError(
-                format!("{:?}", value),
+                format!("{:?}", value.kind),
                 "Name".to_owned(),
             ))
         }
     }
 }
+impl FromValue for i32 {
+    fn from(value: &Value) -> QlResult<i32> {
+        match value.kind {
+            ValueKind::Int(n) if i32::try_from(n).is_ok() => Ok(n as i32),
+            _ => Err(QlError::TranslationError(
+                format!("{:?}", value.kind),
+                "i32".to_owned(),
+            )),
+        }
+    }
+}
+impl FromValue for i64 {
+    fn from(value: &Value) -> QlResult<i64> {
+        if let ValueKind::Int(n) = value.kind {
+            Ok(n)
+        } else {
+            Err(QlError::TranslationError(
+                format!("{:?}", value.kind),
+                "i64".to_owned(),
+            ))
+        }
+    }
+}
+impl FromValue for f64 {
+    fn from(value: &Value) -> QlResult<f64> {
+        // Int literals are accepted where a Float is expected.
+        match value.kind {
+            ValueKind::Float(n) => Ok(n),
+            ValueKind::Int(n) => Ok(n as f64),
+            _ => Err(QlError::TranslationError(
+                format!("{:?}", value.kind),
+                "f64".to_owned(),
+            )),
+        }
+    }
+}
+impl FromValue for bool {
+    fn from(value: &Value) -> QlResult<bool> {
+        if let ValueKind::Boolean(b) = value.kind {
+            Ok(b)
+        } else {
+            Err(QlError::TranslationError(
+                format!("{:?}", value.kind),
+                "bool".to_owned(),
+            ))
+        }
+    }
+}
 impl<T: FromValue> FromValue for Vec<T> {
     fn from(value: &Value) -> QlResult<Vec<T>> {
-        if let Value::Array(ref a) = *value {
+        if let ValueKind::Array(ref a) = value.kind {
             a.iter().map(|x| T::from(x)).collect()
         } else {
             Err(QlError::TranslationError(
-                format!("{:?}", value),
+                format!("{:?}", value.kind),
                 "Array".to_owned(),
             ))
         }
@@ -143,7 +396,7 @@ impl<T: FromValue> FromValue for Vec<T> {
 }
 impl<T: FromValue> FromValue for Option<T> {
     fn from(value: &Value) -> QlResult<Option<T>> {
-        if let Value::Null = *value {
+        if let ValueKind::Null = value.kind {
             Ok(None)
         } else {
             Ok(Some(T::from(value)?))
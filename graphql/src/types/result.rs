@@ -30,6 +30,7 @@ pub enum Value {
     String(String),
     Int(i64),
     Float(f64),
+    Boolean(bool),
     Null,
 }
 
@@ -40,21 +41,64 @@ pub struct Object {
 
 // QUESTION: Is this the right place for Resolve?
 pub trait Resolve {
-    fn resolve(&self, fields: &[query::Field]) -> QlResult<Value>;
+    fn resolve(&self, fields: &[query::Selection]) -> QlResult<Value>;
+}
+
+// The results of a subscription, one for each event.
+pub type Stream = Box<dyn Iterator<Item = QlResult<Value>>>;
+
+// Implemented by the root object of subscriptions.
+pub trait Subscribe {
+    fn subscribe(&self, field: &query::Field) -> QlResult<Stream>;
+}
+
+// Resolves each event against `field`'s selections. Each result has the same
+// shape as the result of a query which selects `field`.
+pub fn resolve_stream<T: Resolve + 'static>(events: Box<dyn Iterator<Item = T>>, field: &query::Field) -> Stream {
+    let field = field.clone();
+    Box::new(events.map(move |event| {
+        let value = event.resolve(&field.selections)?;
+        let data = Value::Object(Object {
+            fields: vec![(field.response_key().clone(), value)],
+        });
+        Ok(Value::Object(Object {
+            fields: vec![(Name("data".to_owned()), data)],
+        }))
+    }))
 }
 
 impl Resolve for Id {
-    fn resolve(&self, _fields: &[query::Field]) -> QlResult<Value> {
+    fn resolve(&self, _fields: &[query::Selection]) -> QlResult<Value> {
         Ok(Value::Id(self.clone()))
     }
 }
 impl Resolve for String {
-    fn resolve(&self, _fields: &[query::Field]) -> QlResult<Value> {
+    fn resolve(&self, _fields: &[query::Selection]) -> QlResult<Value> {
         Ok(Value::String(self.clone()))
     }
 }
+impl Resolve for i32 {
+    fn resolve(&self, _fields: &[query::Selection]) -> QlResult<Value> {
+        Ok(Value::Int(*self as i64))
+    }
+}
+impl Resolve for i64 {
+    fn resolve(&self, _fields: &[query::Selection]) -> QlResult<Value> {
+        Ok(Value::Int(*self))
+    }
+}
+impl Resolve for f64 {
+    fn resolve(&self, _fields: &[query::Selection]) -> QlResult<Value> {
+        Ok(Value::Float(*self))
+    }
+}
+impl Resolve for bool {
+    fn resolve(&self, _fields: &[query::Selection]) -> QlResult<Value> {
+        Ok(Value::Boolean(*self))
+    }
+}
 impl<T: Resolve> Resolve for Option<T> {
-    fn resolve(&self, fields: &[query::Field]) -> QlResult<Value> {
+    fn resolve(&self, fields: &[query::Selection]) -> QlResult<Value> {
         match self.as_ref() {
             Some(x) => x.resolve(fields),
             None => Ok(Value::Null),
@@ -62,7 +106,7 @@ impl<T: Resolve> Resolve for Option<T> {
     }
 }
 impl<T: Resolve> Resolve for Vec<T> {
-    fn resolve(&self, fields: &[query::Field]) -> QlResult<Value> {
+    fn resolve(&self, fields: &[query::Selection]) -> QlResult<Value> {
         // TODO collect all errors not just one
         Ok(Value::Array(self.iter()
             .map(|t| t.resolve(fields))
@@ -95,6 +139,7 @@ mod display {
                 Value::String(ref s) => write!(f, "\"{}\"", s),
                 Value::Int(n) => write!(f, "{}", n),
                 Value::Float(n) => write!(f, "{}", n),
+                Value::Boolean(b) => write!(f, "{}", b),
                 Value::Null => write!(f, "null"),
             }
         }
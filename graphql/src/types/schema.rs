@@ -1,38 +1,416 @@
-use QlResult;
-use types::{query, result, Name};
+use {QlError, QlResult};
+use introspection;
+use printer;
+use visit::{self, SchemaVisitor};
+use types::{query, result, Name, Span};
+use validation::{self, Rule};
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::mem;
 
 #[derive(Clone, Debug)]
 pub struct Schema {
     pub items: HashMap<Name, Item>,
+    // Directives defined in the schema, not including the built-in directives.
+    pub directives: Vec<Directive>,
+    // Extensions of types which are not defined in this schema (yet), they are
+    // applied when the defining schema is merged in.
+    pub extensions: Vec<Extension>,
+    // The span of each item's name, for items parsed from the IDL.
+    pub spans: HashMap<Name, Span>,
 }
 
 impl Schema {
     pub fn new() -> Schema {
         Schema {
             items: HashMap::new(),
+            directives: vec![],
+            extensions: vec![],
+            spans: HashMap::new(),
         }
     }
 
-    pub fn validate(&self) -> QlResult<()> {
-        // TODO
+    // Adds the `__schema` and `__type` fields to the query type, and the types
+    // which they return. Queries which use introspection must be validated and
+    // executed against the returned schema.
+    pub fn with_introspection(mut self) -> Schema {
+        introspection::add_to_schema(&mut self);
+        self
+    }
+
+    // Prints the schema in the IDL, e.g., to dump the schema of a `query::Root`.
+    pub fn print(&self) -> String {
+        printer::print_schema(self)
+    }
+
+    // Adds the definitions and extensions in `other` to `self`. Directive
+    // definitions are not checked for conflicts here, that is left to
+    // `validate`.
+    pub fn merge(&mut self, other: Schema) -> QlResult<()> {
+        let mut errors = vec![];
+        for (name, item) in other.items {
+            let span = other.spans.get(&name).cloned().unwrap_or_else(Span::dummy);
+            if let Err(e) = self.add_item(name, item, span) {
+                errors.push(e);
+            }
+        }
+        self.directives.extend(other.directives);
+        self.extensions.extend(other.extensions);
+        errors.extend(self.apply_extensions());
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(QlError::ValidationError(errors))
+        }
+    }
+
+    // Fails if an item called `name` is already defined. `span` is the span of
+    // the item's name.
+    pub(crate) fn add_item(&mut self, name: Name, item: Item, span: Span) -> Result<(), validation::Error> {
+        if self.items.contains_key(&name) {
+            return Err(validation::Error::new(
+                Rule::UniqueTypeNames,
+                format!("There can be only one type named \"{}\".", name),
+                span,
+            ));
+        }
+        self.spans.insert(name.clone(), span);
+        self.items.insert(name, item);
         Ok(())
     }
+
+    // Applies any extensions of items which are defined, the rest are kept.
+    pub(crate) fn apply_extensions(&mut self) -> Vec<validation::Error> {
+        let mut errors = vec![];
+        for ext in mem::take(&mut self.extensions) {
+            match self.items.get_mut(&ext.name) {
+                Some(item) => errors.extend(item.extend(&ext.name, ext.item, ext.span)),
+                None => self.extensions.push(ext),
+            }
+        }
+        errors
+    }
+
+    // Duplicate type names and extensions of the wrong kind of type are
+    // reported when items are added, by the parser or `merge`.
+    pub fn validate(&self) -> QlResult<()> {
+        let mut errors = vec![];
+
+        for ext in &self.extensions {
+            errors.push(ext.not_found());
+        }
+
+        let mut validator = Validator {
+            schema: self,
+            errors,
+            builtins: builtin_directives(),
+            directive_names: HashSet::new(),
+            member_names: HashSet::new(),
+        };
+        visit::walk_schema(&mut validator, self);
+
+        if validator.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(QlError::ValidationError(validator.errors))
+        }
+    }
+
+    // True if a value of type `sub` is always a valid value of type `sup`,
+    // e.g., `Human!` is a subtype of `Character` if `Human` implements
+    // `Character`.
+    pub fn is_subtype(&self, sub: &Type, sup: &Type) -> bool {
+        if sub.nullable && !sup.nullable {
+            return false;
+        }
+        match (&sub.kind, &sup.kind) {
+            (TypeKind::Name(a), TypeKind::Name(b)) => {
+                a == b || self.implements(a, b) || match self.items.get(b) {
+                    Some(Item::Union(u)) => u.members.contains(a),
+                    _ => false,
+                }
+            }
+            (TypeKind::Array(a), TypeKind::Array(b)) => self.is_subtype(a, b),
+            (&TypeKind::String, &TypeKind::String)
+            | (&TypeKind::Id, &TypeKind::Id)
+            | (&TypeKind::Int, &TypeKind::Int)
+            | (&TypeKind::Float, &TypeKind::Float)
+            | (&TypeKind::Boolean, &TypeKind::Boolean) => true,
+            _ => false,
+        }
+    }
+
+    // True if `ty` implements `interface`, either directly or because one of
+    // the interfaces it implements does.
+    pub fn implements(&self, ty: &Name, interface: &Name) -> bool {
+        let mut seen = HashSet::new();
+        let mut stack = vec![ty];
+        while let Some(n) = stack.pop() {
+            if !seen.insert(n) {
+                continue;
+            }
+            if let Some(item) = self.items.get(n) {
+                for i in item.implements() {
+                    if i == interface {
+                        return true;
+                    }
+                    stack.push(i);
+                }
+            }
+        }
+        false
+    }
+
+    // The object types which a value of a union or interface type may have at
+    // runtime: the members of the union, or the objects which implement the
+    // interface (in name order). Empty for other types.
+    pub fn possible_types(&self, name: &Name) -> Vec<&Name> {
+        match self.items.get(name) {
+            Some(Item::Union(u)) => u.members.iter().collect(),
+            Some(&Item::Interface(_)) => {
+                let mut result: Vec<_> = self.items
+                    .iter()
+                    .filter(|&(n, item)| match *item {
+                        Item::Object(_) => self.implements(n, name),
+                        _ => false,
+                    })
+                    .map(|(n, _)| n)
+                    .collect();
+                result.sort_by(|a, b| a.0.cmp(&b.0));
+                result
+            }
+            _ => vec![],
+        }
+    }
+}
+
+struct Validator<'a> {
+    schema: &'a Schema,
+    errors: Vec<validation::Error>,
+    builtins: Vec<Directive>,
+    // The names of directives defined so far.
+    directive_names: HashSet<&'a Name>,
+    // The names of the fields or enum values of the current item.
+    member_names: HashSet<&'a Name>,
+}
+
+impl<'a> Validator<'a> {
+    fn item_span(&self, name: &Name) -> Span {
+        self.schema.spans.get(name).cloned().unwrap_or_else(Span::dummy)
+    }
+
+    // `item` must have each field of each interface it implements, with the
+    // same arguments and a type which is the same or more specific.
+    fn check_implements(&mut self, name: &'a Name, item: &'a Item) {
+        for i in item.implements() {
+            let interface = match self.schema.items.get(i) {
+                Some(Item::Interface(interface)) => interface,
+                Some(_) => {
+                    let msg = format!("Type \"{}\" cannot implement \"{}\" because it is not an interface.", name, i);
+                    let span = self.item_span(name);
+                    self.errors.push(validation::Error::new(Rule::ConsistentFieldTypes, msg, span));
+                    continue;
+                }
+                None => {
+                    let span = self.item_span(name);
+                    self.errors.push(validation::Error::new(Rule::KnownTypeNames, format!("Unknown type \"{}\".", i), span));
+                    continue;
+                }
+            };
+
+            for expected in &interface.fields {
+                let field = item.fields().iter().find(|f| f.name == expected.name);
+                let field = match field {
+                    Some(f) => f,
+                    None => {
+                        let msg = format!(
+                            "Interface field \"{}.{}\" expected but \"{}\" does not provide it.",
+                            i, expected.name, name
+                        );
+                        let span = self.item_span(name);
+                        self.errors.push(validation::Error::new(Rule::ConsistentFieldTypes, msg, span));
+                        continue;
+                    }
+                };
+
+                if !self.schema.is_subtype(&field.ty, &expected.ty) {
+                    let msg = format!(
+                        "Interface field \"{}.{}\" expects type \"{}\" but \"{}.{}\" is type \"{}\".",
+                        i, expected.name, expected.ty, name, field.name, field.ty
+                    );
+                    self.errors.push(validation::Error::new(Rule::ConsistentFieldTypes, msg, field.span));
+                }
+                for arg in &expected.args {
+                    if !field.args.iter().any(|a| a.name == arg.name && a.ty.same(&arg.ty)) {
+                        let msg = format!(
+                            "Interface field argument \"{}.{}({}:)\" of type \"{}\" expected but \"{}.{}\" does not provide it.",
+                            i, expected.name, arg.name, arg.ty, name, field.name
+                        );
+                        self.errors.push(validation::Error::new(Rule::ConsistentFieldTypes, msg, field.span));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<'a> SchemaVisitor<'a> for Validator<'a> {
+    fn visit_directive_definition(&mut self, d: &'a Directive) {
+        let msg = if self.builtins.iter().any(|b| b.name == d.name) {
+            format!("Directive \"@{}\" already exists in the schema. It cannot be redefined.", d.name)
+        } else if !self.directive_names.insert(&d.name) {
+            format!("There can be only one directive named \"@{}\".", d.name)
+        } else {
+            return;
+        };
+        self.errors.push(validation::Error::new(Rule::UniqueDirectiveNames, msg, d.span));
+    }
+
+    fn enter_item(&mut self, name: &'a Name, item: &'a Item) {
+        self.member_names.clear();
+        self.check_implements(name, item);
+
+        if let Item::Union(ref u) = *item {
+            for m in &u.members {
+                let error = match self.schema.items.get(m) {
+                    Some(&Item::Object(_)) => continue,
+                    Some(_) => validation::Error::new(
+                        Rule::UnionMemberTypes,
+                        format!(
+                            "Union type \"{}\" can only include Object types, it cannot include \"{}\".",
+                            name, m
+                        ),
+                        u.span,
+                    ),
+                    None => validation::Error::new(Rule::KnownTypeNames, format!("Unknown type \"{}\".", m), u.span),
+                };
+                self.errors.push(error);
+            }
+        }
+    }
+
+    fn enter_field(&mut self, parent: &'a Name, field: &'a Field) {
+        if !self.member_names.insert(&field.name) {
+            self.errors.push(validation::Error::new(
+                Rule::UniqueFieldNames,
+                format!("Field \"{}.{}\" can only be defined once.", parent, field.name),
+                field.span,
+            ));
+        }
+    }
+
+    fn visit_variant(&mut self, parent: &'a Name, variant: &'a Variant) {
+        if !self.member_names.insert(&variant.name) {
+            let span = self.item_span(parent);
+            self.errors.push(validation::Error::new(
+                Rule::UniqueEnumValueNames,
+                format!("Enum value \"{}.{}\" can only be defined once.", parent, variant.name),
+                span,
+            ));
+        }
+    }
+
+    fn visit_argument(&mut self, arg: &'a Argument) {
+        if let Some(ref default) = arg.default {
+            self.errors.extend(validation::validate_default_value(default, &arg.ty, self.schema));
+        }
+    }
+
+    fn visit_directives(&mut self, directives: &'a [query::Directive], location: DirectiveLocation) {
+        self.errors.extend(validation::validate_schema_directives(directives, location, self.schema));
+    }
+}
+
+// Merges several schema documents, e.g., one per file, into a single schema.
+// Every extension must extend a type defined in one of the documents.
+pub fn merge_schemas<I: IntoIterator<Item = Schema>>(schemas: I) -> QlResult<Schema> {
+    let mut result = Schema::new();
+    let mut errors = vec![];
+    for s in schemas {
+        if let Err(QlError::ValidationError(errs)) = result.merge(s) {
+            errors.extend(errs);
+        }
+    }
+    for ext in &result.extensions {
+        errors.push(ext.not_found());
+    }
+
+    if errors.is_empty() {
+        Ok(result)
+    } else {
+        Err(QlError::ValidationError(errors))
+    }
 }
 
-// TODO should include mutation if provided by user (and maybe query should be optional too?)
-pub fn schema_type() -> Item {
+// QUESTION maybe query should be optional too?
+pub fn schema_type(has_mutation: bool, has_subscription: bool) -> Item {
+    let mut fields = vec![
+        Field::fun(Name("query".to_owned()), vec![], Type::name("Query")),
+    ];
+    if has_mutation {
+        fields.push(Field::fun(Name("mutation".to_owned()), vec![], Type::name("Mutation")));
+    }
+    if has_subscription {
+        fields.push(Field::fun(Name("subscription".to_owned()), vec![], Type::name("Subscription")));
+    }
     Item::Object(Object {
         implements: vec![],
-        fields: vec![
-            Field::fun(Name("query".to_owned()), vec![], Type::name("Query")),
-        ],
+        fields,
+        description: None,
+        directives: vec![],
     })
 }
 
 pub const SCHEMA_NAME: &'static str = "schema";
 
+pub const DEFAULT_DEPRECATION_REASON: &str = "No longer supported";
+
+// Directives which are always available in queries and schemas.
+pub fn builtin_directives() -> Vec<Directive> {
+    let locations = vec![
+        DirectiveLocation::Field,
+        DirectiveLocation::FragmentSpread,
+        DirectiveLocation::InlineFragment,
+    ];
+    let if_arg = Argument::new(Name("if".to_owned()), Type::non_null(TypeKind::Boolean));
+    let reason_arg = Argument {
+        default: Some(query::Value::new(
+            query::ValueKind::String(DEFAULT_DEPRECATION_REASON.to_owned()),
+            Span::dummy(),
+        )),
+        ..Argument::new(
+            Name("reason".to_owned()),
+            Type {
+                nullable: true,
+                ..Type::non_null(TypeKind::String)
+            },
+        )
+    };
+    vec![
+        Directive::new(Name("skip".to_owned()), vec![if_arg.clone()], locations.clone()),
+        Directive::new(Name("include".to_owned()), vec![if_arg], locations),
+        Directive::new(
+            Name("deprecated".to_owned()),
+            vec![reason_arg],
+            vec![DirectiveLocation::FieldDefinition, DirectiveLocation::EnumValue],
+        ),
+    ]
+}
+
+// `Some(reason)` if `directives` includes `@deprecated`.
+pub fn deprecation_reason(directives: &[query::Directive]) -> Option<&str> {
+    let d = directives.iter().find(|d| d.name.0 == "deprecated")?;
+    match d.find_arg(&Name("reason".to_owned())) {
+        Some(&query::Value {
+            kind: query::ValueKind::String(ref s),
+            ..
+        }) => Some(s),
+        _ => Some(DEFAULT_DEPRECATION_REASON),
+    }
+}
+
 // QUESTION Reflect and Resolve should probably be elsewhere
 pub trait Reflect {
     const NAME: &'static str;
@@ -46,12 +424,22 @@ pub trait ResolveObject: Reflect + result::Resolve {
 
 pub trait ResolveEnum: Reflect + result::Resolve {}
 
+// Implemented by the Rust type for a custom scalar, e.g., `scalar DateTime`.
+pub trait ResolveScalar: Sized {
+    fn to_value(&self) -> QlResult<result::Value>;
+    // Should return an error if `value` is not a valid value for the scalar.
+    fn from_value(value: &query::Value) -> QlResult<Self>;
+}
+
 #[derive(Clone, Debug)]
 pub enum Item {
     Schema(Interface),
     Object(Object),
     Interface(Interface),
     Enum(Enum),
+    InputObject(InputObject),
+    Union(Union),
+    Scalar(Scalar),
 }
 
 impl Item {
@@ -59,37 +447,233 @@ impl Item {
         match *self {
             Item::Object(ref obj) => &obj.fields,
             Item::Schema(ref i) | Item::Interface(ref i) => &i.fields,
-            Item::Enum(_) => &[],
+            // Input objects have fields, but they can't be selected in a query.
+            // Fields of a union's members may only be selected using fragments.
+            Item::Enum(_) | Item::InputObject(_) | Item::Union(_) | Item::Scalar(_) => &[],
+        }
+    }
+
+    // The interfaces which this item directly implements.
+    pub fn implements(&self) -> &[Name] {
+        match *self {
+            Item::Object(ref obj) => &obj.implements,
+            Item::Interface(ref i) => &i.implements,
+            _ => &[],
+        }
+    }
+
+    pub fn directives(&self) -> &[query::Directive] {
+        match *self {
+            Item::Schema(ref i) | Item::Interface(ref i) => &i.directives,
+            Item::Object(ref obj) => &obj.directives,
+            Item::Enum(ref e) => &e.directives,
+            Item::InputObject(ref i) => &i.directives,
+            Item::Union(ref u) => &u.directives,
+            Item::Scalar(ref s) => &s.directives,
         }
     }
 
+    // Where the item's directives appear.
+    pub fn directive_location(&self) -> DirectiveLocation {
+        match *self {
+            Item::Schema(_) => DirectiveLocation::Schema,
+            Item::Object(_) => DirectiveLocation::Object,
+            Item::Interface(_) => DirectiveLocation::Interface,
+            Item::Enum(_) => DirectiveLocation::Enum,
+            Item::InputObject(_) => DirectiveLocation::InputObject,
+            Item::Union(_) => DirectiveLocation::Union,
+            Item::Scalar(_) => DirectiveLocation::Scalar,
+        }
+    }
+
+    // The description written before the item in the IDL, if any.
+    pub fn description(&self) -> Option<&str> {
+        let description = match *self {
+            Item::Schema(ref i) | Item::Interface(ref i) => &i.description,
+            Item::Object(ref obj) => &obj.description,
+            Item::Enum(ref e) => &e.description,
+            Item::InputObject(ref i) => &i.description,
+            Item::Union(ref u) => &u.description,
+            Item::Scalar(ref s) => &s.description,
+        };
+        description.as_ref().map(|d| &**d)
+    }
+
     pub fn assert_field(&self, name: Name) -> &Field {
         self.fields().iter().find(|f| f.name == name).expect("Missing field")
     }
+
+    // Adds the fields, variants, interfaces, and directives of `ext` to this
+    // item, which is called `name`. `span` is the span of the extension.
+    fn extend(&mut self, name: &Name, ext: Item, span: Span) -> Vec<validation::Error> {
+        let mut errors = vec![];
+        match (self, ext) {
+            (&mut Item::Schema(ref mut i), Item::Schema(ext))
+            | (&mut Item::Interface(ref mut i), Item::Interface(ext)) => {
+                extend_fields(name, &mut i.fields, ext.fields, &mut errors);
+                extend_names(&mut i.implements, ext.implements);
+                i.directives.extend(ext.directives);
+            }
+            (&mut Item::Object(ref mut obj), Item::Object(ext)) => {
+                extend_fields(name, &mut obj.fields, ext.fields, &mut errors);
+                extend_names(&mut obj.implements, ext.implements);
+                obj.directives.extend(ext.directives);
+            }
+            (&mut Item::Enum(ref mut e), Item::Enum(ext)) => {
+                for v in ext.variants {
+                    if e.variants.iter().any(|ev| ev.name == v.name) {
+                        errors.push(validation::Error::new(
+                            Rule::UniqueEnumValueNames,
+                            format!("Enum value \"{}.{}\" already exists.", name, v.name),
+                            span,
+                        ));
+                    } else {
+                        e.variants.push(v);
+                    }
+                }
+                e.directives.extend(ext.directives);
+            }
+            _ => errors.push(validation::Error::new(
+                Rule::PossibleTypeExtensions,
+                format!("Cannot extend type \"{}\" with an extension of a different kind.", name),
+                span,
+            )),
+        }
+        errors
+    }
+}
+
+// An extended field must not already exist. Redefining a field with a
+// different type is reported as a mismatch, since that is usually a mistake in
+// one of the definitions rather than a copy.
+fn extend_fields(item: &Name, fields: &mut Vec<Field>, new_fields: Vec<Field>, errors: &mut Vec<validation::Error>) {
+    for f in new_fields {
+        let conflict = match fields.iter().find(|old| old.name == f.name) {
+            Some(old) if old.ty.same(&f.ty) => Some((
+                Rule::UniqueFieldNames,
+                format!("Field \"{}.{}\" already exists.", item, f.name),
+            )),
+            Some(old) => Some((
+                Rule::ConsistentFieldTypes,
+                format!(
+                    "Field \"{}.{}\" already has type \"{}\", it cannot be redefined as \"{}\".",
+                    item, f.name, old.ty, f.ty
+                ),
+            )),
+            None => None,
+        };
+        match conflict {
+            Some((rule, msg)) => errors.push(validation::Error::new(rule, msg, f.span)),
+            None => fields.push(f),
+        }
+    }
+}
+
+fn extend_names(names: &mut Vec<Name>, new_names: Vec<Name>) {
+    for n in new_names {
+        if !names.contains(&n) {
+            names.push(n);
+        }
+    }
+}
+
+// `extend type Name ...`, `item` only contains what is added to the type.
+#[derive(Clone, Debug)]
+pub struct Extension {
+    pub name: Name,
+    pub item: Item,
+    pub span: Span,
+}
+
+impl Extension {
+    // The error for an extension whose type is never defined.
+    fn not_found(&self) -> validation::Error {
+        validation::Error::new(
+            Rule::PossibleTypeExtensions,
+            format!("Cannot extend type \"{}\" because it is not defined.", self.name),
+            self.span,
+        )
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct Object {
     pub implements: Vec<Name>,
     pub fields: Vec<Field>,
+    pub description: Option<String>,
+    pub directives: Vec<query::Directive>,
 }
 
 #[derive(Clone, Debug)]
 pub struct Interface {
+    // Always empty for the `schema` item.
+    pub implements: Vec<Name>,
     pub fields: Vec<Field>,
+    pub description: Option<String>,
+    pub directives: Vec<query::Directive>,
 }
 
 #[derive(Clone, Debug)]
 pub struct Enum {
-    pub variants: Vec<Name>,
+    pub variants: Vec<Variant>,
+    pub description: Option<String>,
+    pub directives: Vec<query::Directive>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Variant {
+    pub name: Name,
+    pub description: Option<String>,
+    pub directives: Vec<query::Directive>,
+}
+
+impl Variant {
+    pub fn new(name: Name) -> Variant {
+        Variant {
+            name,
+            description: None,
+            directives: vec![],
+        }
+    }
+
+    pub fn deprecation_reason(&self) -> Option<&str> {
+        deprecation_reason(&self.directives)
+    }
+}
+
+// `scalar Name`, values are checked by the scalar's `ResolveScalar` impl.
+#[derive(Clone, Debug)]
+pub struct Scalar {
+    pub description: Option<String>,
+    pub directives: Vec<query::Directive>,
+}
+
+// `union Name = A | B`, every member must be an object type.
+#[derive(Clone, Debug)]
+pub struct Union {
+    pub members: Vec<Name>,
+    pub span: Span,
+    pub description: Option<String>,
+    pub directives: Vec<query::Directive>,
+}
+
+// Fields of an input object never have arguments.
+#[derive(Clone, Debug)]
+pub struct InputObject {
+    pub fields: Vec<Field>,
+    pub description: Option<String>,
+    pub directives: Vec<query::Directive>,
 }
 
 #[derive(Clone, Debug)]
 pub struct Field {
     pub name: Name,
     // QUESTION: Do we need to distinguish between no arg list and an empty arg list?
-    pub args: Vec<(Name, Type)>,
+    pub args: Vec<Argument>,
     pub ty: Type,
+    pub span: Span,
+    pub description: Option<String>,
+    pub directives: Vec<query::Directive>,
 }
 
 impl Field {
@@ -98,24 +682,164 @@ impl Field {
             name,
             args: vec![],
             ty,
+            span: Span::dummy(),
+            description: None,
+            directives: vec![],
+        }
+    }
+
+    pub fn fun(name: Name, args: Vec<Argument>, ty: Type) -> Field {
+        Field {
+            name,
+            args,
+            ty,
+            span: Span::dummy(),
+            description: None,
+            directives: vec![],
+        }
+    }
+
+    pub fn deprecation_reason(&self) -> Option<&str> {
+        deprecation_reason(&self.directives)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Argument {
+    pub name: Name,
+    pub ty: Type,
+    // Used when the argument is not supplied.
+    pub default: Option<query::Value>,
+    pub description: Option<String>,
+    pub directives: Vec<query::Directive>,
+}
+
+impl Argument {
+    pub fn new(name: Name, ty: Type) -> Argument {
+        Argument {
+            name,
+            ty,
+            default: None,
+            description: None,
+            directives: vec![],
+        }
+    }
+}
+
+// `directive @name(args) on LOCATION | ...`
+#[derive(Clone, Debug)]
+pub struct Directive {
+    pub name: Name,
+    pub args: Vec<Argument>,
+    // Where the directive may be used in a query or schema.
+    pub locations: Vec<DirectiveLocation>,
+    pub description: Option<String>,
+    pub span: Span,
+}
+
+impl Directive {
+    pub fn new(name: Name, args: Vec<Argument>, locations: Vec<DirectiveLocation>) -> Directive {
+        Directive {
+            name,
+            args,
+            locations,
+            description: None,
+            span: Span::dummy(),
         }
     }
+}
 
-    pub fn fun(name: Name, args: Vec<(Name, Type)>, ty: Type) -> Field {
-        Field { name, args, ty }
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DirectiveLocation {
+    // Queries.
+    Query,
+    Mutation,
+    Subscription,
+    Field,
+    FragmentDefinition,
+    FragmentSpread,
+    InlineFragment,
+    // Schemas.
+    Schema,
+    Scalar,
+    Object,
+    FieldDefinition,
+    ArgumentDefinition,
+    Interface,
+    Union,
+    Enum,
+    EnumValue,
+    InputObject,
+    InputFieldDefinition,
+}
+
+impl DirectiveLocation {
+    pub fn from_name(name: &str) -> Option<DirectiveLocation> {
+        DIRECTIVE_LOCATIONS.iter().find(|l| l.0 == name).map(|l| l.1)
+    }
+
+    // The name used in the schema language, e.g., `FIELD_DEFINITION`.
+    pub fn name(&self) -> &'static str {
+        DIRECTIVE_LOCATIONS.iter().find(|l| l.1 == *self).unwrap().0
     }
 }
 
+const DIRECTIVE_LOCATIONS: [(&str, DirectiveLocation); 18] = [
+    ("QUERY", DirectiveLocation::Query),
+    ("MUTATION", DirectiveLocation::Mutation),
+    ("SUBSCRIPTION", DirectiveLocation::Subscription),
+    ("FIELD", DirectiveLocation::Field),
+    ("FRAGMENT_DEFINITION", DirectiveLocation::FragmentDefinition),
+    ("FRAGMENT_SPREAD", DirectiveLocation::FragmentSpread),
+    ("INLINE_FRAGMENT", DirectiveLocation::InlineFragment),
+    ("SCHEMA", DirectiveLocation::Schema),
+    ("SCALAR", DirectiveLocation::Scalar),
+    ("OBJECT", DirectiveLocation::Object),
+    ("FIELD_DEFINITION", DirectiveLocation::FieldDefinition),
+    ("ARGUMENT_DEFINITION", DirectiveLocation::ArgumentDefinition),
+    ("INTERFACE", DirectiveLocation::Interface),
+    ("UNION", DirectiveLocation::Union),
+    ("ENUM", DirectiveLocation::Enum),
+    ("ENUM_VALUE", DirectiveLocation::EnumValue),
+    ("INPUT_OBJECT", DirectiveLocation::InputObject),
+    ("INPUT_FIELD_DEFINITION", DirectiveLocation::InputFieldDefinition),
+];
+
 #[derive(Clone, Debug)]
 pub struct Type {
     pub kind: TypeKind,
     pub nullable: bool,
+    pub span: Span,
+}
+
+// As written in the IDL, e.g., `[String!]`.
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            TypeKind::String => write!(f, "String")?,
+            TypeKind::Id => write!(f, "ID")?,
+            TypeKind::Int => write!(f, "Int")?,
+            TypeKind::Float => write!(f, "Float")?,
+            TypeKind::Boolean => write!(f, "Boolean")?,
+            TypeKind::Name(ref n) => write!(f, "{}", n)?,
+            TypeKind::Array(ref inner) => write!(f, "[{}]", inner)?,
+        }
+        if !self.nullable {
+            write!(f, "!")?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Clone, Debug)]
 pub enum TypeKind {
     String,
     Id,
+    // i32
+    Int,
+    // f64
+    Float,
+    Boolean,
     Name(Name),
     Array(Box<Type>),
 }
@@ -125,6 +849,7 @@ impl Type {
         Type {
             kind,
             nullable: false,
+            span: Span::dummy(),
         }
     }
 
@@ -132,6 +857,7 @@ impl Type {
         Type {
             kind: TypeKind::Array(Box::new(ty)),
             nullable: true,
+            span: Span::dummy(),
         }
     }
 
@@ -139,6 +865,7 @@ impl Type {
         Type {
             kind: TypeKind::Name(Name(s.to_owned())),
             nullable: true,
+            span: Span::dummy(),
         }
     }
 
@@ -149,10 +876,282 @@ impl Type {
         }
     }
 
+    // The name of the type, looking through any arrays.
+    pub fn named_type(&self) -> Option<&Name> {
+        match self.kind {
+            TypeKind::Name(ref n) => Some(n),
+            TypeKind::Array(ref inner) => inner.named_type(),
+            _ => None,
+        }
+    }
+
     pub fn as_name_null(&self) -> Option<&Name> {
         match self.kind {
             TypeKind::Name(ref n) => Some(n),
             _ => None,
         }
     }
+
+    // True if both types are the same, ignoring spans.
+    pub fn same(&self, other: &Type) -> bool {
+        if self.nullable != other.nullable {
+            return false;
+        }
+        match (&self.kind, &other.kind) {
+            (&TypeKind::String, &TypeKind::String)
+            | (&TypeKind::Id, &TypeKind::Id)
+            | (&TypeKind::Int, &TypeKind::Int)
+            | (&TypeKind::Float, &TypeKind::Float)
+            | (&TypeKind::Boolean, &TypeKind::Boolean) => true,
+            (TypeKind::Name(a), TypeKind::Name(b)) => a == b,
+            (TypeKind::Array(a), TypeKind::Array(b)) => a.same(b),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::parse_idl::parse_schema;
+
+    #[test]
+    fn test_merge_schemas() {
+        let characters = parse_schema(
+            "interface Character { id: ID! }
+            type Human implements Character { id: ID!, name: String }
+            extend type Query { human(id: ID!): Human }",
+        ).unwrap();
+        let droids = parse_schema(
+            "type Droid implements Character { id: ID! }
+            extend type Query { droid(id: ID!): Droid }
+            extend type Human { friends: [Character] }",
+        ).unwrap();
+        let root = parse_schema("schema { query: Query } type Query { hero: Character }").unwrap();
+
+        let schema = merge_schemas(vec![characters, droids, root]).unwrap();
+        schema.validate().unwrap();
+        assert_eq!(schema.items.len(), 5);
+        assert!(schema.extensions.is_empty());
+        let query = &schema.items[&Name("Query".to_owned())];
+        let mut names: Vec<_> = query.fields().iter().map(|f| &*f.name.0).collect();
+        names.sort();
+        assert_eq!(names, vec!["droid", "hero", "human"]);
+        assert_eq!(schema.items[&Name("Human".to_owned())].fields().len(), 3);
+
+        let base = parse_schema("type Query { hero: Character } enum Episode { JEDI }").unwrap();
+        let conflicts = parse_schema(
+            "type Query { droid: Droid }
+            extend type Episode { hero: String }
+            extend type Wookiee { name: String }",
+        ).unwrap();
+        let fields = parse_schema("extend type Query { hero: Character, droid: Droid } extend enum Episode { JEDI }")
+            .unwrap();
+        let mismatch = parse_schema("extend type Query { hero: [Character] }").unwrap();
+        match merge_schemas(vec![base, conflicts, fields, mismatch]) {
+            Err(QlError::ValidationError(ref errs)) => {
+                let mut codes: Vec<_> = errs.iter().map(|e| e.rule.code()).collect();
+                codes.sort();
+                assert_eq!(
+                    codes,
+                    vec![
+                        "CONSISTENT_FIELD_TYPES",
+                        "POSSIBLE_TYPE_EXTENSIONS",
+                        "POSSIBLE_TYPE_EXTENSIONS",
+                        "UNIQUE_ENUM_VALUE_NAMES",
+                        "UNIQUE_FIELD_NAMES",
+                        "UNIQUE_TYPE_NAMES",
+                    ]
+                );
+                // Conflicts are reported where they are in the later document.
+                let duplicate = errs.iter().find(|e| e.rule == Rule::UniqueTypeNames).unwrap();
+                assert_eq!((duplicate.span.start.row.0, duplicate.span.start.col.0), (0, 5));
+                assert_eq!(duplicate.span.hi - duplicate.span.lo, "Query".len());
+
+                let mismatch = errs.iter().find(|e| e.rule == Rule::ConsistentFieldTypes).unwrap();
+                assert_eq!(
+                    mismatch.message,
+                    "Field \"Query.hero\" already has type \"Character\", it cannot be redefined as \"[Character]\"."
+                );
+            }
+            r => panic!("Expected validation errors, found {:?}", r),
+        }
+
+        // A lone document can't extend a type it doesn't define.
+        let schema = parse_schema("extend type Query { hero: String }").unwrap();
+        assert!(schema.validate().is_err());
+    }
+
+    #[test]
+    fn test_possible_types() {
+        let schema = parse_schema(
+            "interface Node { id: ID! }
+            interface Entity implements Node { id: ID!, name: String }
+            type Ship implements Entity & Node { id: ID!, name: String }
+            type Planet implements Node { id: ID! }
+            union Search = Ship | Planet",
+        ).unwrap();
+        let names = |ty: &str| -> Vec<&str> {
+            schema.possible_types(&Name(ty.to_owned())).iter().map(|n| &*n.0).collect()
+        };
+        assert_eq!(names("Node"), vec!["Planet", "Ship"]);
+        assert_eq!(names("Entity"), vec!["Ship"]);
+        assert_eq!(names("Search"), vec!["Ship", "Planet"]);
+        assert_eq!(names("Ship"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_validate_fields() {
+        let schema = parse_schema(
+            "interface Node { id: ID! }
+            interface Character implements Node { id: ID!, friends(first: Int): [Character] }
+            type Human implements Character & Node { id: ID!, friends(first: Int): [Human!]! }
+            type Droid implements Character { id: ID, name: String, name: String }
+            type Ship implements Node & Human { id: String! }
+            enum Episode { JEDI, JEDI }
+            input Review { stars: Int, stars: Int }",
+        ).unwrap();
+        match schema.validate() {
+            Err(QlError::ValidationError(ref errs)) => {
+                let mut msgs: Vec<_> = errs.iter().map(|e| (e.rule.code(), &*e.message)).collect();
+                msgs.sort();
+                assert_eq!(
+                    msgs,
+                    vec![
+                        (
+                            "CONSISTENT_FIELD_TYPES",
+                            "Interface field \"Character.friends\" expected but \"Droid\" does not provide it.",
+                        ),
+                        (
+                            "CONSISTENT_FIELD_TYPES",
+                            "Interface field \"Character.id\" expects type \"ID!\" but \"Droid.id\" is type \"ID\".",
+                        ),
+                        (
+                            "CONSISTENT_FIELD_TYPES",
+                            "Interface field \"Node.id\" expects type \"ID!\" but \"Ship.id\" is type \"String!\".",
+                        ),
+                        (
+                            "CONSISTENT_FIELD_TYPES",
+                            "Type \"Ship\" cannot implement \"Human\" because it is not an interface.",
+                        ),
+                        ("UNIQUE_ENUM_VALUE_NAMES", "Enum value \"Episode.JEDI\" can only be defined once."),
+                        ("UNIQUE_FIELD_NAMES", "Field \"Droid.name\" can only be defined once."),
+                        ("UNIQUE_FIELD_NAMES", "Field \"Review.stars\" can only be defined once."),
+                    ]
+                );
+            }
+            r => panic!("Expected validation errors, found {:?}", r),
+        }
+
+        // Interface field arguments must be provided.
+        let schema = parse_schema(
+            "interface Character { friends(first: Int): [Character] }
+            type Human implements Character { friends: [Character] }",
+        ).unwrap();
+        match schema.validate() {
+            Err(QlError::ValidationError(ref errs)) => assert_eq!(
+                errs[0].message,
+                "Interface field argument \"Character.friends(first:)\" of type \"Int\" expected but \"Human.friends\" does not provide it."
+            ),
+            r => panic!("Expected validation errors, found {:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_validate_unions() {
+        let schema = parse_schema(
+            "interface Character { name: String }
+            type Human { name: String }
+            type Droid { name: String }
+            union Ok = Human | Droid
+            union NotObject = Human | Character
+            union NotFound = Human | Wookiee",
+        ).unwrap();
+        match schema.validate() {
+            Err(QlError::ValidationError(ref errs)) => {
+                let mut msgs: Vec<_> = errs.iter().map(|e| &*e.message).collect();
+                msgs.sort();
+                assert_eq!(
+                    msgs,
+                    vec![
+                        "Union type \"NotObject\" can only include Object types, it cannot include \"Character\".",
+                        "Unknown type \"Wookiee\".",
+                    ]
+                );
+            }
+            r => panic!("Expected validation errors, found {:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_validate_default_args() {
+        let schema = parse_schema(
+            "enum Episode { NEWHOPE, EMPIRE, JEDI }
+            type Query {
+                hero(episode: Episode = JEDI): String,
+                count(n: Int = 10, m: Float = 1): Int,
+                bad(n: Int = \"ten\"): Int,
+                var(n: Int = $n): Int,
+                notNull(s: String! = null): Int,
+            }",
+        ).unwrap();
+        match schema.validate() {
+            Err(QlError::ValidationError(ref errs)) => {
+                let mut codes: Vec<_> = errs.iter().map(|e| e.rule.code()).collect();
+                codes.sort();
+                assert_eq!(
+                    codes,
+                    vec!["VALUES_OF_CORRECT_TYPE", "VALUES_OF_CORRECT_TYPE", "VALUES_OF_CORRECT_TYPE"]
+                );
+            }
+            r => panic!("Expected validation errors, found {:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_validate_directives() {
+        let schema = parse_schema(
+            "directive @cached(seconds: Int!) on FIELD_DEFINITION
+            type Query {
+                a: String @deprecated,
+                b: String @deprecated(reason: \"Use `a`.\") @cached(seconds: 10),
+            }
+            enum Episode { NEWHOPE @deprecated }",
+        ).unwrap();
+        schema.validate().unwrap();
+
+        let schema = parse_schema(
+            "directive @cached(seconds: Int!) on FIELD_DEFINITION
+            directive @cached on OBJECT
+            directive @skip on FIELD
+            type Query @cached {
+                a: String @unknown,
+                b: String @cached,
+                c: String @cached(seconds: \"ten\"),
+                d(x: Int @deprecated): String @deprecated(reason: 42) @deprecated,
+            }",
+        ).unwrap();
+        match schema.validate() {
+            Err(QlError::ValidationError(ref errs)) => {
+                let mut codes: Vec<_> = errs.iter().map(|e| e.rule.code()).collect();
+                codes.sort();
+                assert_eq!(
+                    codes,
+                    vec![
+                        "KNOWN_DIRECTIVES",
+                        "KNOWN_DIRECTIVES",
+                        "KNOWN_DIRECTIVES",
+                        "PROVIDED_REQUIRED_ARGUMENTS",
+                        "PROVIDED_REQUIRED_ARGUMENTS",
+                        "UNIQUE_DIRECTIVES_PER_LOCATION",
+                        "UNIQUE_DIRECTIVE_NAMES",
+                        "UNIQUE_DIRECTIVE_NAMES",
+                        "VALUES_OF_CORRECT_TYPE",
+                        "VALUES_OF_CORRECT_TYPE",
+                    ]
+                );
+            }
+            r => panic!("Expected validation errors, found {:?}", r),
+        }
+    }
 }
@@ -0,0 +1,154 @@
+use types::{result, Name, Span};
+
+use std::fmt;
+
+// A violation of one of the validation rules of a query or schema.
+#[derive(Clone, Debug)]
+pub struct Error {
+    pub rule: Rule,
+    // Names the offending field, type, argument, etc.
+    pub message: String,
+    pub span: Span,
+    // The response keys of the fields from the root of the operation to where
+    // the error was found, or from the root of the fragment if it was found in
+    // a fragment definition. Empty if the error is not inside any selections,
+    // e.g., in a variable definition or a schema.
+    pub path: Vec<Name>,
+}
+
+impl Error {
+    pub fn new(rule: Rule, message: String, span: Span) -> Error {
+        Error {
+            rule,
+            message,
+            span,
+            path: vec![],
+        }
+    }
+
+    // An entry of the `errors` list in a response, as described by the spec:
+    // `{message, locations: [{line, column}], path, extensions: {code}}`.
+    // `path` is omitted if it is empty.
+    pub fn to_value(&self) -> result::Value {
+        let location = object(vec![
+            ("line", result::Value::Int(self.span.start.row.0 as i64 + 1)),
+            ("column", result::Value::Int(self.span.start.col.0 as i64 + 1)),
+        ]);
+        let mut fields = vec![
+            ("message", result::Value::String(self.message.clone())),
+            ("locations", result::Value::Array(vec![location])),
+        ];
+        if !self.path.is_empty() {
+            let path = self.path.iter().map(|n| result::Value::String(n.0.clone())).collect();
+            fields.push(("path", result::Value::Array(path)));
+        }
+        fields.push((
+            "extensions",
+            object(vec![("code", result::Value::String(self.rule.code().to_owned()))]),
+        ));
+        object(fields)
+    }
+}
+
+fn object(fields: Vec<(&str, result::Value)>) -> result::Value {
+    result::Value::Object(result::Object {
+        fields: fields.into_iter().map(|(n, v)| (Name(n.to_owned()), v)).collect(),
+    })
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.span, self.message)
+    }
+}
+
+// Named after the rules in the Validation section of the spec, where there is
+// one. The rest check schemas or variable values.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum Rule {
+    // Operations.
+    LoneAnonymousOperation,
+    UniqueOperationNames,
+    KnownOperationTypes,
+    SingleFieldSubscriptions,
+    // Fields.
+    FieldsOnCorrectType,
+    OverlappingFieldsCanBeMerged,
+    ScalarLeafs,
+    // Arguments.
+    KnownArgumentNames,
+    UniqueArgumentNames,
+    ProvidedRequiredArguments,
+    // Fragments.
+    UniqueFragmentNames,
+    KnownFragmentNames,
+    KnownTypeNames,
+    FragmentsOnCompositeTypes,
+    PossibleFragmentSpreads,
+    NoUnusedFragments,
+    NoFragmentCycles,
+    // Values.
+    ValuesOfCorrectType,
+    UniqueInputFieldNames,
+    // Directives.
+    KnownDirectives,
+    UniqueDirectivesPerLocation,
+    // Variables.
+    UniqueVariableNames,
+    VariablesAreInputTypes,
+    NoUndefinedVariables,
+    NoUnusedVariables,
+    VariablesInAllowedPosition,
+    // The values supplied for an operation's variables.
+    ProvidedVariables,
+    // Schemas.
+    UniqueTypeNames,
+    UniqueFieldNames,
+    UniqueEnumValueNames,
+    UniqueDirectiveNames,
+    ConsistentFieldTypes,
+    PossibleTypeExtensions,
+    UnionMemberTypes,
+}
+
+impl Rule {
+    // E.g., `FIELDS_ON_CORRECT_TYPE`, used as the error code in responses.
+    pub fn code(&self) -> &'static str {
+        match *self {
+            Rule::LoneAnonymousOperation => "LONE_ANONYMOUS_OPERATION",
+            Rule::UniqueOperationNames => "UNIQUE_OPERATION_NAMES",
+            Rule::KnownOperationTypes => "KNOWN_OPERATION_TYPES",
+            Rule::SingleFieldSubscriptions => "SINGLE_FIELD_SUBSCRIPTIONS",
+            Rule::FieldsOnCorrectType => "FIELDS_ON_CORRECT_TYPE",
+            Rule::OverlappingFieldsCanBeMerged => "OVERLAPPING_FIELDS_CAN_BE_MERGED",
+            Rule::ScalarLeafs => "SCALAR_LEAFS",
+            Rule::KnownArgumentNames => "KNOWN_ARGUMENT_NAMES",
+            Rule::UniqueArgumentNames => "UNIQUE_ARGUMENT_NAMES",
+            Rule::ProvidedRequiredArguments => "PROVIDED_REQUIRED_ARGUMENTS",
+            Rule::UniqueFragmentNames => "UNIQUE_FRAGMENT_NAMES",
+            Rule::KnownFragmentNames => "KNOWN_FRAGMENT_NAMES",
+            Rule::KnownTypeNames => "KNOWN_TYPE_NAMES",
+            Rule::FragmentsOnCompositeTypes => "FRAGMENTS_ON_COMPOSITE_TYPES",
+            Rule::PossibleFragmentSpreads => "POSSIBLE_FRAGMENT_SPREADS",
+            Rule::NoUnusedFragments => "NO_UNUSED_FRAGMENTS",
+            Rule::NoFragmentCycles => "NO_FRAGMENT_CYCLES",
+            Rule::ValuesOfCorrectType => "VALUES_OF_CORRECT_TYPE",
+            Rule::UniqueInputFieldNames => "UNIQUE_INPUT_FIELD_NAMES",
+            Rule::KnownDirectives => "KNOWN_DIRECTIVES",
+            Rule::UniqueDirectivesPerLocation => "UNIQUE_DIRECTIVES_PER_LOCATION",
+            Rule::UniqueVariableNames => "UNIQUE_VARIABLE_NAMES",
+            Rule::VariablesAreInputTypes => "VARIABLES_ARE_INPUT_TYPES",
+            Rule::NoUndefinedVariables => "NO_UNDEFINED_VARIABLES",
+            Rule::NoUnusedVariables => "NO_UNUSED_VARIABLES",
+            Rule::VariablesInAllowedPosition => "VARIABLES_IN_ALLOWED_POSITION",
+            Rule::ProvidedVariables => "PROVIDED_VARIABLES",
+            Rule::UniqueTypeNames => "UNIQUE_TYPE_NAMES",
+            Rule::UniqueFieldNames => "UNIQUE_FIELD_NAMES",
+            Rule::UniqueEnumValueNames => "UNIQUE_ENUM_VALUE_NAMES",
+            Rule::UniqueDirectiveNames => "UNIQUE_DIRECTIVE_NAMES",
+            Rule::ConsistentFieldTypes => "CONSISTENT_FIELD_TYPES",
+            Rule::PossibleTypeExtensions => "POSSIBLE_TYPE_EXTENSIONS",
+            Rule::UnionMemberTypes => "UNION_MEMBER_TYPES",
+        }
+    }
+}
@@ -1,190 +1,556 @@
 use {QlError, QlResult};
-use query::{Field, Operation, Value};
+use query::{Directive, Document, Operation, Value, Variables};
 use schema::{self, Schema};
-use types::Name;
 
-use std::collections::HashSet;
+pub use self::error::{Error, Rule};
+pub use self::rules::QUERY_RULES;
 
-// QUESTION: we do a lot of lookups in lists, these are O(n) where hashing is O(1)
-// however, n is usually pretty small. Is it worth using hashing?
+mod error;
+mod rules;
 
-pub fn validate_query(query: &Operation, schema: &Schema) -> QlResult<()> {
-    let mut ctx = Context::new(schema);
+// Checks a query document against all of the rules in `QUERY_RULES`.
+pub fn validate_query(doc: &Document, schema: &Schema) -> QlResult<()> {
+    validate_query_with_rules(doc, schema, QUERY_RULES)
+}
+
+// Only checks `rules`, e.g., to allow unused fragments in a document which is
+// shared by several clients. Rules which don't apply to queries are ignored.
+pub fn validate_query_with_rules(doc: &Document, schema: &Schema, rules: &[Rule]) -> QlResult<()> {
+    finish(rules::run(doc, schema, rules))
+}
 
-    match *query {
-        Operation::Query(ref f) => {
-            validate_field(f, schema.items[&Name("schema".to_owned())].assert_field(Name("query".to_owned())), &mut ctx);
+// Checks the values supplied for an operation's variables. Should be called
+// after `validate_query`.
+pub fn validate_variables(query: &Operation, variables: &Variables, schema: &Schema) -> QlResult<()> {
+    let mut errors = vec![];
+    for def in &query.variables {
+        match variables.get(&def.name.0) {
+            Some(value) => rules::validate_value(value, &def.ty, schema, true, &mut errors),
+            None => {
+                if !def.ty.nullable && def.default.is_none() {
+                    errors.push(Error::new(
+                        Rule::ProvidedVariables,
+                        format!(
+                            "Variable \"${}\" of required type \"{}\" was not provided.",
+                            def.name, def.ty
+                        ),
+                        def.span,
+                    ));
+                }
+            }
         }
-        Operation::Mutation => unimplemented!(),
     }
+    finish(errors)
+}
+
+// Checks the default value of an argument in the schema. Default values may
+// not refer to variables.
+pub fn validate_default_value(value: &Value, ty: &schema::Type, schema: &Schema) -> Vec<Error> {
+    let mut errors = vec![];
+    rules::validate_value(value, ty, schema, true, &mut errors);
+    rules::validate_unique_input_fields(value, &mut errors);
+    errors
+}
+
+// Checks the directives used on part of the schema, e.g., `@deprecated` on a
+// field.
+pub fn validate_schema_directives(
+    directives: &[Directive],
+    location: schema::DirectiveLocation,
+    schema: &Schema,
+) -> Vec<Error> {
+    let defs: Vec<_> = schema::builtin_directives()
+        .into_iter()
+        .chain(schema.directives.iter().cloned())
+        .collect();
+
+    let mut errors = vec![];
+    rules::validate_unique_directives(directives, &mut errors);
+    for d in directives {
+        let def = defs.iter().find(|def| def.name == d.name);
+        rules::validate_directive(d, def, location, &mut errors);
+        let def = match def {
+            Some(def) => def,
+            None => continue,
+        };
 
-    if ctx.errors.is_empty() {
+        let owner = format!("directive \"@{}\"", d.name);
+        rules::validate_argument_names(&d.args, &def.args, &owner, &mut errors);
+        rules::validate_unique_arguments(&d.args, &mut errors);
+        rules::validate_required_arguments(&d.args, &def.args, &owner, d.span, &mut errors);
+        for (name, value) in &d.args {
+            if let Some(arg) = def.args.iter().find(|a| &a.name == name) {
+                rules::validate_value(value, &arg.ty, schema, true, &mut errors);
+            }
+            rules::validate_unique_input_fields(value, &mut errors);
+        }
+    }
+    errors
+}
+
+fn finish(errors: Vec<Error>) -> QlResult<()> {
+    if errors.is_empty() {
         Ok(())
     } else {
-        Err(QlError::ValidationError(ctx.errors))
+        Err(QlError::ValidationError(errors))
     }
 }
 
-pub type Error = &'static str;
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::parse_idl::parse_schema;
+    use parser::parse_query::parse_query;
+    use query::ValueKind;
+    use types::{Name, Span};
 
-struct Context<'a> {
-    errors: Vec<Error>,
-    schema: &'a Schema,
-}
+    fn schema() -> Schema {
+        parse_schema(
+            "schema { query: Query }
+            directive @upper on FIELD
+            directive @traced on QUERY | MUTATION
+            type Query {
+                page(first: Int, ratio: Float, flag: Boolean, id: ID): String,
+                review(review: ReviewInput!): String,
+                hero: Character,
+                droid: Droid,
+                node: Node,
+                entity: Entity,
+                search: [Search],
+                events(after: Date): [Date],
+                ships(first: Int! = 10): [String],
+                film(episode: Episode, episodes: [Episode!], review: ReviewInput): String,
+            }
+            enum Episode { NEWHOPE, EMPIRE, JEDI }
+            interface Character {
+                name: String,
+            }
+            type Human implements Character {
+                name: String,
+                homePlanet: String,
+            }
+            type Droid {
+                primaryFunction: String,
+            }
+            union Search = Human | Droid
+            scalar Date
+            interface Node {
+                id: ID!,
+            }
+            interface Entity implements Node {
+                id: ID!,
+                name: String,
+            }
+            type Ship implements Entity & Node {
+                id: ID!,
+                name: String,
+            }
+            input ReviewInput {
+                stars: Int!,
+                commentary: String,
+                author: AuthorInput,
+            }
+            input AuthorInput {
+                name: String!,
+            }",
+        ).unwrap()
+    }
+
+    fn validate(query: &str) -> QlResult<()> {
+        validate_query(&parse_query(query).unwrap(), &schema())
+    }
 
-impl<'a> Context<'a> {
-    fn new(schema: &'a Schema) -> Context<'a> {
-        Context {
-            errors: vec![],
-            schema,
+    fn parse_operation(query: &str) -> Operation {
+        parse_query(query).unwrap().operations.remove(0)
+    }
+
+    fn assert_one_error(result: QlResult<()>, query: &str) {
+        match result {
+            Err(QlError::ValidationError(ref errs)) => assert_eq!(errs.len(), 1, "{}", query),
+            r => panic!("Expected validation error for {}, found {:?}", query, r),
         }
     }
-}
 
-// {
-//   human(id: 1002) {
-//     name,
-//     appearsIn,
-//     id
-//   }
-// }
+    #[test]
+    fn test_scalar_values() {
+        validate("{ page(first: 10, ratio: 0.5, flag: true, id: 42) }").unwrap();
+        validate("{ page(ratio: 2, id: \"42\") }").unwrap();
+        validate("{ page(first: null, flag: null) }").unwrap();
+
+        for q in &[
+            "{ page(first: 1.5) }",
+            "{ page(first: \"10\") }",
+            "{ page(ratio: true) }",
+            "{ page(flag: 1) }",
+            "{ page(flag: TRUE) }",
+            "{ page(id: 1.5) }",
+        ] {
+            assert_one_error(validate(q), q);
+        }
+    }
+
+    #[test]
+    fn test_enum_values() {
+        validate("{ film(episode: JEDI, episodes: [NEWHOPE, EMPIRE]) }").unwrap();
+        validate("{ film(episode: null, episodes: []) }").unwrap();
+        validate("query ($e: Episode = EMPIRE) { film(episode: $e) }").unwrap();
+
+        for q in &[
+            "{ film(episode: FOO) }",
+            "{ film(episodes: [JEDI, jedi]) }",
+            "{ film(episode: \"JEDI\") }",
+            "{ film(episode: 4) }",
+            "{ film(episode: {name: JEDI}) }",
+            "{ page(flag: JEDI) }",
+            "{ film(review: JEDI) }",
+            "query ($e: Episode = FOO) { film(episode: $e) }",
+        ] {
+            assert_one_error(validate(q), q);
+        }
 
-fn validate_fields(fields: &[Field], ty: &schema::Item, ctx: &mut Context) {
-    let ty_fields = ty.fields();
+        match validate("{ film(episode: \"JEDI\") }") {
+            Err(QlError::ValidationError(ref errs)) => {
+                assert_eq!(errs[0].message, "Enum \"Episode\" cannot represent non-enum value: \"JEDI\".")
+            }
+            r => panic!("Expected validation error, found {:?}", r),
+        }
 
-    if ty_fields.is_empty() && !fields.is_empty() {
-        ctx.errors.push("fields on scalar type");
-        return;
+        let query = parse_operation("query ($e: Episode) { film(episode: $e) }");
+        let mut variables = Variables::new();
+        variables.insert("e".to_owned(), Value::new(ValueKind::Enum(Name("JEDI".to_owned())), Span::dummy()));
+        validate_variables(&query, &variables, &schema()).unwrap();
+        variables.insert("e".to_owned(), Value::new(ValueKind::String("JEDI".to_owned()), Span::dummy()));
+        assert_one_error(validate_variables(&query, &variables, &schema()), "string variable");
     }
-    if !ty_fields.is_empty() && fields.is_empty() {
-        ctx.errors.push("object type must have fields");
+
+    #[test]
+    fn test_input_objects() {
+        validate("{ review(review: {stars: 5}) }").unwrap();
+        validate("{ review(review: {stars: 5, commentary: null, author: {name: \"Han\"}}) }").unwrap();
+
+        for q in &[
+            "{ review(review: null) }",
+            "{ review(review: 5) }",
+            "{ review(review: {}) }",
+            "{ review(review: {stars: 5, stars: 4}) }",
+            "{ review(review: {stars: 5, rating: 4}) }",
+            "{ review(review: {stars: \"5\"}) }",
+            "{ review(review: {stars: 5, author: {}}) }",
+            "{ page(first: {stars: 5}) }",
+        ] {
+            assert_one_error(validate(q), q);
+        }
     }
 
-    let mut names = HashSet::new();
-    for f in fields {
-        if names.contains(&*f.name.0) {
-            ctx.errors.push("duplicate field");
+    #[test]
+    fn test_variables() {
+        validate("query ($n: Int) { page(first: $n) }").unwrap();
+        validate("query ($n: Int = 5, $f: Boolean!) { page(first: $n, flag: $f) }").unwrap();
+        validate("query ($r: ReviewInput!) { review(review: $r) }").unwrap();
+        validate("query ($s: Int = 3) { review(review: {stars: $s}) }").unwrap();
+
+        for q in &[
+            "{ page(first: $n) }",
+            "query ($n: Int) { page(first: 1) }",
+            "query ($n: Int, $n: Int) { page(first: $n) }",
+            "query ($n: String) { page(first: $n) }",
+            "query ($s: Int) { review(review: {stars: $s}) }",
+            "query ($n: Int = \"x\") { page(first: $n) }",
+            "query ($n: Int = $m) { page(first: $n) }",
+        ] {
+            assert_one_error(validate(q), q);
         }
-        names.insert(&*f.name.0);
+    }
 
-        let field_ty = get_field(ty_fields, &f.name);
-        let field_ty = match field_ty {
-            Some(field_ty) => field_ty,
-            None => {
-                println!("Looking for {} in {:?}", f.name, ty_fields);
-                ctx.errors.push("field not found");
-                continue;
-            }
-        };
-        validate_field(f, field_ty, ctx);
+    #[test]
+    fn test_variable_values() {
+        use query::Variables;
+
+        let schema = schema();
+        let query = parse_operation("query ($n: Int!, $r: ReviewInput = {stars: 1}) { page(first: $n) review(review: $r) }");
+
+        let mut variables = Variables::new();
+        variables.insert("n".to_owned(), Value::new(ValueKind::Int(3), Span::dummy()));
+        validate_variables(&query, &variables, &schema).unwrap();
+
+        variables.insert("r".to_owned(), Value::new(ValueKind::Object(vec![]), Span::dummy()));
+        assert_one_error(validate_variables(&query, &variables, &schema), "missing input field");
+
+        let mut variables = Variables::new();
+        variables.insert("r".to_owned(), Value::new(ValueKind::Null, Span::dummy()));
+        assert_one_error(validate_variables(&query, &variables, &schema), "missing variable");
     }
-}
 
-fn validate_field(field: &Field, ty: &schema::Field, ctx: &mut Context) {
-    validate_args(&field.args, &ty.args, ctx);
+    #[test]
+    fn test_operation_names() {
+        validate("query A { page } query B { page }").unwrap();
 
-    // TODO what do the fields on an array type look like?
-    match ty.ty.as_name_null() {
-        Some(n) => match ctx.schema.items.get(n) {
-            Some(item) => {
-                validate_fields(&field.fields, item, ctx);
-            }
-            None => ctx.errors.push("type not found"),
-        },
-        None if !field.fields.is_empty() => {
-            ctx.errors.push("fields on scalar type");
+        for q in &[
+            "query A { page } query A { page(first: 1) }",
+            "{ page } query B { page }",
+        ] {
+            assert_one_error(validate(q), q);
         }
-        _ => {}
+
+        // Each operation has its own variables.
+        validate("query A($n: Int) { page(first: $n) } query B { page }").unwrap();
+        assert_one_error(
+            validate("query A($n: Int) { page(first: $n) } query B { page(first: $n) }"),
+            "variable in another operation",
+        );
     }
-}
 
-fn validate_args(args: &[(Name, Value)], ty: &[(Name, schema::Type)], ctx: &mut Context) {
-    fn get_type<'a>(name: &Name, ty: &'a [(Name, schema::Type)]) -> Option<&'a schema::Type> {
-        for &(ref n, ref t) in ty {
-            if name == n {
-                return Some(t);
-            }
+    #[test]
+    fn test_fragments() {
+        validate(
+            "{ hero { ...names ... on Human { homePlanet } ... { name } } }
+            fragment names on Character { name }",
+        ).unwrap();
+        validate("{ hero { ...a } } fragment a on Character { ...b } fragment b on Human { name }").unwrap();
+
+        for q in &[
+            "{ hero { ...missing } }",
+            "{ droid { ...names } } fragment names on Character { name }",
+            "{ hero { ... on Foo { name } } }",
+            "{ hero { ... on ReviewInput { name } } }",
+            "{ hero { ... on Human { primaryFunction } } }",
+            "{ hero { name } } fragment names on Character { name }",
+            "{ hero { ...a } } fragment a on Character { name } fragment a on Character { name }",
+            "{ hero { ...a } } fragment a on Character { ...b } fragment b on Character { ...a }",
+        ] {
+            assert_one_error(validate(q), q);
         }
-        None
     }
 
-    let mut names = HashSet::new();
-    for a in args {
-        if names.contains(&a.0) {
-            ctx.errors.push("duplicate argument");
+    #[test]
+    fn test_directives() {
+        validate("{ page @skip(if: true) hero { ...names @include(if: false) } } fragment names on Character { name }")
+            .unwrap();
+        validate("query ($b: Boolean!) { page @include(if: $b) hero { ... @skip(if: $b) { name } } }").unwrap();
+        validate("{ page @upper @skip(if: false) }").unwrap();
+        validate("query @traced { page }").unwrap();
+
+        for q in &[
+            "{ page @foo }",
+            "{ page @deprecated }",
+            "{ page @upper(if: true) }",
+            "{ page @skip }",
+            "{ page @skip(if: 1) }",
+            "{ page @skip(if: true, unless: true) }",
+            "{ page @skip(if: true) @skip(if: false) }",
+            "query ($b: Int) { page @skip(if: $b) }",
+            "query @upper { page }",
+            "query @traced @traced { page }",
+            "{ page @traced }",
+        ] {
+            assert_one_error(validate(q), q);
         }
-        names.insert(&a.0);
+    }
+
+    #[test]
+    fn test_mutations() {
+        let q = "mutation { review(review: {stars: 5}) }";
+        assert_one_error(validate(q), q);
 
-        match get_type(&a.0, ty) {
-            Some(ty) => {
-                validate_value(&a.1, ty, ctx);
+        let schema = parse_schema(
+            "schema { query: Query, mutation: Mutation }
+            type Query {
+                page: String,
             }
-            None => {
-                ctx.errors.push("argument not found");
+            type Mutation {
+                addReview(stars: Int!): String,
+            }",
+        ).unwrap();
+        let validate = |q: &str| validate_query(&parse_query(q).unwrap(), &schema);
+        validate("mutation { addReview(stars: 5) }").unwrap();
+        validate("mutation Add($n: Int!) { addReview(stars: $n) } query Get { page }").unwrap();
+
+        for q in &["mutation { page }", "mutation { addReview }", "query { addReview(stars: 5) }"] {
+            assert_one_error(validate(q), q);
+        }
+    }
+
+    #[test]
+    fn test_subscriptions() {
+        let q = "subscription { page }";
+        assert_one_error(validate(q), q);
+
+        let schema = parse_schema(
+            "schema { query: Query, subscription: Subscription }
+            type Query {
+                page: String,
+            }
+            type Subscription {
+                pageChanged(first: Int): String,
+                heroChanged: Character,
             }
+            interface Character {
+                name: String,
+            }",
+        ).unwrap();
+        let validate = |q: &str| validate_query(&parse_query(q).unwrap(), &schema);
+        validate("subscription { pageChanged(first: 1) }").unwrap();
+        validate("subscription { ...f } fragment f on Subscription { heroChanged { name } }").unwrap();
+        validate("subscription { a: pageChanged ... { a: pageChanged } }").unwrap();
+
+        for q in &[
+            "subscription { pageChanged heroChanged { name } }",
+            "subscription { pageChanged ...f } fragment f on Subscription { heroChanged { name } }",
+            "subscription { page }",
+        ] {
+            assert_one_error(validate(q), q);
         }
     }
 
-    for t in ty {
-        if !names.contains(&t.0) && !(t.1).nullable {
-            ctx.errors.push("missing argument");
+    #[test]
+    fn test_interface_fragments() {
+        validate("{ node { ... on Entity { name } ... on Ship { name } } }").unwrap();
+        validate("{ entity { ... on Node { id } ... on Ship { name } } }").unwrap();
+
+        for q in &["{ node { ... on Character { name } } }", "{ entity { ... on Human { name } } }"] {
+            assert_one_error(validate(q), q);
         }
     }
-}
 
-fn validate_value(value: &Value, ty: &schema::Type, ctx: &mut Context) {
-    if let Value::Null = *value {
-        if !ty.nullable {
-            ctx.errors.push("null value must be non-null");
-        }
-    }
-
-    match ty.kind {
-        schema::TypeKind::String => match *value {
-            Value::Null | Value::String(_) => {}
-            _ => {
-                ctx.errors.push("type mismatch");
-            }
-        },
-        schema::TypeKind::Id => match *value {
-            Value::Null | Value::Name(_) => {}
-            _ => {
-                ctx.errors.push("type mismatch");
-            }
-        },
-        // TODO do we need to lookup the name and check that the value matches it?
-        // yeah, we do e.g., enum values or whatever. Not exactly sure how to do
-        // that though since the values of an enum can be defined by the impl.
-        // What does the spec say?
-        schema::TypeKind::Name(_) => match *value {
-            Value::Null | Value::Name(_) => {}
-            _ => {
-                ctx.errors.push("type mismatch");
-            }
-        },
-        schema::TypeKind::Array(ref el_ty) => match *value {
-            Value::Null => {}
-            Value::Array(ref values) => for v in values {
-                validate_value(v, el_ty, ctx);
-            },
-            _ => {
-                ctx.errors.push("type mismatch");
-            }
-        },
+    #[test]
+    fn test_union_fragments() {
+        validate("{ search { ... on Human { homePlanet } ... on Droid { primaryFunction } } }").unwrap();
+        validate("{ search { ...s } } fragment s on Search { ... on Droid { primaryFunction } }").unwrap();
+        validate("{ droid { ... on Search { ... on Droid { primaryFunction } } } }").unwrap();
+
+        for q in &[
+            "{ search }",
+            "{ search { name } }",
+            "{ search { ... on Ship { name } } }",
+            "{ search { ... on Human { primaryFunction } } }",
+        ] {
+            assert_one_error(validate(q), q);
+        }
     }
-}
 
-fn get_field<'a>(fields: &'a [schema::Field], name: &Name) -> Option<&'a schema::Field> {
-    for f in fields {
-        if &f.name == name {
-            return Some(f);
+    #[test]
+    fn test_custom_scalars() {
+        validate("{ events(after: \"2018-01-01\") }").unwrap();
+        validate("{ events(after: 1514764800) }").unwrap();
+        validate("query ($d: Date) { events(after: $d) }").unwrap();
+
+        for q in &[
+            "{ events(after: \"2018-01-01\") { year } }",
+            "query ($d: String) { events(after: $d) }",
+            "{ hero { ... on Date { name } } }",
+        ] {
+            assert_one_error(validate(q), q);
         }
     }
-    None
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
+    #[test]
+    fn test_default_args() {
+        validate("{ ships }").unwrap();
+        validate("{ ships(first: 5) }").unwrap();
+
+        for q in &["{ ships(first: null) }", "{ ships(first: \"5\") }"] {
+            assert_one_error(validate(q), q);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let errs = match validate("{\n  droid { primaryFunction }\n  h: hero { nam }\n}") {
+            Err(QlError::ValidationError(errs)) => errs,
+            r => panic!("Expected validation error, found {:?}", r),
+        };
+        assert_eq!(errs.len(), 1);
+        let e = &errs[0];
+        assert_eq!(e.rule, Rule::FieldsOnCorrectType);
+        assert_eq!(e.message, "Cannot query field \"nam\" on type \"Character\".");
+        assert_eq!(e.path, vec![Name("h".to_owned()), Name("nam".to_owned())]);
+        assert_eq!(
+            e.to_value().to_string(),
+            "{message:\"Cannot query field \"nam\" on type \"Character\".\",locations:[{line:3,column:13}],\
+             path:[\"h\",\"nam\"],extensions:{code:\"FIELDS_ON_CORRECT_TYPE\"}}"
+        );
+
+        match validate("query Q($id: ID) { page(first: \"ten\") }") {
+            Err(QlError::ValidationError(ref errs)) => {
+                let msgs: Vec<_> = errs.iter().map(|e| &*e.message).collect();
+                assert_eq!(
+                    msgs,
+                    vec![
+                        "Variable \"$id\" is never used in operation \"Q\".",
+                        "Expected value of type \"Int\", found \"ten\".",
+                    ]
+                );
+                assert!(errs[0].path.is_empty());
+                assert_eq!(errs[1].path, vec![Name("page".to_owned())]);
+            }
+            r => panic!("Expected validation error, found {:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_overlapping_fields() {
+        validate("{ hero { name } hero { name } }").unwrap();
+        validate("{ a: page(first: 1, flag: true) a: page(flag: true, first: 1) }").unwrap();
+        validate("{ search { ... on Human { x: homePlanet } ... on Droid { x: primaryFunction } } }").unwrap();
+
+        for q in &[
+            "{ page: hero { name } page }",
+            "{ a: page(first: 1) a: page(first: 2) }",
+            "{ hero { x: name } hero { ... on Human { x: homePlanet } } }",
+            "{ hero { ...f, ... on Human { name: homePlanet } } } fragment f on Character { name }",
+        ] {
+            assert_one_error(validate(q), q);
+        }
+
+        let schema = parse_schema(
+            "schema { query: Query }
+            type Query { u: U }
+            type A { x: Int, y: [Int] }
+            type B { x: String, y: [Int!] }
+            union U = A | B",
+        ).unwrap();
+        let validate = |q: &str| validate_query(&parse_query(q).unwrap(), &schema);
+        validate("{ u { ... on A { x } ... on B { z: x } } }").unwrap();
+        for q in &["{ u { ... on A { x } ... on B { x } } }", "{ u { ... on A { y } ... on B { y } } }"] {
+            assert_one_error(validate(q), q);
+        }
+    }
 
+    #[test]
+    fn test_fragment_variables() {
+        validate("query ($n: Int) { ...f } fragment f on Query { page(first: $n) }").unwrap();
+        validate("{ ...f } fragment f on Query { ...g } fragment g on Query { page }").unwrap();
+
+        for q in &[
+            "query A($n: Int) { ...f } query B { ...f } fragment f on Query { page(first: $n) }",
+            "query ($n: String) { ...f } fragment f on Query { page(first: $n) }",
+            "query ($n: Int) { ...f } fragment f on Query { page }",
+            "{ ...f } fragment f on Query { page } fragment g on Query { ...f }",
+        ] {
+            assert_one_error(validate(q), q);
+        }
+    }
+
+    #[test]
+    fn test_rules() {
+        let schema = schema();
+        let doc = parse_query("{ page } fragment f on Query { page }").unwrap();
+        assert_one_error(validate_query(&doc, &schema), "unused fragment");
+
+        let rules: Vec<_> = QUERY_RULES
+            .iter()
+            .cloned()
+            .filter(|r| *r != Rule::NoUnusedFragments)
+            .collect();
+        validate_query_with_rules(&doc, &schema, &rules).unwrap();
+
+        let doc = parse_query("{ page(first: \"ten\") { name } }").unwrap();
+        match validate_query_with_rules(&doc, &schema, &[Rule::ScalarLeafs, Rule::UniqueTypeNames]) {
+            Err(QlError::ValidationError(ref errs)) => {
+                let rules: Vec<_> = errs.iter().map(|e| e.rule).collect();
+                assert_eq!(rules, vec![Rule::ScalarLeafs]);
+            }
+            r => panic!("Expected validation error, found {:?}", r),
+        }
+    }
 }
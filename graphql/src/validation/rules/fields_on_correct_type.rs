@@ -0,0 +1,28 @@
+use query::Field;
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+// Fields may only be selected on object, interface, or union types which
+// define them. Fields on other types are reported by `ScalarLeafs`.
+#[derive(Default)]
+pub struct FieldsOnCorrectType {
+    errors: Vec<Error>,
+}
+
+impl<'a> Visitor<'a> for FieldsOnCorrectType {
+    fn enter_field(&mut self, field: &'a Field, info: &TypeInfo<'a>) {
+        let parent = match (info.parent_type(), info.parent_item()) {
+            (Some(name), Some(item)) if super::is_composite(item) => name,
+            _ => return,
+        };
+        if info.field_def().is_none() {
+            self.errors.push(Error::new(
+                Rule::FieldsOnCorrectType,
+                format!("Cannot query field \"{}\" on type \"{}\".", field.name, parent),
+                field.span,
+            ));
+        }
+    }
+}
+
+rule_visitor!(FieldsOnCorrectType);
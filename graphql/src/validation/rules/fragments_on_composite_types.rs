@@ -0,0 +1,43 @@
+use query::{Fragment, InlineFragment};
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+// Fragments may only have object, interface, or union types as their type
+// condition. Unknown types are reported by `KnownTypeNames`.
+#[derive(Default)]
+pub struct FragmentsOnCompositeTypes {
+    errors: Vec<Error>,
+}
+
+impl<'a> Visitor<'a> for FragmentsOnCompositeTypes {
+    fn enter_fragment(&mut self, fragment: &'a Fragment, info: &TypeInfo<'a>) {
+        match info.schema().items.get(&fragment.type_condition) {
+            Some(item) if !super::is_composite(item) => self.errors.push(Error::new(
+                Rule::FragmentsOnCompositeTypes,
+                format!(
+                    "Fragment \"{}\" cannot condition on non composite type \"{}\".",
+                    fragment.name, fragment.type_condition
+                ),
+                fragment.span,
+            )),
+            _ => {}
+        }
+    }
+
+    fn enter_inline_fragment(&mut self, inline: &'a InlineFragment, info: &TypeInfo<'a>) {
+        let cond = match inline.type_condition {
+            Some(ref cond) => cond,
+            None => return,
+        };
+        match info.schema().items.get(cond) {
+            Some(item) if !super::is_composite(item) => self.errors.push(Error::new(
+                Rule::FragmentsOnCompositeTypes,
+                format!("Fragment cannot condition on non composite type \"{}\".", cond),
+                inline.span,
+            )),
+            _ => {}
+        }
+    }
+}
+
+rule_visitor!(FragmentsOnCompositeTypes);
@@ -0,0 +1,42 @@
+use query::{Directive, Field, Value};
+use schema::Argument;
+use types::Name;
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+#[derive(Default)]
+pub struct KnownArgumentNames {
+    errors: Vec<Error>,
+}
+
+impl<'a> Visitor<'a> for KnownArgumentNames {
+    fn enter_field(&mut self, field: &'a Field, info: &TypeInfo<'a>) {
+        if let Some(def) = info.field_def() {
+            let owner = super::describe_field(field, info);
+            validate_argument_names(&field.args, &def.args, &owner, &mut self.errors);
+        }
+    }
+
+    fn enter_directive(&mut self, directive: &'a Directive, info: &TypeInfo<'a>) {
+        if let Some(def) = info.directive_def() {
+            let owner = format!("directive \"@{}\"", directive.name);
+            validate_argument_names(&directive.args, &def.args, &owner, &mut self.errors);
+        }
+    }
+}
+
+rule_visitor!(KnownArgumentNames);
+
+// `owner` describes the field or directive which takes `defs`, e.g.,
+// `directive "@skip"`.
+pub fn validate_argument_names(args: &[(Name, Value)], defs: &[Argument], owner: &str, errors: &mut Vec<Error>) {
+    for (name, value) in args {
+        if !defs.iter().any(|d| &d.name == name) {
+            errors.push(Error::new(
+                Rule::KnownArgumentNames,
+                format!("Unknown argument \"{}\" on {}.", name, owner),
+                value.span,
+            ));
+        }
+    }
+}
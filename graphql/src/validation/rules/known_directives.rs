@@ -0,0 +1,66 @@
+use query::{Directive, Field, FragmentSpread, InlineFragment, Operation, OperationKind};
+use schema::{self, DirectiveLocation};
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+// Directives must be defined, and used only in the locations they allow.
+#[derive(Default)]
+pub struct KnownDirectives {
+    errors: Vec<Error>,
+}
+
+impl KnownDirectives {
+    fn check(&mut self, directives: &[Directive], location: DirectiveLocation, info: &TypeInfo) {
+        for d in directives {
+            validate_directive(d, info.find_directive(&d.name), location, &mut self.errors);
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for KnownDirectives {
+    fn enter_operation(&mut self, op: &'a Operation, info: &TypeInfo<'a>) {
+        let location = match op.kind {
+            OperationKind::Query => DirectiveLocation::Query,
+            OperationKind::Mutation => DirectiveLocation::Mutation,
+            OperationKind::Subscription => DirectiveLocation::Subscription,
+        };
+        self.check(&op.directives, location, info);
+    }
+
+    fn enter_field(&mut self, field: &'a Field, info: &TypeInfo<'a>) {
+        self.check(&field.directives, DirectiveLocation::Field, info);
+    }
+
+    fn enter_fragment_spread(&mut self, spread: &'a FragmentSpread, info: &TypeInfo<'a>) {
+        self.check(&spread.directives, DirectiveLocation::FragmentSpread, info);
+    }
+
+    fn enter_inline_fragment(&mut self, inline: &'a InlineFragment, info: &TypeInfo<'a>) {
+        self.check(&inline.directives, DirectiveLocation::InlineFragment, info);
+    }
+}
+
+rule_visitor!(KnownDirectives);
+
+// `def` is the definition of `directive`, if there is one.
+pub fn validate_directive(
+    directive: &Directive,
+    def: Option<&schema::Directive>,
+    location: DirectiveLocation,
+    errors: &mut Vec<Error>,
+) {
+    match def {
+        Some(def) => if !def.locations.contains(&location) {
+            errors.push(Error::new(
+                Rule::KnownDirectives,
+                format!("Directive \"@{}\" may not be used on {}.", directive.name, location.name()),
+                directive.span,
+            ));
+        },
+        None => errors.push(Error::new(
+            Rule::KnownDirectives,
+            format!("Unknown directive \"@{}\".", directive.name),
+            directive.span,
+        )),
+    }
+}
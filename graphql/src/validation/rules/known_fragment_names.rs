@@ -0,0 +1,22 @@
+use query::FragmentSpread;
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+#[derive(Default)]
+pub struct KnownFragmentNames {
+    errors: Vec<Error>,
+}
+
+impl<'a> Visitor<'a> for KnownFragmentNames {
+    fn enter_fragment_spread(&mut self, spread: &'a FragmentSpread, info: &TypeInfo<'a>) {
+        if info.fragment(&spread.name).is_none() {
+            self.errors.push(Error::new(
+                Rule::KnownFragmentNames,
+                format!("Unknown fragment \"{}\".", spread.name),
+                spread.span,
+            ));
+        }
+    }
+}
+
+rule_visitor!(KnownFragmentNames);
@@ -0,0 +1,33 @@
+use query::{Operation, OperationKind};
+use schema;
+use types::Name;
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+// The schema must have a root field for the kind of operation, e.g.,
+// `mutation: Mutation` for mutations.
+#[derive(Default)]
+pub struct KnownOperationTypes {
+    errors: Vec<Error>,
+}
+
+impl<'a> Visitor<'a> for KnownOperationTypes {
+    fn enter_operation(&mut self, op: &'a Operation, info: &TypeInfo<'a>) {
+        let defined = match info.schema().items.get(&Name(schema::SCHEMA_NAME.to_owned())) {
+            Some(root) => root.fields().iter().any(|f| f.name == op.field.name),
+            None => false,
+        };
+        if defined {
+            return;
+        }
+
+        let msg = match op.kind {
+            OperationKind::Query => "Schema does not define a query root type.",
+            OperationKind::Mutation => "Schema is not configured for mutations.",
+            OperationKind::Subscription => "Schema is not configured for subscriptions.",
+        };
+        self.errors.push(Error::new(Rule::KnownOperationTypes, msg.to_owned(), op.field.span));
+    }
+}
+
+rule_visitor!(KnownOperationTypes);
@@ -0,0 +1,40 @@
+use query::{Fragment, InlineFragment, VariableDefinition};
+use types::{Name, Span};
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+// The types named by variable definitions and type conditions must be defined
+// by the schema.
+#[derive(Default)]
+pub struct KnownTypeNames {
+    errors: Vec<Error>,
+}
+
+impl KnownTypeNames {
+    fn check(&mut self, name: &Name, span: Span, info: &TypeInfo) {
+        if !info.schema().items.contains_key(name) {
+            self.errors
+                .push(Error::new(Rule::KnownTypeNames, format!("Unknown type \"{}\".", name), span));
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for KnownTypeNames {
+    fn visit_variable_definition(&mut self, def: &'a VariableDefinition, info: &TypeInfo<'a>) {
+        if let Some(name) = def.ty.named_type() {
+            self.check(name, def.ty.span, info);
+        }
+    }
+
+    fn enter_fragment(&mut self, fragment: &'a Fragment, info: &TypeInfo<'a>) {
+        self.check(&fragment.type_condition, fragment.span, info);
+    }
+
+    fn enter_inline_fragment(&mut self, inline: &'a InlineFragment, info: &TypeInfo<'a>) {
+        if let Some(ref cond) = inline.type_condition {
+            self.check(cond, inline.span, info);
+        }
+    }
+}
+
+rule_visitor!(KnownTypeNames);
@@ -0,0 +1,28 @@
+use query::Document;
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+// An anonymous operation must be the only operation in its document.
+#[derive(Default)]
+pub struct LoneAnonymousOperation {
+    errors: Vec<Error>,
+}
+
+impl<'a> Visitor<'a> for LoneAnonymousOperation {
+    fn enter_document(&mut self, doc: &'a Document, _info: &TypeInfo<'a>) {
+        if doc.operations.len() < 2 {
+            return;
+        }
+        for op in &doc.operations {
+            if op.name.is_none() {
+                self.errors.push(Error::new(
+                    Rule::LoneAnonymousOperation,
+                    "This anonymous operation must be the only defined operation.".to_owned(),
+                    op.field.span,
+                ));
+            }
+        }
+    }
+}
+
+rule_visitor!(LoneAnonymousOperation);
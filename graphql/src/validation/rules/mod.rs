@@ -0,0 +1,255 @@
+// One module per rule from the Validation section of the spec. Each rule is a
+// `Visitor` which collects errors, all enabled rules are run in a single walk of
+// the document.
+
+use query::{Directive, Document, Field, Fragment, FragmentSpread, InlineFragment, Operation, Value,
+            VariableDefinition};
+use schema::{Item, Schema};
+use types::Name;
+use validation::{Error, Rule};
+use visit::{walk_document, TypeInfo, Visitor};
+
+use std::mem;
+
+// Implements `RuleVisitor` for a rule with an `errors` field.
+macro_rules! rule_visitor {
+    ($rule: ident) => {
+        impl<'a> super::RuleVisitor<'a> for $rule {
+            fn errors(&mut self) -> &mut Vec<::validation::Error> {
+                &mut self.errors
+            }
+        }
+    };
+    ($rule: ident<'a>) => {
+        impl<'a> super::RuleVisitor<'a> for $rule<'a> {
+            fn errors(&mut self) -> &mut Vec<::validation::Error> {
+                &mut self.errors
+            }
+        }
+    };
+}
+
+pub use self::known_argument_names::validate_argument_names;
+pub use self::known_directives::validate_directive;
+pub use self::provided_required_arguments::validate_required_arguments;
+pub use self::unique_argument_names::validate_unique_arguments;
+pub use self::unique_directives_per_location::validate_unique_directives;
+pub use self::unique_input_field_names::validate_unique_input_fields;
+pub use self::values_of_correct_type::validate_value;
+
+mod fields_on_correct_type;
+mod fragments_on_composite_types;
+mod known_argument_names;
+mod known_directives;
+mod known_fragment_names;
+mod known_operation_types;
+mod known_type_names;
+mod lone_anonymous_operation;
+mod no_fragment_cycles;
+mod no_undefined_variables;
+mod no_unused_fragments;
+mod no_unused_variables;
+mod overlapping_fields_can_be_merged;
+mod possible_fragment_spreads;
+mod provided_required_arguments;
+mod scalar_leafs;
+mod single_field_subscriptions;
+mod unique_argument_names;
+mod unique_directives_per_location;
+mod unique_fragment_names;
+mod unique_input_field_names;
+mod unique_operation_names;
+mod unique_variable_names;
+mod usages;
+mod values_of_correct_type;
+mod variables_are_input_types;
+mod variables_in_allowed_position;
+
+// The rules which `validate_query` checks.
+pub const QUERY_RULES: &[Rule] = &[
+    Rule::LoneAnonymousOperation,
+    Rule::UniqueOperationNames,
+    Rule::KnownOperationTypes,
+    Rule::SingleFieldSubscriptions,
+    Rule::FieldsOnCorrectType,
+    Rule::OverlappingFieldsCanBeMerged,
+    Rule::ScalarLeafs,
+    Rule::KnownArgumentNames,
+    Rule::UniqueArgumentNames,
+    Rule::ProvidedRequiredArguments,
+    Rule::UniqueFragmentNames,
+    Rule::KnownFragmentNames,
+    Rule::KnownTypeNames,
+    Rule::FragmentsOnCompositeTypes,
+    Rule::PossibleFragmentSpreads,
+    Rule::NoUnusedFragments,
+    Rule::NoFragmentCycles,
+    Rule::ValuesOfCorrectType,
+    Rule::UniqueInputFieldNames,
+    Rule::KnownDirectives,
+    Rule::UniqueDirectivesPerLocation,
+    Rule::UniqueVariableNames,
+    Rule::VariablesAreInputTypes,
+    Rule::NoUndefinedVariables,
+    Rule::NoUnusedVariables,
+    Rule::VariablesInAllowedPosition,
+];
+
+// A rule pushes its errors without a path, the path is filled in when they are
+// collected.
+trait RuleVisitor<'a>: Visitor<'a> {
+    fn errors(&mut self) -> &mut Vec<Error>;
+}
+
+// Objects, interfaces, and unions, the types whose fields can be selected.
+fn is_composite(item: &Item) -> bool {
+    matches!(*item, Item::Object(_) | Item::Interface(_) | Item::Union(_))
+}
+
+// E.g., `field "Query.hero"`, for messages about a field's arguments.
+fn describe_field(field: &Field, info: &TypeInfo) -> String {
+    match info.parent_type() {
+        Some(parent) => format!("field \"{}.{}\"", parent, field.name),
+        None => format!("field \"{}\"", field.name),
+    }
+}
+
+// `None` for rules which don't apply to query documents.
+fn new_rule<'a>(rule: Rule) -> Option<Box<dyn RuleVisitor<'a> + 'a>> {
+    Some(match rule {
+        Rule::LoneAnonymousOperation => Box::new(lone_anonymous_operation::LoneAnonymousOperation::default()),
+        Rule::UniqueOperationNames => Box::new(unique_operation_names::UniqueOperationNames::default()),
+        Rule::KnownOperationTypes => Box::new(known_operation_types::KnownOperationTypes::default()),
+        Rule::SingleFieldSubscriptions => Box::new(single_field_subscriptions::SingleFieldSubscriptions::default()),
+        Rule::FieldsOnCorrectType => Box::new(fields_on_correct_type::FieldsOnCorrectType::default()),
+        Rule::OverlappingFieldsCanBeMerged => {
+            Box::new(overlapping_fields_can_be_merged::OverlappingFieldsCanBeMerged::default())
+        }
+        Rule::ScalarLeafs => Box::new(scalar_leafs::ScalarLeafs::default()),
+        Rule::KnownArgumentNames => Box::new(known_argument_names::KnownArgumentNames::default()),
+        Rule::UniqueArgumentNames => Box::new(unique_argument_names::UniqueArgumentNames::default()),
+        Rule::ProvidedRequiredArguments => {
+            Box::new(provided_required_arguments::ProvidedRequiredArguments::default())
+        }
+        Rule::UniqueFragmentNames => Box::new(unique_fragment_names::UniqueFragmentNames::default()),
+        Rule::KnownFragmentNames => Box::new(known_fragment_names::KnownFragmentNames::default()),
+        Rule::KnownTypeNames => Box::new(known_type_names::KnownTypeNames::default()),
+        Rule::FragmentsOnCompositeTypes => {
+            Box::new(fragments_on_composite_types::FragmentsOnCompositeTypes::default())
+        }
+        Rule::PossibleFragmentSpreads => Box::new(possible_fragment_spreads::PossibleFragmentSpreads::default()),
+        Rule::NoUnusedFragments => Box::new(no_unused_fragments::NoUnusedFragments::default()),
+        Rule::NoFragmentCycles => Box::new(no_fragment_cycles::NoFragmentCycles::default()),
+        Rule::ValuesOfCorrectType => Box::new(values_of_correct_type::ValuesOfCorrectType::default()),
+        Rule::UniqueInputFieldNames => Box::new(unique_input_field_names::UniqueInputFieldNames::default()),
+        Rule::KnownDirectives => Box::new(known_directives::KnownDirectives::default()),
+        Rule::UniqueDirectivesPerLocation => {
+            Box::new(unique_directives_per_location::UniqueDirectivesPerLocation::default())
+        }
+        Rule::UniqueVariableNames => Box::new(unique_variable_names::UniqueVariableNames::default()),
+        Rule::VariablesAreInputTypes => Box::new(variables_are_input_types::VariablesAreInputTypes::default()),
+        Rule::NoUndefinedVariables => Box::new(no_undefined_variables::NoUndefinedVariables::default()),
+        Rule::NoUnusedVariables => Box::new(no_unused_variables::NoUnusedVariables::default()),
+        Rule::VariablesInAllowedPosition => {
+            Box::new(variables_in_allowed_position::VariablesInAllowedPosition::default())
+        }
+        Rule::ProvidedVariables
+        | Rule::UniqueTypeNames
+        | Rule::UniqueFieldNames
+        | Rule::UniqueEnumValueNames
+        | Rule::UniqueDirectiveNames
+        | Rule::ConsistentFieldTypes
+        | Rule::PossibleTypeExtensions
+        | Rule::UnionMemberTypes => return None,
+    })
+}
+
+pub fn run(doc: &Document, schema: &Schema, rules: &[Rule]) -> Vec<Error> {
+    let mut rules = rules.to_vec();
+    rules.sort();
+    rules.dedup();
+
+    let mut runner = Runner {
+        rules: rules.into_iter().filter_map(new_rule).collect(),
+        errors: vec![],
+    };
+    walk_document(&mut runner, doc, schema);
+    runner.errors
+}
+
+// Passes each hook on to every rule.
+struct Runner<'a> {
+    rules: Vec<Box<dyn RuleVisitor<'a> + 'a>>,
+    errors: Vec<Error>,
+}
+
+impl<'a> Runner<'a> {
+    fn each<F: FnMut(&mut (dyn RuleVisitor<'a> + 'a))>(&mut self, info: &TypeInfo<'a>, mut f: F) {
+        for rule in &mut self.rules {
+            f(&mut **rule);
+            for mut e in mem::take(rule.errors()) {
+                e.path = info.path().iter().map(|n| (*n).clone()).collect();
+                self.errors.push(e);
+            }
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for Runner<'a> {
+    fn enter_document(&mut self, doc: &'a Document, info: &TypeInfo<'a>) {
+        self.each(info, |r| r.enter_document(doc, info));
+    }
+    fn leave_document(&mut self, doc: &'a Document, info: &TypeInfo<'a>) {
+        self.each(info, |r| r.leave_document(doc, info));
+    }
+
+    fn enter_operation(&mut self, op: &'a Operation, info: &TypeInfo<'a>) {
+        self.each(info, |r| r.enter_operation(op, info));
+    }
+    fn leave_operation(&mut self, op: &'a Operation, info: &TypeInfo<'a>) {
+        self.each(info, |r| r.leave_operation(op, info));
+    }
+
+    fn enter_fragment(&mut self, fragment: &'a Fragment, info: &TypeInfo<'a>) {
+        self.each(info, |r| r.enter_fragment(fragment, info));
+    }
+    fn leave_fragment(&mut self, fragment: &'a Fragment, info: &TypeInfo<'a>) {
+        self.each(info, |r| r.leave_fragment(fragment, info));
+    }
+
+    fn visit_variable_definition(&mut self, def: &'a VariableDefinition, info: &TypeInfo<'a>) {
+        self.each(info, |r| r.visit_variable_definition(def, info));
+    }
+
+    fn enter_field(&mut self, field: &'a Field, info: &TypeInfo<'a>) {
+        self.each(info, |r| r.enter_field(field, info));
+    }
+    fn leave_field(&mut self, field: &'a Field, info: &TypeInfo<'a>) {
+        self.each(info, |r| r.leave_field(field, info));
+    }
+
+    fn enter_fragment_spread(&mut self, spread: &'a FragmentSpread, info: &TypeInfo<'a>) {
+        self.each(info, |r| r.enter_fragment_spread(spread, info));
+    }
+    fn leave_fragment_spread(&mut self, spread: &'a FragmentSpread, info: &TypeInfo<'a>) {
+        self.each(info, |r| r.leave_fragment_spread(spread, info));
+    }
+
+    fn enter_inline_fragment(&mut self, inline: &'a InlineFragment, info: &TypeInfo<'a>) {
+        self.each(info, |r| r.enter_inline_fragment(inline, info));
+    }
+    fn leave_inline_fragment(&mut self, inline: &'a InlineFragment, info: &TypeInfo<'a>) {
+        self.each(info, |r| r.leave_inline_fragment(inline, info));
+    }
+
+    fn enter_directive(&mut self, directive: &'a Directive, info: &TypeInfo<'a>) {
+        self.each(info, |r| r.enter_directive(directive, info));
+    }
+    fn leave_directive(&mut self, directive: &'a Directive, info: &TypeInfo<'a>) {
+        self.each(info, |r| r.leave_directive(directive, info));
+    }
+
+    fn visit_argument(&mut self, name: &'a Name, value: &'a Value, info: &TypeInfo<'a>) {
+        self.each(info, |r| r.visit_argument(name, value, info));
+    }
+}
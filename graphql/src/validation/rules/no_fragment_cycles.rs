@@ -0,0 +1,64 @@
+use query::{Document, Fragment, Selection};
+use types::Name;
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+use std::collections::HashSet;
+
+// A fragment must not spread itself, directly or through other fragments.
+#[derive(Default)]
+pub struct NoFragmentCycles {
+    errors: Vec<Error>,
+}
+
+impl<'a> Visitor<'a> for NoFragmentCycles {
+    fn enter_document(&mut self, doc: &'a Document, _info: &TypeInfo<'a>) {
+        // Fragments which have been fully explored.
+        let mut done = HashSet::new();
+        for f in &doc.fragments {
+            if done.contains(&f.name) {
+                continue;
+            }
+            let mut path = vec![&f.name];
+            find_cycles(&f.selections, &mut path, &mut done, &doc.fragments, &mut self.errors);
+            done.insert(&f.name);
+        }
+    }
+}
+
+rule_visitor!(NoFragmentCycles);
+
+// `path` is the chain of fragments which has been spread to reach `selections`.
+fn find_cycles<'a>(
+    selections: &'a [Selection],
+    path: &mut Vec<&'a Name>,
+    done: &mut HashSet<&'a Name>,
+    fragments: &'a [Fragment],
+    errors: &mut Vec<Error>,
+) {
+    for s in selections {
+        match *s {
+            Selection::Field(ref f) => find_cycles(&f.selections, path, done, fragments, errors),
+            Selection::InlineFragment(ref i) => find_cycles(&i.selections, path, done, fragments, errors),
+            Selection::FragmentSpread(ref spread) => {
+                if path.contains(&&spread.name) {
+                    errors.push(Error::new(
+                        Rule::NoFragmentCycles,
+                        format!("Cannot spread fragment \"{}\" within itself.", spread.name),
+                        spread.span,
+                    ));
+                    continue;
+                }
+                if done.contains(&spread.name) {
+                    continue;
+                }
+                if let Some(f) = fragments.iter().find(|f| f.name == spread.name) {
+                    path.push(&f.name);
+                    find_cycles(&f.selections, path, done, fragments, errors);
+                    path.pop();
+                    done.insert(&f.name);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use query::Document;
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+use super::usages::Usages;
+
+// Every variable used by an operation, including in the fragments it spreads,
+// must be defined by that operation.
+#[derive(Default)]
+pub struct NoUndefinedVariables {
+    errors: Vec<Error>,
+}
+
+impl<'a> Visitor<'a> for NoUndefinedVariables {
+    fn enter_document(&mut self, doc: &'a Document, info: &TypeInfo<'a>) {
+        let usages = Usages::new(doc, info.schema());
+        for &(op, ref scope) in &usages.operations {
+            for usage in usages.variables(scope) {
+                if op.variables.iter().any(|def| &def.name == usage.name) {
+                    continue;
+                }
+                let msg = match op.name {
+                    Some(ref op_name) => format!(
+                        "Variable \"${}\" is not defined by operation \"{}\".",
+                        usage.name, op_name
+                    ),
+                    None => format!("Variable \"${}\" is not defined.", usage.name),
+                };
+                self.errors.push(Error::new(Rule::NoUndefinedVariables, msg, usage.span));
+            }
+        }
+    }
+}
+
+rule_visitor!(NoUndefinedVariables);
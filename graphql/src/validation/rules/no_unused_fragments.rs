@@ -0,0 +1,36 @@
+use query::Document;
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+use super::usages::Usages;
+
+use std::collections::HashSet;
+
+// Every fragment must be spread by an operation, directly or through other
+// fragments.
+#[derive(Default)]
+pub struct NoUnusedFragments {
+    errors: Vec<Error>,
+}
+
+impl<'a> Visitor<'a> for NoUnusedFragments {
+    fn enter_document(&mut self, doc: &'a Document, info: &TypeInfo<'a>) {
+        let usages = Usages::new(doc, info.schema());
+        let mut used = HashSet::new();
+        for (_, scope) in &usages.operations {
+            used.extend(usages.fragments(scope));
+        }
+
+        for f in &doc.fragments {
+            if !used.contains(&f.name) {
+                self.errors.push(Error::new(
+                    Rule::NoUnusedFragments,
+                    format!("Fragment \"{}\" is never used.", f.name),
+                    f.span,
+                ));
+            }
+        }
+    }
+}
+
+rule_visitor!(NoUnusedFragments);
@@ -0,0 +1,38 @@
+use query::Document;
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+use super::usages::Usages;
+
+use std::collections::HashSet;
+
+// Every variable defined by an operation must be used by it, or by a fragment
+// it spreads.
+#[derive(Default)]
+pub struct NoUnusedVariables {
+    errors: Vec<Error>,
+}
+
+impl<'a> Visitor<'a> for NoUnusedVariables {
+    fn enter_document(&mut self, doc: &'a Document, info: &TypeInfo<'a>) {
+        let usages = Usages::new(doc, info.schema());
+        for &(op, ref scope) in &usages.operations {
+            let used: HashSet<_> = usages.variables(scope).iter().map(|u| u.name).collect();
+            for def in &op.variables {
+                if used.contains(&def.name) {
+                    continue;
+                }
+                let msg = match op.name {
+                    Some(ref op_name) => format!(
+                        "Variable \"${}\" is never used in operation \"{}\".",
+                        def.name, op_name
+                    ),
+                    None => format!("Variable \"${}\" is never used.", def.name),
+                };
+                self.errors.push(Error::new(Rule::NoUnusedVariables, msg, def.span));
+            }
+        }
+    }
+}
+
+rule_visitor!(NoUnusedVariables);
@@ -0,0 +1,187 @@
+use query::{Field, Fragment, Operation, Selection, Value};
+use schema::{self, Item, Schema, Type, TypeKind};
+use types::Name;
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+use std::collections::HashSet;
+
+// Fields selected with the same response key, including through fragments,
+// must be merged into a single entry of the response. So they must be the same
+// field with the same arguments (unless they are on different object types, and
+// so can never both apply), and their types must have the same shape.
+#[derive(Default)]
+pub struct OverlappingFieldsCanBeMerged {
+    errors: Vec<Error>,
+    // Pairs of fields which have already been compared, so that a conflict is
+    // only reported once.
+    compared: HashSet<(*const Field, *const Field)>,
+}
+
+// A field as it is selected, `parent` is the type it is selected on.
+struct FieldInfo<'a> {
+    parent: Option<&'a Name>,
+    field: &'a Field,
+    def: Option<&'a schema::Field>,
+}
+
+impl<'a> Visitor<'a> for OverlappingFieldsCanBeMerged {
+    fn enter_operation(&mut self, op: &'a Operation, info: &TypeInfo<'a>) {
+        self.check_selections(&op.field.selections, info.parent_type(), info);
+    }
+
+    fn enter_fragment(&mut self, fragment: &'a Fragment, info: &TypeInfo<'a>) {
+        self.check_selections(&fragment.selections, Some(&fragment.type_condition), info);
+    }
+
+    // Inline fragments are checked as part of the selections which contain
+    // them.
+    fn enter_field(&mut self, field: &'a Field, info: &TypeInfo<'a>) {
+        let ty = info.field_def().and_then(|def| def.ty.named_type());
+        self.check_selections(&field.selections, ty, info);
+    }
+}
+
+rule_visitor!(OverlappingFieldsCanBeMerged);
+
+impl OverlappingFieldsCanBeMerged {
+    fn check_selections<'a>(&mut self, selections: &'a [Selection], parent: Option<&'a Name>, info: &TypeInfo<'a>) {
+        let mut fields = vec![];
+        collect_fields(selections, parent, info, &mut HashSet::new(), &mut fields);
+        for (i, a) in fields.iter().enumerate() {
+            for b in &fields[i + 1..] {
+                if a.field.response_key() == b.field.response_key() {
+                    self.compare(a, b, false, info);
+                }
+            }
+        }
+    }
+
+    // `exclusive` if the parents of `a` and `b` (or of fields which contain
+    // them) are different object types.
+    fn compare<'a>(&mut self, a: &FieldInfo<'a>, b: &FieldInfo<'a>, exclusive: bool, info: &TypeInfo<'a>) {
+        if !self.compared.insert((a.field as *const Field, b.field as *const Field)) {
+            return;
+        }
+
+        let schema = info.schema();
+        let key = a.field.response_key();
+        let exclusive = exclusive || (a.parent != b.parent && is_object(a.parent, schema) && is_object(b.parent, schema));
+        if !exclusive {
+            if a.field.name != b.field.name {
+                self.conflict(
+                    key,
+                    &format!("\"{}\" and \"{}\" are different fields", a.field.name, b.field.name),
+                    b.field,
+                );
+                return;
+            }
+            if !same_args(&a.field.args, &b.field.args) {
+                self.conflict(key, "they have differing arguments", b.field);
+                return;
+            }
+        }
+
+        if let (Some(def_a), Some(def_b)) = (a.def, b.def) {
+            if types_conflict(&def_a.ty, &def_b.ty, schema) {
+                self.conflict(
+                    key,
+                    &format!("they return conflicting types \"{}\" and \"{}\"", def_a.ty, def_b.ty),
+                    b.field,
+                );
+                return;
+            }
+
+            // The selections of `a` and `b` are merged, so must not conflict
+            // with each other. Conflicts within either are found when it is
+            // visited.
+            let mut fields_a = vec![];
+            collect_fields(&a.field.selections, def_a.ty.named_type(), info, &mut HashSet::new(), &mut fields_a);
+            let mut fields_b = vec![];
+            collect_fields(&b.field.selections, def_b.ty.named_type(), info, &mut HashSet::new(), &mut fields_b);
+            for sub_a in &fields_a {
+                for sub_b in &fields_b {
+                    if sub_a.field.response_key() == sub_b.field.response_key() {
+                        self.compare(sub_a, sub_b, exclusive, info);
+                    }
+                }
+            }
+        }
+    }
+
+    fn conflict(&mut self, key: &Name, reason: &str, field: &Field) {
+        self.errors.push(Error::new(
+            Rule::OverlappingFieldsCanBeMerged,
+            format!(
+                "Fields \"{}\" conflict because {}. Use different aliases on the fields to fetch both if this was intentional.",
+                key, reason
+            ),
+            field.span,
+        ));
+    }
+}
+
+// The fields in `selections`, including those in fragments. `visited` are the
+// fragments which have already been spread.
+fn collect_fields<'a>(
+    selections: &'a [Selection],
+    parent: Option<&'a Name>,
+    info: &TypeInfo<'a>,
+    visited: &mut HashSet<&'a Name>,
+    fields: &mut Vec<FieldInfo<'a>>,
+) {
+    let schema = info.schema();
+    for s in selections {
+        match *s {
+            Selection::Field(ref field) => {
+                let def = parent
+                    .and_then(|p| schema.items.get(p))
+                    .and_then(|item| item.fields().iter().find(|f| f.name == field.name));
+                fields.push(FieldInfo { parent, field, def });
+            }
+            Selection::InlineFragment(ref inline) => {
+                let parent = inline.type_condition.as_ref().or(parent);
+                collect_fields(&inline.selections, parent, info, visited, fields);
+            }
+            Selection::FragmentSpread(ref spread) => {
+                if !visited.insert(&spread.name) {
+                    continue;
+                }
+                if let Some(f) = info.fragment(&spread.name) {
+                    collect_fields(&f.selections, Some(&f.type_condition), info, visited, fields);
+                }
+            }
+        }
+    }
+}
+
+fn is_object(name: Option<&Name>, schema: &Schema) -> bool {
+    matches!(name.and_then(|n| schema.items.get(n)), Some(&Item::Object(_)))
+}
+
+// Arguments may be given in any order.
+fn same_args(a: &[(Name, Value)], b: &[(Name, Value)]) -> bool {
+    a.len() == b.len() && a.iter().all(|arg| b.contains(arg))
+}
+
+// Types conflict unless they have the same nullability and nesting of arrays,
+// and either the same leaf type or composite types (whose fields are compared
+// separately).
+fn types_conflict(a: &Type, b: &Type, schema: &Schema) -> bool {
+    if a.nullable != b.nullable {
+        return true;
+    }
+    match (&a.kind, &b.kind) {
+        (TypeKind::Array(a), TypeKind::Array(b)) => types_conflict(a, b, schema),
+        (TypeKind::Name(name_a), TypeKind::Name(name_b))
+            if is_composite_type(name_a, schema) && is_composite_type(name_b, schema) =>
+        {
+            false
+        }
+        _ => !a.same(b),
+    }
+}
+
+fn is_composite_type(name: &Name, schema: &Schema) -> bool {
+    schema.items.get(name).is_some_and(super::is_composite)
+}
@@ -0,0 +1,81 @@
+use query::{FragmentSpread, InlineFragment};
+use schema::{Item, Schema};
+use types::Name;
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+use std::collections::HashSet;
+
+// A fragment may only be spread where an object could have both the type of
+// the enclosing selections and the fragment's type condition.
+#[derive(Default)]
+pub struct PossibleFragmentSpreads {
+    errors: Vec<Error>,
+}
+
+impl<'a> Visitor<'a> for PossibleFragmentSpreads {
+    fn enter_fragment_spread(&mut self, spread: &'a FragmentSpread, info: &TypeInfo<'a>) {
+        let (fragment, parent) = match (info.fragment(&spread.name), info.parent_type()) {
+            (Some(fragment), Some(parent)) => (fragment, parent),
+            _ => return,
+        };
+        if !can_overlap(&fragment.type_condition, parent, info.schema()) {
+            self.errors.push(Error::new(
+                Rule::PossibleFragmentSpreads,
+                format!(
+                    "Fragment \"{}\" cannot be spread here as objects of type \"{}\" can never be of type \"{}\".",
+                    spread.name, parent, fragment.type_condition
+                ),
+                spread.span,
+            ));
+        }
+    }
+
+    fn enter_inline_fragment(&mut self, inline: &'a InlineFragment, info: &TypeInfo<'a>) {
+        let (cond, parent) = match (inline.type_condition.as_ref(), info.parent_type()) {
+            (Some(cond), Some(parent)) => (cond, parent),
+            _ => return,
+        };
+        if !can_overlap(cond, parent, info.schema()) {
+            self.errors.push(Error::new(
+                Rule::PossibleFragmentSpreads,
+                format!(
+                    "Fragment cannot be spread here as objects of type \"{}\" can never be of type \"{}\".",
+                    parent, cond
+                ),
+                inline.span,
+            ));
+        }
+    }
+}
+
+rule_visitor!(PossibleFragmentSpreads);
+
+// True unless `a` and `b` are both composite types with no possible types in
+// common. Other types are reported by other rules.
+fn can_overlap(a: &Name, b: &Name, schema: &Schema) -> bool {
+    match (schema.items.get(a), schema.items.get(b)) {
+        (Some(item_a), Some(item_b)) if super::is_composite(item_a) && super::is_composite(item_b) => {
+            let types_a = possible_types(a, schema);
+            possible_types(b, schema).iter().any(|t| types_a.contains(t))
+        }
+        _ => true,
+    }
+}
+
+// The types which a value of type `name` might have when executed. Interfaces
+// are resolved as themselves, as well as any type which implements them.
+fn possible_types<'a>(name: &'a Name, schema: &'a Schema) -> HashSet<&'a Name> {
+    let mut result = HashSet::new();
+    result.insert(name);
+    match schema.items.get(name) {
+        Some(&Item::Interface(_)) => for n in schema.items.keys() {
+            if schema.implements(n, name) {
+                result.insert(n);
+            }
+        },
+        Some(Item::Union(u)) => result.extend(&u.members),
+        _ => {}
+    }
+    result
+}
@@ -0,0 +1,52 @@
+use query::{Directive, Field, Value};
+use schema::Argument;
+use types::{Name, Span};
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+// Non-null arguments without a default value must be supplied.
+#[derive(Default)]
+pub struct ProvidedRequiredArguments {
+    errors: Vec<Error>,
+}
+
+impl<'a> Visitor<'a> for ProvidedRequiredArguments {
+    fn enter_field(&mut self, field: &'a Field, info: &TypeInfo<'a>) {
+        if let Some(def) = info.field_def() {
+            let owner = super::describe_field(field, info);
+            validate_required_arguments(&field.args, &def.args, &owner, field.span, &mut self.errors);
+        }
+    }
+
+    fn enter_directive(&mut self, directive: &'a Directive, info: &TypeInfo<'a>) {
+        if let Some(def) = info.directive_def() {
+            let owner = format!("directive \"@{}\"", directive.name);
+            validate_required_arguments(&directive.args, &def.args, &owner, directive.span, &mut self.errors);
+        }
+    }
+}
+
+rule_visitor!(ProvidedRequiredArguments);
+
+// `owner` describes the field or directive which takes `defs`, and `span` is
+// its span.
+pub fn validate_required_arguments(
+    args: &[(Name, Value)],
+    defs: &[Argument],
+    owner: &str,
+    span: Span,
+    errors: &mut Vec<Error>,
+) {
+    for d in defs {
+        if !d.ty.nullable && d.default.is_none() && !args.iter().any(|a| a.0 == d.name) {
+            errors.push(Error::new(
+                Rule::ProvidedRequiredArguments,
+                format!(
+                    "Argument \"{}\" of type \"{}\" is required by {}, but it was not provided.",
+                    d.name, d.ty, owner
+                ),
+                span,
+            ));
+        }
+    }
+}
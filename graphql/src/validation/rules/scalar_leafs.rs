@@ -0,0 +1,49 @@
+use query::Field;
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+// Fields of scalar and enum types must not have selections, fields of other
+// types must.
+#[derive(Default)]
+pub struct ScalarLeafs {
+    errors: Vec<Error>,
+}
+
+impl<'a> Visitor<'a> for ScalarLeafs {
+    fn enter_field(&mut self, field: &'a Field, info: &TypeInfo<'a>) {
+        let def = match info.field_def() {
+            Some(def) => def,
+            None => return,
+        };
+        // Selections on an array apply to each of its elements.
+        let composite = match def.ty.named_type() {
+            Some(n) => match info.schema().items.get(n) {
+                Some(item) => super::is_composite(item),
+                None => return,
+            },
+            None => false,
+        };
+
+        if composite && field.selections.is_empty() {
+            self.errors.push(Error::new(
+                Rule::ScalarLeafs,
+                format!(
+                    "Field \"{}\" of type \"{}\" must have a selection of subfields.",
+                    field.name, def.ty
+                ),
+                field.span,
+            ));
+        } else if !composite && !field.selections.is_empty() {
+            self.errors.push(Error::new(
+                Rule::ScalarLeafs,
+                format!(
+                    "Field \"{}\" must not have a selection since type \"{}\" has no subfields.",
+                    field.name, def.ty
+                ),
+                field.span,
+            ));
+        }
+    }
+}
+
+rule_visitor!(ScalarLeafs);
@@ -0,0 +1,59 @@
+use query::{Operation, OperationKind, Selection};
+use types::Name;
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+use std::collections::HashSet;
+
+#[derive(Default)]
+pub struct SingleFieldSubscriptions {
+    errors: Vec<Error>,
+}
+
+impl<'a> Visitor<'a> for SingleFieldSubscriptions {
+    fn enter_operation(&mut self, op: &'a Operation, info: &TypeInfo<'a>) {
+        if op.kind != OperationKind::Subscription {
+            return;
+        }
+
+        let mut keys = HashSet::new();
+        collect_response_keys(&op.field.selections, info, &mut HashSet::new(), &mut keys);
+        if keys.len() != 1 {
+            let msg = match op.name {
+                Some(ref name) => format!("Subscription \"{}\" must select only one top level field.", name),
+                None => "Anonymous subscription must select only one top level field.".to_owned(),
+            };
+            self.errors.push(Error::new(Rule::SingleFieldSubscriptions, msg, op.field.span));
+        }
+    }
+}
+
+rule_visitor!(SingleFieldSubscriptions);
+
+// The response keys of the fields in `selections`, including those in
+// fragments. Directives are ignored.
+fn collect_response_keys<'a>(
+    selections: &'a [Selection],
+    info: &TypeInfo<'a>,
+    visited: &mut HashSet<&'a Name>,
+    keys: &mut HashSet<&'a Name>,
+) {
+    for s in selections {
+        match *s {
+            Selection::Field(ref f) => {
+                keys.insert(f.response_key());
+            }
+            Selection::FragmentSpread(ref spread) => {
+                if !visited.insert(&spread.name) {
+                    continue;
+                }
+                if let Some(f) = info.fragment(&spread.name) {
+                    collect_response_keys(&f.selections, info, visited, keys);
+                }
+            }
+            Selection::InlineFragment(ref inline) => {
+                collect_response_keys(&inline.selections, info, visited, keys);
+            }
+        }
+    }
+}
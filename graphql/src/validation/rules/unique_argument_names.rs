@@ -0,0 +1,36 @@
+use query::{Directive, Field, Value};
+use types::Name;
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+use std::collections::HashSet;
+
+#[derive(Default)]
+pub struct UniqueArgumentNames {
+    errors: Vec<Error>,
+}
+
+impl<'a> Visitor<'a> for UniqueArgumentNames {
+    fn enter_field(&mut self, field: &'a Field, _info: &TypeInfo<'a>) {
+        validate_unique_arguments(&field.args, &mut self.errors);
+    }
+
+    fn enter_directive(&mut self, directive: &'a Directive, _info: &TypeInfo<'a>) {
+        validate_unique_arguments(&directive.args, &mut self.errors);
+    }
+}
+
+rule_visitor!(UniqueArgumentNames);
+
+pub fn validate_unique_arguments(args: &[(Name, Value)], errors: &mut Vec<Error>) {
+    let mut names = HashSet::new();
+    for (name, value) in args {
+        if !names.insert(name) {
+            errors.push(Error::new(
+                Rule::UniqueArgumentNames,
+                format!("There can be only one argument named \"{}\".", name),
+                value.span,
+            ));
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use query::{Directive, Field, FragmentSpread, InlineFragment, Operation};
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+use std::collections::HashSet;
+
+#[derive(Default)]
+pub struct UniqueDirectivesPerLocation {
+    errors: Vec<Error>,
+}
+
+impl<'a> Visitor<'a> for UniqueDirectivesPerLocation {
+    fn enter_operation(&mut self, op: &'a Operation, _info: &TypeInfo<'a>) {
+        validate_unique_directives(&op.directives, &mut self.errors);
+    }
+
+    fn enter_field(&mut self, field: &'a Field, _info: &TypeInfo<'a>) {
+        validate_unique_directives(&field.directives, &mut self.errors);
+    }
+
+    fn enter_fragment_spread(&mut self, spread: &'a FragmentSpread, _info: &TypeInfo<'a>) {
+        validate_unique_directives(&spread.directives, &mut self.errors);
+    }
+
+    fn enter_inline_fragment(&mut self, inline: &'a InlineFragment, _info: &TypeInfo<'a>) {
+        validate_unique_directives(&inline.directives, &mut self.errors);
+    }
+}
+
+rule_visitor!(UniqueDirectivesPerLocation);
+
+// `directives` are the directives used at a single location.
+pub fn validate_unique_directives(directives: &[Directive], errors: &mut Vec<Error>) {
+    let mut names = HashSet::new();
+    for d in directives {
+        if !names.insert(&d.name) {
+            errors.push(Error::new(
+                Rule::UniqueDirectivesPerLocation,
+                format!("The directive \"@{}\" can only be used once at this location.", d.name),
+                d.span,
+            ));
+        }
+    }
+}
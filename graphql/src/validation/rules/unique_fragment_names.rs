@@ -0,0 +1,27 @@
+use query::Document;
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+use std::collections::HashSet;
+
+#[derive(Default)]
+pub struct UniqueFragmentNames {
+    errors: Vec<Error>,
+}
+
+impl<'a> Visitor<'a> for UniqueFragmentNames {
+    fn enter_document(&mut self, doc: &'a Document, _info: &TypeInfo<'a>) {
+        let mut names = HashSet::new();
+        for f in &doc.fragments {
+            if !names.insert(&f.name) {
+                self.errors.push(Error::new(
+                    Rule::UniqueFragmentNames,
+                    format!("There can be only one fragment named \"{}\".", f.name),
+                    f.span,
+                ));
+            }
+        }
+    }
+}
+
+rule_visitor!(UniqueFragmentNames);
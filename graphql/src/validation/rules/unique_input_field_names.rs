@@ -0,0 +1,49 @@
+use query::{Value, ValueKind, VariableDefinition};
+use types::Name;
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+use std::collections::HashSet;
+
+// Each field of an input object literal may only be given once.
+#[derive(Default)]
+pub struct UniqueInputFieldNames {
+    errors: Vec<Error>,
+}
+
+impl<'a> Visitor<'a> for UniqueInputFieldNames {
+    fn visit_variable_definition(&mut self, def: &'a VariableDefinition, _info: &TypeInfo<'a>) {
+        if let Some(ref default) = def.default {
+            validate_unique_input_fields(default, &mut self.errors);
+        }
+    }
+
+    fn visit_argument(&mut self, _name: &'a Name, value: &'a Value, _info: &TypeInfo<'a>) {
+        validate_unique_input_fields(value, &mut self.errors);
+    }
+}
+
+rule_visitor!(UniqueInputFieldNames);
+
+// Checks `value` and any objects nested in it.
+pub fn validate_unique_input_fields(value: &Value, errors: &mut Vec<Error>) {
+    match value.kind {
+        ValueKind::Object(ref fields) => {
+            let mut names = HashSet::new();
+            for (name, v) in fields {
+                if !names.insert(name) {
+                    errors.push(Error::new(
+                        Rule::UniqueInputFieldNames,
+                        format!("There can be only one input field named \"{}\".", name),
+                        v.span,
+                    ));
+                }
+                validate_unique_input_fields(v, errors);
+            }
+        }
+        ValueKind::Array(ref values) => for v in values {
+            validate_unique_input_fields(v, errors);
+        },
+        _ => {}
+    }
+}
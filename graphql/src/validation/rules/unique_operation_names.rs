@@ -0,0 +1,29 @@
+use query::Document;
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+use std::collections::HashSet;
+
+#[derive(Default)]
+pub struct UniqueOperationNames {
+    errors: Vec<Error>,
+}
+
+impl<'a> Visitor<'a> for UniqueOperationNames {
+    fn enter_document(&mut self, doc: &'a Document, _info: &TypeInfo<'a>) {
+        let mut names = HashSet::new();
+        for op in &doc.operations {
+            if let Some(ref name) = op.name {
+                if !names.insert(name) {
+                    self.errors.push(Error::new(
+                        Rule::UniqueOperationNames,
+                        format!("There can be only one operation named \"{}\".", name),
+                        op.field.span,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+rule_visitor!(UniqueOperationNames);
@@ -0,0 +1,27 @@
+use query::Operation;
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+use std::collections::HashSet;
+
+#[derive(Default)]
+pub struct UniqueVariableNames {
+    errors: Vec<Error>,
+}
+
+impl<'a> Visitor<'a> for UniqueVariableNames {
+    fn enter_operation(&mut self, op: &'a Operation, _info: &TypeInfo<'a>) {
+        let mut names = HashSet::new();
+        for def in &op.variables {
+            if !names.insert(&def.name) {
+                self.errors.push(Error::new(
+                    Rule::UniqueVariableNames,
+                    format!("There can be only one variable named \"${}\".", def.name),
+                    def.span,
+                ));
+            }
+        }
+    }
+}
+
+rule_visitor!(UniqueVariableNames);
@@ -0,0 +1,138 @@
+use query::{Document, Fragment, FragmentSpread, Operation, Value, ValueKind};
+use schema::{self, Item, Schema, TypeKind};
+use types::{Name, Span};
+use visit::{walk_document, TypeInfo, Visitor};
+
+use std::collections::{HashMap, HashSet};
+use std::mem;
+
+// The fragments spread and the variables used directly by each operation and
+// fragment of a document, so that rules can follow the spreads from an
+// operation to everything it uses. Not a rule itself.
+pub struct Usages<'a> {
+    pub operations: Vec<(&'a Operation, Scope<'a>)>,
+    fragments: HashMap<&'a Name, Scope<'a>>,
+}
+
+#[derive(Default)]
+pub struct Scope<'a> {
+    spreads: Vec<&'a Name>,
+    variables: Vec<VariableUsage<'a>>,
+}
+
+pub struct VariableUsage<'a> {
+    pub name: &'a Name,
+    pub span: Span,
+    // The type expected where the variable is used, `None` if it is unknown.
+    pub ty: Option<schema::Type>,
+}
+
+impl<'a> Usages<'a> {
+    pub fn new(doc: &'a Document, schema: &'a Schema) -> Usages<'a> {
+        let mut collector = Collector {
+            usages: Usages {
+                operations: vec![],
+                fragments: HashMap::new(),
+            },
+            current: Scope::default(),
+        };
+        walk_document(&mut collector, doc, schema);
+        collector.usages
+    }
+
+    // The fragments spread by `scope`, directly or through other fragments.
+    pub fn fragments(&self, scope: &Scope<'a>) -> Vec<&'a Name> {
+        let mut result = vec![];
+        let mut seen = HashSet::new();
+        let mut stack: Vec<_> = scope.spreads.iter().rev().cloned().collect();
+        while let Some(name) = stack.pop() {
+            if !seen.insert(name) {
+                continue;
+            }
+            result.push(name);
+            if let Some(s) = self.fragments.get(name) {
+                stack.extend(s.spreads.iter().rev());
+            }
+        }
+        result
+    }
+
+    // The variables used by `scope` and by the fragments it spreads.
+    pub fn variables<'b>(&'b self, scope: &'b Scope<'a>) -> Vec<&'b VariableUsage<'a>> {
+        let mut result: Vec<_> = scope.variables.iter().collect();
+        for name in self.fragments(scope) {
+            if let Some(s) = self.fragments.get(name) {
+                result.extend(&s.variables);
+            }
+        }
+        result
+    }
+}
+
+struct Collector<'a> {
+    usages: Usages<'a>,
+    // The operation or fragment being walked.
+    current: Scope<'a>,
+}
+
+impl<'a> Visitor<'a> for Collector<'a> {
+    fn leave_operation(&mut self, op: &'a Operation, _info: &TypeInfo<'a>) {
+        let scope = mem::take(&mut self.current);
+        self.usages.operations.push((op, scope));
+    }
+
+    // If there are several fragments with the same name, the first is used.
+    fn leave_fragment(&mut self, fragment: &'a Fragment, _info: &TypeInfo<'a>) {
+        let scope = mem::take(&mut self.current);
+        self.usages.fragments.entry(&fragment.name).or_insert(scope);
+    }
+
+    fn enter_fragment_spread(&mut self, spread: &'a FragmentSpread, _info: &TypeInfo<'a>) {
+        self.current.spreads.push(&spread.name);
+    }
+
+    fn visit_argument(&mut self, _name: &'a Name, value: &'a Value, info: &TypeInfo<'a>) {
+        let ty = info.argument_def().map(|a| &a.ty);
+        collect_variables(value, ty, info.schema(), &mut self.current.variables);
+    }
+}
+
+// `ty` is the type expected for `value`.
+fn collect_variables<'a>(
+    value: &'a Value,
+    ty: Option<&schema::Type>,
+    schema: &Schema,
+    usages: &mut Vec<VariableUsage<'a>>,
+) {
+    match value.kind {
+        ValueKind::Variable(ref name) => usages.push(VariableUsage {
+            name,
+            span: value.span,
+            ty: ty.cloned(),
+        }),
+        ValueKind::Array(ref values) => {
+            let el_ty = match ty.map(|t| &t.kind) {
+                Some(TypeKind::Array(el_ty)) => Some(&**el_ty),
+                _ => None,
+            };
+            for v in values {
+                collect_variables(v, el_ty, schema, usages);
+            }
+        }
+        ValueKind::Object(ref fields) => {
+            let obj = match ty.map(|t| &t.kind) {
+                Some(TypeKind::Name(n)) => match schema.items.get(n) {
+                    Some(Item::InputObject(obj)) => Some(obj),
+                    _ => None,
+                },
+                _ => None,
+            };
+            for (name, v) in fields {
+                let field_ty = obj.and_then(|o| o.fields.iter().find(|f| &f.name == name))
+                    .map(|f| &f.ty);
+                collect_variables(v, field_ty, schema, usages);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,163 @@
+use printer::{self, Style};
+use query::{Value, ValueKind, VariableDefinition};
+use schema::{self, Item, Schema, TypeKind};
+use types::{Name, Span};
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+use std::convert::TryFrom;
+
+// Argument values and the default values of variables must be valid for their
+// types, including the fields of input objects. Variables used as values are
+// checked by `VariablesInAllowedPosition`.
+#[derive(Default)]
+pub struct ValuesOfCorrectType {
+    errors: Vec<Error>,
+}
+
+impl<'a> Visitor<'a> for ValuesOfCorrectType {
+    fn visit_variable_definition(&mut self, def: &'a VariableDefinition, info: &TypeInfo<'a>) {
+        if let Some(ref default) = def.default {
+            validate_value(default, &def.ty, info.schema(), true, &mut self.errors);
+        }
+    }
+
+    fn visit_argument(&mut self, _name: &'a Name, value: &'a Value, info: &TypeInfo<'a>) {
+        if let Some(arg) = info.argument_def() {
+            validate_value(value, &arg.ty, info.schema(), false, &mut self.errors);
+        }
+    }
+}
+
+rule_visitor!(ValuesOfCorrectType);
+
+// Checks that `value` can be used where a `ty` is expected. Variables may be
+// used anywhere, unless `is_const` (e.g., for a default value).
+pub fn validate_value(value: &Value, ty: &schema::Type, schema: &Schema, is_const: bool, errors: &mut Vec<Error>) {
+    if let ValueKind::Variable(ref n) = value.kind {
+        if is_const {
+            errors.push(Error::new(
+                Rule::ValuesOfCorrectType,
+                format!("Expected a constant value of type \"{}\", found ${}.", ty, n),
+                value.span,
+            ));
+        }
+        return;
+    }
+
+    if let ValueKind::Null = value.kind {
+        if !ty.nullable {
+            type_mismatch(value, ty, errors);
+        }
+    }
+
+    match ty.kind {
+        TypeKind::String => match value.kind {
+            ValueKind::Null | ValueKind::String(_) => {}
+            _ => type_mismatch(value, ty, errors),
+        },
+        // IDs may be written as either strings or integers.
+        TypeKind::Id => match value.kind {
+            ValueKind::Null | ValueKind::String(_) | ValueKind::Int(_) => {}
+            _ => type_mismatch(value, ty, errors),
+        },
+        TypeKind::Int => match value.kind {
+            ValueKind::Null => {}
+            ValueKind::Int(n) if i32::try_from(n).is_ok() => {}
+            _ => type_mismatch(value, ty, errors),
+        },
+        // Int literals may be used where a Float is expected.
+        TypeKind::Float => match value.kind {
+            ValueKind::Null | ValueKind::Float(_) | ValueKind::Int(_) => {}
+            _ => type_mismatch(value, ty, errors),
+        },
+        TypeKind::Boolean => match value.kind {
+            ValueKind::Null | ValueKind::Boolean(_) => {}
+            _ => type_mismatch(value, ty, errors),
+        },
+        TypeKind::Name(ref n) => match (&value.kind, schema.items.get(n)) {
+            (&ValueKind::Null, _) => {}
+            // Unknown types are reported elsewhere, and custom scalars check
+            // their own values when they are parsed.
+            (_, None) | (_, Some(&Item::Scalar(_))) => {}
+            (ValueKind::Object(fields), Some(Item::InputObject(obj))) => {
+                validate_input_object(fields, n, obj, value.span, schema, is_const, errors);
+            }
+            (ValueKind::Enum(variant), Some(Item::Enum(e))) => {
+                if !e.variants.iter().any(|v| &v.name == variant) {
+                    errors.push(Error::new(
+                        Rule::ValuesOfCorrectType,
+                        format!("Value \"{}\" does not exist in \"{}\" enum.", variant, n),
+                        value.span,
+                    ));
+                }
+            }
+            // Enum values must be written as names, not strings.
+            (_, Some(&Item::Enum(_))) => errors.push(Error::new(
+                Rule::ValuesOfCorrectType,
+                format!(
+                    "Enum \"{}\" cannot represent non-enum value: {}.",
+                    n,
+                    printer::print_value(value, Style::Pretty)
+                ),
+                value.span,
+            )),
+            _ => type_mismatch(value, ty, errors),
+        },
+        TypeKind::Array(ref el_ty) => match value.kind {
+            ValueKind::Null => {}
+            ValueKind::Array(ref values) => for v in values {
+                validate_value(v, el_ty, schema, is_const, errors);
+            },
+            _ => type_mismatch(value, ty, errors),
+        },
+    }
+}
+
+fn type_mismatch(value: &Value, ty: &schema::Type, errors: &mut Vec<Error>) {
+    errors.push(Error::new(
+        Rule::ValuesOfCorrectType,
+        format!(
+            "Expected value of type \"{}\", found {}.",
+            ty,
+            printer::print_value(value, Style::Pretty)
+        ),
+        value.span,
+    ));
+}
+
+// `ty_name` is the name of `ty`, `span` is the span of the object literal.
+// Duplicate fields are reported by `UniqueInputFieldNames`.
+fn validate_input_object(
+    fields: &[(Name, Value)],
+    ty_name: &Name,
+    ty: &schema::InputObject,
+    span: Span,
+    schema: &Schema,
+    is_const: bool,
+    errors: &mut Vec<Error>,
+) {
+    for (name, value) in fields {
+        match ty.fields.iter().find(|f| &f.name == name) {
+            Some(field) => validate_value(value, &field.ty, schema, is_const, errors),
+            None => errors.push(Error::new(
+                Rule::ValuesOfCorrectType,
+                format!("Field \"{}\" is not defined by type \"{}\".", name, ty_name),
+                value.span,
+            )),
+        }
+    }
+
+    for f in &ty.fields {
+        if !f.ty.nullable && !fields.iter().any(|(name, _)| name == &f.name) {
+            errors.push(Error::new(
+                Rule::ValuesOfCorrectType,
+                format!(
+                    "Field \"{}.{}\" of required type \"{}\" was not provided.",
+                    ty_name, f.name, f.ty
+                ),
+                span,
+            ));
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use query::VariableDefinition;
+use schema::Item;
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+// Variables may only have scalar, enum, or input object types. Unknown types
+// are reported by `KnownTypeNames`.
+#[derive(Default)]
+pub struct VariablesAreInputTypes {
+    errors: Vec<Error>,
+}
+
+impl<'a> Visitor<'a> for VariablesAreInputTypes {
+    fn visit_variable_definition(&mut self, def: &'a VariableDefinition, info: &TypeInfo<'a>) {
+        let item = match def.ty.named_type() {
+            Some(name) => info.schema().items.get(name),
+            None => None,
+        };
+        match item {
+            Some(&Item::Enum(_)) | Some(&Item::InputObject(_)) | Some(&Item::Scalar(_)) | None => {}
+            Some(_) => self.errors.push(Error::new(
+                Rule::VariablesAreInputTypes,
+                format!("Variable \"${}\" cannot be non-input type \"{}\".", def.name, def.ty),
+                def.ty.span,
+            )),
+        }
+    }
+}
+
+rule_visitor!(VariablesAreInputTypes);
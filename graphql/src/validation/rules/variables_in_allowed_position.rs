@@ -0,0 +1,72 @@
+use query::{Document, ValueKind, VariableDefinition};
+use schema::{Type, TypeKind};
+use validation::{Error, Rule};
+use visit::{TypeInfo, Visitor};
+
+use super::usages::Usages;
+
+// A variable's type must be compatible with each position it is used in.
+// Undefined variables are reported by `NoUndefinedVariables`.
+#[derive(Default)]
+pub struct VariablesInAllowedPosition {
+    errors: Vec<Error>,
+}
+
+impl<'a> Visitor<'a> for VariablesInAllowedPosition {
+    fn enter_document(&mut self, doc: &'a Document, info: &TypeInfo<'a>) {
+        let usages = Usages::new(doc, info.schema());
+        for &(op, ref scope) in &usages.operations {
+            for usage in usages.variables(scope) {
+                let def = op.variables.iter().find(|def| &def.name == usage.name);
+                let (def, ty) = match (def, usage.ty.as_ref()) {
+                    (Some(def), Some(ty)) => (def, ty),
+                    _ => continue,
+                };
+                if !variable_compatible(def, ty) {
+                    self.errors.push(Error::new(
+                        Rule::VariablesInAllowedPosition,
+                        format!(
+                            "Variable \"${}\" of type \"{}\" used in position expecting type \"{}\".",
+                            usage.name, def.ty, ty
+                        ),
+                        usage.span,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+rule_visitor!(VariablesInAllowedPosition);
+
+// Can the variable `def` be used where a value of type `ty` is expected?
+fn variable_compatible(def: &VariableDefinition, ty: &Type) -> bool {
+    fn compatible(var_ty: &Type, ty: &Type) -> bool {
+        if var_ty.nullable && !ty.nullable {
+            return false;
+        }
+        match (&var_ty.kind, &ty.kind) {
+            (&TypeKind::String, &TypeKind::String)
+            | (&TypeKind::Id, &TypeKind::Id)
+            | (&TypeKind::Int, &TypeKind::Int)
+            | (&TypeKind::Float, &TypeKind::Float)
+            | (&TypeKind::Boolean, &TypeKind::Boolean) => true,
+            (TypeKind::Name(a), TypeKind::Name(b)) => a == b,
+            (TypeKind::Array(a), TypeKind::Array(b)) => compatible(a, b),
+            _ => false,
+        }
+    }
+
+    // A nullable variable with a non-null default can be used where a non-null
+    // value is expected.
+    match def.default {
+        Some(ref d) if d.kind != ValueKind::Null && def.ty.nullable => {
+            let var_ty = Type {
+                nullable: false,
+                ..def.ty.clone()
+            };
+            compatible(&var_ty, ty)
+        }
+        _ => compatible(&def.ty, ty),
+    }
+}
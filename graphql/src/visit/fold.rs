@@ -0,0 +1,227 @@
+use query::{Directive, Document, Field, Fragment, FragmentSpread, InlineFragment, Operation, Selection, Value,
+            ValueKind, VariableDefinition};
+use types::Name;
+
+// Rewrites a query document. Each method defaults to the free function of the
+// same name, which folds the node's children; an implementation overrides the
+// methods for the nodes it changes and calls the free function to keep
+// recursing.
+//
+// Folding does not use the schema, so a rewritten document should be validated
+// again before it is executed.
+pub trait Fold {
+    fn fold_document(&mut self, doc: Document) -> Document {
+        fold_document(self, doc)
+    }
+
+    fn fold_operation(&mut self, op: Operation) -> Operation {
+        fold_operation(self, op)
+    }
+
+    fn fold_fragment(&mut self, fragment: Fragment) -> Fragment {
+        fold_fragment(self, fragment)
+    }
+
+    fn fold_variable_definition(&mut self, def: VariableDefinition) -> VariableDefinition {
+        fold_variable_definition(self, def)
+    }
+
+    // Override this to add or remove selections.
+    fn fold_selections(&mut self, selections: Vec<Selection>) -> Vec<Selection> {
+        fold_selections(self, selections)
+    }
+
+    fn fold_selection(&mut self, selection: Selection) -> Selection {
+        fold_selection(self, selection)
+    }
+
+    fn fold_field(&mut self, field: Field) -> Field {
+        fold_field(self, field)
+    }
+
+    fn fold_fragment_spread(&mut self, spread: FragmentSpread) -> FragmentSpread {
+        fold_fragment_spread(self, spread)
+    }
+
+    fn fold_inline_fragment(&mut self, inline: InlineFragment) -> InlineFragment {
+        fold_inline_fragment(self, inline)
+    }
+
+    fn fold_directive(&mut self, directive: Directive) -> Directive {
+        fold_directive(self, directive)
+    }
+
+    // An argument of a field or directive, or a field of an input object.
+    fn fold_argument(&mut self, arg: (Name, Value)) -> (Name, Value) {
+        fold_argument(self, arg)
+    }
+
+    fn fold_value(&mut self, value: Value) -> Value {
+        fold_value(self, value)
+    }
+}
+
+pub fn fold_document<F: Fold + ?Sized>(folder: &mut F, doc: Document) -> Document {
+    Document {
+        operations: doc.operations.into_iter().map(|op| folder.fold_operation(op)).collect(),
+        fragments: doc.fragments.into_iter().map(|f| folder.fold_fragment(f)).collect(),
+    }
+}
+
+// The root field of the operation is not passed to `fold_field`, only its
+// selections are folded.
+pub fn fold_operation<F: Fold + ?Sized>(folder: &mut F, op: Operation) -> Operation {
+    let variables = op.variables
+        .into_iter()
+        .map(|def| folder.fold_variable_definition(def))
+        .collect();
+    let selections = folder.fold_selections(op.field.selections);
+    Operation {
+        variables,
+        field: Field {
+            selections,
+            ..op.field
+        },
+        ..op
+    }
+}
+
+pub fn fold_fragment<F: Fold + ?Sized>(folder: &mut F, fragment: Fragment) -> Fragment {
+    Fragment {
+        selections: folder.fold_selections(fragment.selections),
+        ..fragment
+    }
+}
+
+pub fn fold_variable_definition<F: Fold + ?Sized>(folder: &mut F, def: VariableDefinition) -> VariableDefinition {
+    VariableDefinition {
+        default: def.default.map(|v| folder.fold_value(v)),
+        ..def
+    }
+}
+
+pub fn fold_selections<F: Fold + ?Sized>(folder: &mut F, selections: Vec<Selection>) -> Vec<Selection> {
+    selections.into_iter().map(|s| folder.fold_selection(s)).collect()
+}
+
+pub fn fold_selection<F: Fold + ?Sized>(folder: &mut F, selection: Selection) -> Selection {
+    match selection {
+        Selection::Field(f) => Selection::Field(folder.fold_field(f)),
+        Selection::FragmentSpread(s) => Selection::FragmentSpread(folder.fold_fragment_spread(s)),
+        Selection::InlineFragment(i) => Selection::InlineFragment(folder.fold_inline_fragment(i)),
+    }
+}
+
+pub fn fold_field<F: Fold + ?Sized>(folder: &mut F, field: Field) -> Field {
+    let args = field.args.into_iter().map(|a| folder.fold_argument(a)).collect();
+    let directives = field.directives.into_iter().map(|d| folder.fold_directive(d)).collect();
+    let selections = folder.fold_selections(field.selections);
+    Field {
+        args,
+        directives,
+        selections,
+        ..field
+    }
+}
+
+pub fn fold_fragment_spread<F: Fold + ?Sized>(folder: &mut F, spread: FragmentSpread) -> FragmentSpread {
+    FragmentSpread {
+        directives: spread.directives.into_iter().map(|d| folder.fold_directive(d)).collect(),
+        ..spread
+    }
+}
+
+pub fn fold_inline_fragment<F: Fold + ?Sized>(folder: &mut F, inline: InlineFragment) -> InlineFragment {
+    let directives = inline.directives.into_iter().map(|d| folder.fold_directive(d)).collect();
+    let selections = folder.fold_selections(inline.selections);
+    InlineFragment {
+        directives,
+        selections,
+        ..inline
+    }
+}
+
+pub fn fold_directive<F: Fold + ?Sized>(folder: &mut F, directive: Directive) -> Directive {
+    Directive {
+        args: directive.args.into_iter().map(|a| folder.fold_argument(a)).collect(),
+        ..directive
+    }
+}
+
+pub fn fold_argument<F: Fold + ?Sized>(folder: &mut F, (name, value): (Name, Value)) -> (Name, Value) {
+    (name, folder.fold_value(value))
+}
+
+pub fn fold_value<F: Fold + ?Sized>(folder: &mut F, value: Value) -> Value {
+    let kind = match value.kind {
+        ValueKind::Array(values) => ValueKind::Array(values.into_iter().map(|v| folder.fold_value(v)).collect()),
+        ValueKind::Object(fields) => ValueKind::Object(fields.into_iter().map(|f| folder.fold_argument(f)).collect()),
+        kind => kind,
+    };
+    Value { kind, ..value }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::parse_query::parse_query;
+    use printer::Style;
+    use std::collections::HashMap;
+
+    // Removes selections skipped with a literal `@skip(if: true)`.
+    struct StripSkipped;
+
+    impl Fold for StripSkipped {
+        fn fold_selections(&mut self, selections: Vec<Selection>) -> Vec<Selection> {
+            let selections = selections
+                .into_iter()
+                .filter(|s| {
+                    !s.directives().iter().any(|d| {
+                        d.name.0 == "skip" && d.find_arg(&Name("if".to_owned())).map(|v| &v.kind)
+                            == Some(&ValueKind::Boolean(true))
+                    })
+                })
+                .collect();
+            fold_selections(self, selections)
+        }
+    }
+
+    // Replaces variables with their values.
+    struct Inline(HashMap<String, Value>);
+
+    impl Fold for Inline {
+        fn fold_value(&mut self, value: Value) -> Value {
+            if let ValueKind::Variable(ref n) = value.kind {
+                if let Some(v) = self.0.get(&n.0) {
+                    return v.clone();
+                }
+            }
+            fold_value(self, value)
+        }
+    }
+
+    #[test]
+    fn test_fold() {
+        let doc = parse_query(
+            "query Q($id: ID, $n: Int) {
+                hero(id: $id) {
+                    name @skip(if: true)
+                    friends(first: $n, filter: {ids: [$id]}) @skip(if: false) { name }
+                    ... on Human @skip(if: true) { height }
+                    ...F
+                }
+            }
+            fragment F on Character { id @skip(if: true), appearsIn }",
+        ).unwrap();
+
+        let doc = StripSkipped.fold_document(doc);
+        let mut vars = HashMap::new();
+        vars.insert("id".to_owned(), Value::new(ValueKind::String("1000".to_owned()), ::types::Span::dummy()));
+        let doc = Inline(vars).fold_document(doc);
+        assert_eq!(
+            doc.print(Style::Minified),
+            "query Q($id:ID,$n:Int){hero(id:\"1000\"){friends(first:$n,filter:{ids:[\"1000\"]})@skip(if:false){name},\
+             ...F}}fragment F on Character{appearsIn}"
+        );
+    }
+}
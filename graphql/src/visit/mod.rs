@@ -0,0 +1,15 @@
+// Traversals of query documents and schemas, so that passes such as lints or
+// cost analysis can be written without recursing over the ASTs by hand.
+//
+// `Visitor` walks a query document with type information from a schema,
+// `Fold` rewrites a query document, and `SchemaVisitor` walks a schema.
+
+mod fold;
+mod visit_idl;
+mod visit_query;
+
+pub use self::fold::{fold_argument, fold_directive, fold_document, fold_field, fold_fragment, fold_fragment_spread,
+                     fold_inline_fragment, fold_operation, fold_selection, fold_selections, fold_value,
+                     fold_variable_definition, Fold};
+pub use self::visit_idl::{walk_schema, SchemaVisitor};
+pub use self::visit_query::{walk_document, TypeInfo, Visitor};
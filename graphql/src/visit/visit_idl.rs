@@ -0,0 +1,133 @@
+use query;
+use schema::{Argument, Directive, DirectiveLocation, Field, Item, Schema, Variant};
+use types::Name;
+
+// Like `Visitor`, but for schemas. Every method has an empty default.
+pub trait SchemaVisitor<'a> {
+    fn visit_directive_definition(&mut self, _directive: &'a Directive) {}
+
+    // `name` is the item's name, or `schema` for the schema definition.
+    fn enter_item(&mut self, _name: &'a Name, _item: &'a Item) {}
+    fn leave_item(&mut self, _name: &'a Name, _item: &'a Item) {}
+
+    // A field of an object, interface, or input object. `parent` is the name
+    // of the item which has the field.
+    fn enter_field(&mut self, _parent: &'a Name, _field: &'a Field) {}
+    fn leave_field(&mut self, _parent: &'a Name, _field: &'a Field) {}
+
+    // An argument of a field or of a directive definition.
+    fn visit_argument(&mut self, _arg: &'a Argument) {}
+
+    fn visit_variant(&mut self, _parent: &'a Name, _variant: &'a Variant) {}
+
+    // The directives used on a single part of the schema, e.g., on one field.
+    // The default visits each directive in turn.
+    fn visit_directives(&mut self, directives: &'a [query::Directive], location: DirectiveLocation) {
+        for d in directives {
+            self.visit_directive(d, location);
+        }
+    }
+
+    fn visit_directive(&mut self, _directive: &'a query::Directive, _location: DirectiveLocation) {}
+}
+
+// Directive definitions are visited first, then items in order of their names.
+pub fn walk_schema<'a, V: SchemaVisitor<'a>>(visitor: &mut V, schema: &'a Schema) {
+    for d in &schema.directives {
+        visitor.visit_directive_definition(d);
+        walk_args(visitor, &d.args);
+    }
+
+    let mut items: Vec<_> = schema.items.iter().collect();
+    items.sort_by(|a, b| (a.0).0.cmp(&(b.0).0));
+    for (name, item) in items {
+        visitor.enter_item(name, item);
+        visitor.visit_directives(item.directives(), item.directive_location());
+        match *item {
+            Item::Enum(ref e) => for v in &e.variants {
+                visitor.visit_variant(name, v);
+                visitor.visit_directives(&v.directives, DirectiveLocation::EnumValue);
+            },
+            Item::InputObject(ref i) => for f in &i.fields {
+                visitor.enter_field(name, f);
+                visitor.visit_directives(&f.directives, DirectiveLocation::InputFieldDefinition);
+                visitor.leave_field(name, f);
+            },
+            _ => for f in item.fields() {
+                visitor.enter_field(name, f);
+                walk_args(visitor, &f.args);
+                visitor.visit_directives(&f.directives, DirectiveLocation::FieldDefinition);
+                visitor.leave_field(name, f);
+            },
+        }
+        visitor.leave_item(name, item);
+    }
+}
+
+fn walk_args<'a, V: SchemaVisitor<'a>>(visitor: &mut V, args: &'a [Argument]) {
+    for a in args {
+        visitor.visit_argument(a);
+        visitor.visit_directives(&a.directives, DirectiveLocation::ArgumentDefinition);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::parse_idl::parse_schema;
+
+    // Counts fields and collects the directives used in a schema.
+    #[derive(Default)]
+    struct Counter {
+        fields: usize,
+        args: usize,
+        variants: usize,
+        directives: Vec<(String, DirectiveLocation)>,
+    }
+
+    impl<'a> SchemaVisitor<'a> for Counter {
+        fn enter_field(&mut self, _parent: &'a Name, _field: &'a Field) {
+            self.fields += 1;
+        }
+        fn visit_argument(&mut self, _arg: &'a Argument) {
+            self.args += 1;
+        }
+        fn visit_variant(&mut self, _parent: &'a Name, _variant: &'a Variant) {
+            self.variants += 1;
+        }
+        fn visit_directive(&mut self, d: &'a query::Directive, location: DirectiveLocation) {
+            self.directives.push((d.name.0.clone(), location));
+        }
+    }
+
+    #[test]
+    fn test_walk_schema() {
+        let schema = parse_schema(
+            "directive @cost(value: Int! @tag) on FIELD_DEFINITION | OBJECT
+            type Query @cost(value: 1) {
+                hero(episode: Episode @tag): String @cost(value: 2)
+                droid: String @deprecated
+            }
+            enum Episode { NEWHOPE, JEDI @deprecated }
+            input Review { stars: Int @tag }",
+        ).unwrap();
+
+        let mut counter = Counter::default();
+        walk_schema(&mut counter, &schema);
+        assert_eq!(counter.fields, 3);
+        assert_eq!(counter.args, 2);
+        assert_eq!(counter.variants, 2);
+        assert_eq!(
+            counter.directives,
+            vec![
+                ("tag".to_owned(), DirectiveLocation::ArgumentDefinition),
+                ("deprecated".to_owned(), DirectiveLocation::EnumValue),
+                ("cost".to_owned(), DirectiveLocation::Object),
+                ("tag".to_owned(), DirectiveLocation::ArgumentDefinition),
+                ("cost".to_owned(), DirectiveLocation::FieldDefinition),
+                ("deprecated".to_owned(), DirectiveLocation::FieldDefinition),
+                ("tag".to_owned(), DirectiveLocation::InputFieldDefinition),
+            ]
+        );
+    }
+}
@@ -0,0 +1,324 @@
+use query::{Directive, Document, Field, Fragment, FragmentSpread, InlineFragment, Operation, Selection, Value,
+            VariableDefinition};
+use schema::{self, Schema};
+use types::Name;
+
+// Every method has an empty default, so a visitor only implements the hooks it
+// needs. `enter_` methods are called before a node's children are visited and
+// `leave_` methods after.
+//
+// Fragment spreads are not followed into the fragment, each fragment
+// definition is visited once in its own right.
+pub trait Visitor<'a> {
+    fn enter_document(&mut self, _doc: &'a Document, _info: &TypeInfo<'a>) {}
+    fn leave_document(&mut self, _doc: &'a Document, _info: &TypeInfo<'a>) {}
+
+    fn enter_operation(&mut self, _op: &'a Operation, _info: &TypeInfo<'a>) {}
+    fn leave_operation(&mut self, _op: &'a Operation, _info: &TypeInfo<'a>) {}
+
+    fn enter_fragment(&mut self, _fragment: &'a Fragment, _info: &TypeInfo<'a>) {}
+    fn leave_fragment(&mut self, _fragment: &'a Fragment, _info: &TypeInfo<'a>) {}
+
+    fn visit_variable_definition(&mut self, _def: &'a VariableDefinition, _info: &TypeInfo<'a>) {}
+
+    fn enter_field(&mut self, _field: &'a Field, _info: &TypeInfo<'a>) {}
+    fn leave_field(&mut self, _field: &'a Field, _info: &TypeInfo<'a>) {}
+
+    fn enter_fragment_spread(&mut self, _spread: &'a FragmentSpread, _info: &TypeInfo<'a>) {}
+    fn leave_fragment_spread(&mut self, _spread: &'a FragmentSpread, _info: &TypeInfo<'a>) {}
+
+    fn enter_inline_fragment(&mut self, _inline: &'a InlineFragment, _info: &TypeInfo<'a>) {}
+    fn leave_inline_fragment(&mut self, _inline: &'a InlineFragment, _info: &TypeInfo<'a>) {}
+
+    fn enter_directive(&mut self, _directive: &'a Directive, _info: &TypeInfo<'a>) {}
+    fn leave_directive(&mut self, _directive: &'a Directive, _info: &TypeInfo<'a>) {}
+
+    // An argument of a field or directive. Values are not walked, since most
+    // visitors only care about the value as a whole.
+    fn visit_argument(&mut self, _name: &'a Name, _value: &'a Value, _info: &TypeInfo<'a>) {}
+}
+
+// Where a visitor is in a document, in terms of the schema. Anything which
+// can't be found in the schema (e.g., a field which doesn't exist) is `None`,
+// validation reports those errors.
+pub struct TypeInfo<'a> {
+    schema: &'a Schema,
+    fragments: &'a [Fragment],
+    // Built-in and schema directives.
+    directives: Vec<schema::Directive>,
+    // The type of each enclosing selection set, innermost last.
+    parent_types: Vec<Option<&'a Name>>,
+    // The definition of each enclosing field, innermost last.
+    field_defs: Vec<Option<&'a schema::Field>>,
+    // The response keys of the enclosing fields.
+    path: Vec<&'a Name>,
+    directive: Option<&'a Name>,
+    argument: Option<&'a Name>,
+}
+
+impl<'a> TypeInfo<'a> {
+    fn new(schema: &'a Schema, fragments: &'a [Fragment]) -> TypeInfo<'a> {
+        TypeInfo {
+            schema,
+            fragments,
+            directives: schema::builtin_directives()
+                .into_iter()
+                .chain(schema.directives.iter().cloned())
+                .collect(),
+            parent_types: vec![],
+            field_defs: vec![],
+            path: vec![],
+            directive: None,
+            argument: None,
+        }
+    }
+
+    pub fn schema(&self) -> &'a Schema {
+        self.schema
+    }
+
+    pub fn fragment(&self, name: &Name) -> Option<&'a Fragment> {
+        self.fragments.iter().find(|f| &f.name == name)
+    }
+
+    // The type whose fields are being selected, e.g., `Human` for the fields
+    // of a fragment on `Human`.
+    pub fn parent_type(&self) -> Option<&'a Name> {
+        self.parent_types.last().and_then(|t| *t)
+    }
+
+    pub fn parent_item(&self) -> Option<&'a schema::Item> {
+        let schema = self.schema;
+        self.parent_type().and_then(|n| schema.items.get(n))
+    }
+
+    // The definition of the innermost field being visited.
+    pub fn field_def(&self) -> Option<&'a schema::Field> {
+        self.field_defs.last().and_then(|f| *f)
+    }
+
+    // The definition of the directive being visited.
+    pub fn directive_def(&self) -> Option<&schema::Directive> {
+        self.find_directive(self.directive?)
+    }
+
+    // A built-in directive, or one defined by the schema.
+    pub fn find_directive(&self, name: &Name) -> Option<&schema::Directive> {
+        self.directives.iter().find(|d| &d.name == name)
+    }
+
+    // The definition of the argument being visited, of either a field or a
+    // directive.
+    pub fn argument_def(&self) -> Option<&schema::Argument> {
+        let name = self.argument?;
+        let args = match self.directive {
+            Some(_) => &self.directive_def()?.args,
+            None => &self.field_def()?.args,
+        };
+        args.iter().find(|a| &a.name == name)
+    }
+
+    // The response keys of the fields from the root of the operation (or
+    // fragment) to the current field.
+    pub fn path(&self) -> &[&'a Name] {
+        &self.path
+    }
+
+    fn find_field(&self, name: &Name) -> Option<&'a schema::Field> {
+        self.parent_item()?.fields().iter().find(|f| &f.name == name)
+    }
+}
+
+pub fn walk_document<'a, V: Visitor<'a>>(visitor: &mut V, doc: &'a Document, schema: &'a Schema) {
+    let mut info = TypeInfo::new(schema, &doc.fragments);
+    visitor.enter_document(doc, &info);
+    for op in &doc.operations {
+        walk_operation(visitor, op, &mut info);
+    }
+    for f in &doc.fragments {
+        info.parent_types.push(Some(&f.type_condition));
+        visitor.enter_fragment(f, &info);
+        walk_selections(visitor, &f.selections, &mut info);
+        visitor.leave_fragment(f, &info);
+        info.parent_types.pop();
+    }
+    visitor.leave_document(doc, &info);
+}
+
+fn walk_operation<'a, V: Visitor<'a>>(visitor: &mut V, op: &'a Operation, info: &mut TypeInfo<'a>) {
+    // The root type is the type of the schema's field for the kind of
+    // operation, e.g., `query: Query`.
+    let schema = info.schema;
+    let root = schema
+        .items
+        .get(&Name(schema::SCHEMA_NAME.to_owned()))
+        .and_then(|s| s.fields().iter().find(|f| f.name == op.field.name))
+        .and_then(|f| f.ty.named_type());
+    info.parent_types.push(root);
+    visitor.enter_operation(op, info);
+    for def in &op.variables {
+        visitor.visit_variable_definition(def, info);
+    }
+    walk_directives(visitor, &op.directives, info);
+    walk_selections(visitor, &op.field.selections, info);
+    visitor.leave_operation(op, info);
+    info.parent_types.pop();
+}
+
+fn walk_selections<'a, V: Visitor<'a>>(visitor: &mut V, selections: &'a [Selection], info: &mut TypeInfo<'a>) {
+    for s in selections {
+        match *s {
+            Selection::Field(ref f) => walk_field(visitor, f, info),
+            Selection::FragmentSpread(ref spread) => {
+                visitor.enter_fragment_spread(spread, info);
+                walk_directives(visitor, &spread.directives, info);
+                visitor.leave_fragment_spread(spread, info);
+            }
+            Selection::InlineFragment(ref inline) => {
+                let parent = match inline.type_condition {
+                    Some(ref cond) => Some(cond),
+                    None => info.parent_type(),
+                };
+                visitor.enter_inline_fragment(inline, info);
+                walk_directives(visitor, &inline.directives, info);
+                info.parent_types.push(parent);
+                walk_selections(visitor, &inline.selections, info);
+                info.parent_types.pop();
+                visitor.leave_inline_fragment(inline, info);
+            }
+        }
+    }
+}
+
+fn walk_field<'a, V: Visitor<'a>>(visitor: &mut V, field: &'a Field, info: &mut TypeInfo<'a>) {
+    let def = info.find_field(&field.name);
+    info.field_defs.push(def);
+    info.path.push(field.response_key());
+    visitor.enter_field(field, info);
+
+    for (name, value) in &field.args {
+        info.argument = Some(name);
+        visitor.visit_argument(name, value, info);
+    }
+    info.argument = None;
+    walk_directives(visitor, &field.directives, info);
+
+    // Selections on an array apply to each of its elements.
+    info.parent_types.push(def.and_then(|d| d.ty.named_type()));
+    walk_selections(visitor, &field.selections, info);
+    info.parent_types.pop();
+
+    visitor.leave_field(field, info);
+    info.path.pop();
+    info.field_defs.pop();
+}
+
+fn walk_directives<'a, V: Visitor<'a>>(visitor: &mut V, directives: &'a [Directive], info: &mut TypeInfo<'a>) {
+    for d in directives {
+        info.directive = Some(&d.name);
+        visitor.enter_directive(d, info);
+        for (name, value) in &d.args {
+            info.argument = Some(name);
+            visitor.visit_argument(name, value, info);
+        }
+        info.argument = None;
+        visitor.leave_directive(d, info);
+        info.directive = None;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parser::parse_idl::parse_schema;
+    use parser::parse_query::parse_query;
+
+    // Records each hook as it is called, along with the type info.
+    struct Recorder(Vec<String>);
+
+    impl<'a> Visitor<'a> for Recorder {
+        fn enter_operation(&mut self, op: &'a Operation, info: &TypeInfo<'a>) {
+            self.0.push(format!("operation {} on {}", op.field.name, info.parent_type().unwrap()));
+        }
+        fn enter_fragment(&mut self, f: &'a Fragment, info: &TypeInfo<'a>) {
+            self.0.push(format!("fragment {} on {}", f.name, info.parent_type().unwrap()));
+        }
+        fn enter_field(&mut self, field: &'a Field, info: &TypeInfo<'a>) {
+            let path: Vec<_> = info.path().iter().map(|n| &*n.0).collect();
+            let ty = match info.field_def() {
+                Some(def) => def.ty.to_string(),
+                None => "?".to_owned(),
+            };
+            self.0.push(format!(
+                "field {}.{}: {} at {}",
+                info.parent_type().map_or("?", |n| &n.0),
+                field.name,
+                ty,
+                path.join("/")
+            ));
+        }
+        fn leave_field(&mut self, field: &'a Field, _info: &TypeInfo<'a>) {
+            self.0.push(format!("leave {}", field.name));
+        }
+        fn enter_inline_fragment(&mut self, _inline: &'a InlineFragment, info: &TypeInfo<'a>) {
+            self.0.push(format!("inline in {}", info.parent_type().unwrap()));
+        }
+        fn enter_fragment_spread(&mut self, spread: &'a FragmentSpread, _info: &TypeInfo<'a>) {
+            self.0.push(format!("spread {}", spread.name));
+        }
+        fn enter_directive(&mut self, d: &'a Directive, info: &TypeInfo<'a>) {
+            self.0.push(format!("directive {} found {}", d.name, info.directive_def().is_some()));
+        }
+        fn visit_argument(&mut self, name: &'a Name, _value: &'a Value, info: &TypeInfo<'a>) {
+            let ty = info.argument_def().map(|a| a.ty.to_string());
+            self.0.push(format!("arg {}: {}", name, ty.unwrap_or("?".to_owned())));
+        }
+    }
+
+    #[test]
+    fn test_walk_document() {
+        let schema = parse_schema(
+            "schema { query: Query }
+            type Query { hero(episode: Int): Character }
+            interface Character { name: String, friends: [Character] }
+            type Human implements Character { name: String, friends: [Character], height: Float }",
+        ).unwrap();
+        let doc = parse_query(
+            "{
+                hero(episode: 4) {
+                    name @include(if: true)
+                    friends { ...F }
+                    ... on Human { height, unknown }
+                }
+            }
+            fragment F on Character { who: name }",
+        ).unwrap();
+
+        let mut recorder = Recorder(vec![]);
+        walk_document(&mut recorder, &doc, &schema);
+        assert_eq!(
+            recorder.0,
+            vec![
+                "operation query on Query",
+                "field Query.hero: Character at hero",
+                "arg episode: Int",
+                "field Character.name: String at hero/name",
+                "directive include found true",
+                "arg if: Boolean!",
+                "leave name",
+                "field Character.friends: [Character] at hero/friends",
+                "spread F",
+                "leave friends",
+                "inline in Character",
+                "field Human.height: Float at hero/height",
+                "leave height",
+                "field Human.unknown: ? at hero/unknown",
+                "leave unknown",
+                "leave hero",
+                "fragment F on Character",
+                "field Character.name: String at who",
+                "leave name",
+            ]
+        );
+    }
+}